@@ -0,0 +1,13 @@
+use std::process::Command;
+
+fn main() {
+    // the commit being built, for the header of the output files (see `context`)
+    let hash = Command::new("git").args(["rev-parse", "--short", "HEAD"]).output();
+    if let Ok(output) = hash {
+        if output.status.success() {
+            println!("cargo:rustc-env=REPCALC_GIT_HASH={}", String::from_utf8_lossy(&output.stdout).trim());
+        }
+    }
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+}
@@ -0,0 +1,26 @@
+//! Prints the trace of the word of every slope p/q with p, q <= 8 at the point mu = 2i
+//! of the Maskit slice, by the trace recursion down the Farey tree, and checks each one
+//! against the matrix of its Stern-Brocot word.
+//!
+//! cargo run --example farey_traces
+
+use rug::{Complex, Float};
+use repcalc::budget::IterationLimit;
+use repcalc::classify::tolerance;
+use repcalc::rho::{self, Generators};
+use repcalc::stern_brocot::{for_each_farey, stern_brocot_word};
+
+fn main() {
+    let precision = 128;
+    let (a, b) = rho::maskit(precision, Complex::with_val(precision, (0, 2)));
+    let gens = Generators::from_matrices(precision, a, b);
+    let ab = gens.a.clone().mul(gens.b.clone());
+
+    for_each_farey(8, gens.a.trace(), gens.b.trace(), ab.trace(), |u, v, w| u.clone() * v - w, |q, trace| {
+        let m = stern_brocot_word(q.clone(), gens.a.clone(), gens.b.clone(), IterationLimit::unlimited())
+            .expect("no limit");
+        let error = Float::with_val(precision, (m.trace() - trace).abs_ref());
+        assert!(error <= tolerance(precision), "the recursion is off by {} at {}", error, q);
+        println!("{} {}", q, trace);
+    });
+}
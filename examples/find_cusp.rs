@@ -0,0 +1,29 @@
+//! Finds the cusp of slope 1/2 on the boundary of the Maskit slice: a parameter mu
+//! where the Stern-Brocot word of 1/2 is parabolic with trace 2, by Newton's method
+//! from a point just outside the slice. The derivative of the trace in mu is exact,
+//! from evaluating the word on dual numbers.
+//!
+//! cargo run --example find_cusp
+
+use rug::{Complex, Rational};
+use repcalc::budget::IterationLimit;
+use repcalc::dual;
+use repcalc::scalar;
+use repcalc::stern_brocot::{ExtendedRational, stern_brocot_letters};
+
+fn main() {
+    let precision = 128;
+    let slope = ExtendedRational::R(Rational::from((1, 2)));
+    let word = stern_brocot_letters(slope.clone(), IterationLimit::unlimited()).expect("no limit");
+
+    let start = Complex::with_val(precision, (1, 1.8));
+    let target = Complex::with_val(precision, 2);
+    let solution = dual::solve_trace(scalar::maskit, precision, &word, &target, start, 50).expect("a word in a and b");
+    assert!(solution.converged, "Newton's method did not converge: residual {}", solution.residual);
+
+    println!("slope = {}", slope);
+    println!("word = {}", word);
+    println!("cusp = {}", solution.z);
+    println!("trace = {}", solution.trace);
+    println!("iterations = {}", solution.iterations);
+}
@@ -0,0 +1,35 @@
+//! Renders the limit set of the rho_a, rho_b representation at z = 1.5 + 0.3i to a PNG,
+//! from the attracting fixed points of all reduced words up to length 10.
+//!
+//! cargo run --release --example limit_set -- limit_set.png
+
+use std::fs::File;
+use std::io::BufWriter;
+
+use rug::Complex;
+use repcalc::budget::Budget;
+use repcalc::density::{Density, Window};
+use repcalc::limit_set::for_each_limit_point;
+use repcalc::rho::Generators;
+use repcalc::shard::Shard;
+
+fn main() {
+    let path = std::env::args().nth(1).unwrap_or_else(|| "limit_set.png".to_string());
+    let precision = 64;
+    let gens = Generators::new(precision, Complex::with_val(precision, (1.5, 0.3)));
+
+    let mut points = Vec::new();
+    let everything = Shard { index: 0, count: 1 };
+    for_each_limit_point(&gens, 10, everything, &mut Budget::unlimited(), |_, point| {
+        points.push((point.real().to_f64(), point.imag().to_f64()));
+    });
+
+    let window = Window::around(&points).expect("the limit set has points");
+    let mut density = Density::new(window, 800);
+    for &(x, y) in &points {
+        density.add(x, y);
+    }
+    let file = BufWriter::new(File::create(&path).expect("could not create the PNG"));
+    density.write_png(file).expect("could not write the PNG");
+    println!("{} points written to {}", points.len(), path);
+}
@@ -0,0 +1,263 @@
+use std::fmt;
+use rug::{Complex, Float};
+
+use crate::error::Error;
+use crate::matrix::{C, M};
+use crate::scalar;
+
+/// Precision of the radii. Radii are only ever rounded up, so they stay valid
+/// upper bounds however coarse they are.
+pub const RAD_PREC: u32 = 53;
+
+/// A closed complex ball: the set of points within `rad` of `mid`. Every operation
+/// returns a ball containing all results of the operation on points of its inputs,
+/// accounting for the rounding of the midpoint at its precision.
+#[derive(Clone)]
+pub struct Ball {
+    pub mid: C,
+    pub rad: Float,
+}
+
+impl fmt::Display for Ball {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} +/- {}", self.mid, self.rad)
+    }
+}
+
+fn up(mut x: Float) -> Float {
+    x.next_up();
+    x
+}
+
+fn down(mut x: Float) -> Float {
+    x.next_down();
+    x
+}
+
+pub fn add_up(a: &Float, b: &Float) -> Float {
+    up(Float::with_val(RAD_PREC, a + b))
+}
+
+pub fn mul_up(a: &Float, b: &Float) -> Float {
+    up(Float::with_val(RAD_PREC, a * b))
+}
+
+pub fn div_up(a: &Float, b: &Float) -> Float {
+    up(Float::with_val(RAD_PREC, a / b))
+}
+
+pub fn sub_down(a: &Float, b: &Float) -> Float {
+    down(Float::with_val(RAD_PREC, a - b))
+}
+
+pub fn abs_up(x: &C) -> Float {
+    up(Float::with_val(RAD_PREC, x.abs_ref()))
+}
+
+pub fn abs_down(x: &C) -> Float {
+    let lower = down(Float::with_val(RAD_PREC, x.abs_ref()));
+    if lower < 0 { Float::with_val(RAD_PREC, 0) } else { lower }
+}
+
+/// A bound for the error of rounding the exact result to `mid`: each part is
+/// correctly rounded, so the error is at most 2^(1 - prec) |mid|.
+fn rounding(mid: &C) -> Float {
+    let eps = Float::with_val(RAD_PREC, Float::u_exp(1, 1 - mid.prec().0 as i32));
+    mul_up(&abs_up(mid), &eps)
+}
+
+impl Ball {
+    pub fn exact(mid: C) -> Self {
+        Ball { mid, rad: Float::with_val(RAD_PREC, 0) }
+    }
+
+    /// A ball around a correctly rounded value, e.g. one produced by `with_val`.
+    pub fn rounded(mid: C) -> Self {
+        let rad = rounding(&mid);
+        Ball { mid, rad }
+    }
+
+    fn with_rounding(mid: C, rad: Float) -> Self {
+        let rad = add_up(&rad, &rounding(&mid));
+        Ball { mid, rad }
+    }
+
+    /// Upper bound for |x| over the ball.
+    pub fn abs_upper(&self) -> Float {
+        add_up(&abs_up(&self.mid), &self.rad)
+    }
+
+    /// Lower bound for |x| over the ball, zero if the ball contains zero.
+    pub fn abs_lower(&self) -> Float {
+        let lower = sub_down(&abs_down(&self.mid), &self.rad);
+        if lower < 0 { Float::with_val(RAD_PREC, 0) } else { lower }
+    }
+
+    pub fn add(&self, other: &Ball) -> Ball {
+        Ball::with_rounding(self.mid.clone() + &other.mid, add_up(&self.rad, &other.rad))
+    }
+
+    pub fn sub(&self, other: &Ball) -> Ball {
+        Ball::with_rounding(self.mid.clone() - &other.mid, add_up(&self.rad, &other.rad))
+    }
+
+    pub fn conj(&self) -> Ball {
+        Ball { mid: self.mid.clone().conj(), rad: self.rad.clone() }
+    }
+
+    pub fn neg(&self) -> Ball {
+        Ball { mid: -self.mid.clone(), rad: self.rad.clone() }
+    }
+
+    pub fn mul(&self, other: &Ball) -> Ball {
+        // |xy - mn| <= |m| r' + |n| r + r r'
+        let rad = add_up(
+            &add_up(&mul_up(&abs_up(&self.mid), &other.rad), &mul_up(&abs_up(&other.mid), &self.rad)),
+            &mul_up(&self.rad, &other.rad),
+        );
+        Ball::with_rounding(self.mid.clone() * &other.mid, rad)
+    }
+
+    /// `None` if the ball contains zero.
+    pub fn recip(&self) -> Option<Ball> {
+        // |1/x - 1/m| = |x - m| / (|x| |m|) <= r / ((|m| - r) |m|)
+        let lower = self.abs_lower();
+        if lower.is_zero() {
+            return None;
+        }
+        let denominator = down(Float::with_val(RAD_PREC, &lower * &abs_down(&self.mid)));
+        let rad = div_up(&self.rad, &denominator);
+        Some(Ball::with_rounding(self.mid.clone().recip(), rad))
+    }
+
+    pub fn div(&self, other: &Ball) -> Option<Ball> {
+        Some(self.mul(&other.recip()?))
+    }
+
+    /// The principal square root; `None` if the ball meets the branch cut (-inf, 0].
+    pub fn sqrt(&self) -> Option<Ball> {
+        let re = self.mid.real();
+        let im = self.mid.imag();
+        if *re <= self.rad && im.clone().abs() <= self.rad {
+            return None;
+        }
+        // away from the cut sqrt(x) and sqrt(m) are at most a quarter turn apart,
+        // so |sqrt(x) + sqrt(m)| >= sqrt(|m|) and |sqrt(x) - sqrt(m)| <= r / sqrt(|m|)
+        let root = down(abs_down(&self.mid).sqrt());
+        if root.is_zero() {
+            return None;
+        }
+        let rad = div_up(&self.rad, &root);
+        Some(Ball::with_rounding(self.mid.clone().sqrt(), rad))
+    }
+}
+
+impl M<Ball> {
+    pub fn mul_ball(&self, other: &Self) -> Self {
+        let [a1, b1, c1, d1] = &self.0;
+        let [a2, b2, c2, d2] = &other.0;
+        M([
+            a1.mul(a2).add(&b1.mul(c2)),
+            a1.mul(b2).add(&b1.mul(d2)),
+            c1.mul(a2).add(&d1.mul(c2)),
+            c1.mul(b2).add(&d1.mul(d2)),
+        ])
+    }
+
+    /// The inverse of a matrix whose exact determinant is 1.
+    pub fn inv_sl2(&self) -> Self {
+        let [a, b, c, d] = &self.0;
+        M([d.clone(), b.neg(), c.neg(), a.clone()])
+    }
+}
+
+/// Balls around the exact generators `rho_a(z)`, `rho_b(z)` for z in the ball `z`,
+/// or `None` if z is too close to a branch point of the square roots.
+pub fn rho(precision: u32, z: &Ball) -> Option<(M<Ball>, M<Ball>)> {
+    let one = Ball::exact(Complex::with_val(precision, 1));
+    let i = Ball::exact(Complex::with_val(precision, (0, 1)));
+
+    let root = z.mul(z).sub(&one).sqrt()?;
+    let c = root.recip()?;
+    let cz = c.mul(z);
+    let a = M([cz.clone(), c.clone(), c, cz]);
+
+    let y = z.neg().div(&root)?;
+    let c = y.mul(&y).sub(&one).sqrt()?.recip()?;
+    let cy = c.mul(&y);
+    let ci = c.mul(&i);
+    let b = M([cy.clone(), ci.clone(), ci.neg(), cy]);
+
+    Some((a, b))
+}
+
+/// Balls around `rho::maskit(mu)` for mu in the ball `mu`.
+pub fn maskit(precision: u32, mu: &Ball) -> (M<Ball>, M<Ball>) {
+    let exact = |re: i32, im: i32| Ball::exact(Complex::with_val(precision, (re, im)));
+    let minus_i = exact(0, -1);
+    let a = M([minus_i.mul(mu), minus_i.clone(), minus_i, exact(0, 0)]);
+    let b = M([exact(1, 0), exact(2, 0), exact(0, 0), exact(1, 0)]);
+    (a, b)
+}
+
+/// Balls around `rho::riley(rho)` for rho in the ball `rho`.
+pub fn riley(precision: u32, rho: &Ball) -> (M<Ball>, M<Ball>) {
+    let exact = |re: i32| Ball::exact(Complex::with_val(precision, re));
+    let a = M([exact(1), exact(1), exact(0), exact(1)]);
+    let b = M([exact(1), exact(0), rho.clone(), exact(1)]);
+    (a, b)
+}
+
+/// The matrix of a word in {a,b,A,B} as balls; the empty word evaluates to the identity.
+pub fn eval_word(precision: u32, a: &M<Ball>, b: &M<Ball>, word: &str) -> Result<M<Ball>, Error> {
+    let (a_inv, b_inv) = (a.inv_sl2(), b.inv_sl2());
+    let one = Ball::exact(Complex::with_val(precision, 1));
+    let zero = Ball::exact(Complex::with_val(precision, 0));
+    word.chars().try_fold(M([one.clone(), zero.clone(), zero, one]), |m, c| {
+        let letter = match c {
+            'a' => a,
+            'b' => b,
+            'A' => &a_inv,
+            'B' => &b_inv,
+            _ => return Err(scalar::not_a_letter(c)),
+        };
+        Ok(m.mul_ball(letter))
+    })
+}
+
+/// True if no point of the ball is 2 or -2, so that no matrix with its trace in
+/// the ball is parabolic or +/- the identity.
+pub fn avoids_pm2(trace: &Ball) -> bool {
+    let two = Ball::exact(Complex::with_val(trace.mid.prec(), 2));
+    !trace.sub(&two).abs_lower().is_zero() && !trace.add(&two).abs_lower().is_zero()
+}
+
+/// True if the ball misses the segment [-2, 2], so that every matrix with its trace
+/// in the ball is loxodromic.
+pub fn avoids_segment(trace: &Ball) -> bool {
+    // a lower bound for the distance from the midpoint to the segment
+    let re = down(Float::with_val(RAD_PREC, trace.mid.real().abs_ref()));
+    let im = down(Float::with_val(RAD_PREC, trace.mid.imag().abs_ref()));
+    let dx = sub_down(&re, &Float::with_val(RAD_PREC, 2));
+    let dx = if dx < 0 { Float::with_val(RAD_PREC, 0) } else { dx };
+    let square = |x: &Float| down(Float::with_val(RAD_PREC, x * x));
+    let distance = down(down(Float::with_val(RAD_PREC, square(&dx) + square(&im))).sqrt());
+    distance > trace.rad
+}
+
+/// A ball containing an eigenvalue (t + s) / 2 of every matrix of determinant 1 with
+/// trace t in the ball, where s is the square root of t^2 - 4 continuing the one at the
+/// midpoint; of the two, the one with the larger midpoint is returned. `None` if t^2 - 4
+/// is too close to 0 for the square root to be continued.
+pub fn eigenvalue(trace: &Ball) -> Option<Ball> {
+    let precision = trace.mid.prec().0;
+    let four = Ball::exact(Complex::with_val(precision, 4));
+    let half = Ball::exact(Complex::with_val(precision, 0.5));
+    let i = Ball::exact(Complex::with_val(precision, (0, 1)));
+    let discriminant = trace.mul(trace).sub(&four);
+    // off the cut the principal root is continuous; on it, i sqrt(4 - t^2) is
+    let root = discriminant.sqrt().or_else(|| Some(discriminant.neg().sqrt()?.mul(&i)))?;
+    let plus = trace.add(&root).mul(&half);
+    let minus = trace.sub(&root).mul(&half);
+    Some(if abs_down(&plus.mid) >= abs_down(&minus.mid) { plus } else { minus })
+}
@@ -0,0 +1,99 @@
+/// One parameter of a batch of runs and the values it takes, as text to be parsed
+/// like the corresponding command line option.
+#[derive(Clone, Debug)]
+pub struct Axis {
+    pub key: String,
+    pub values: Vec<String>,
+}
+
+/// Most points a single range may expand to.
+const MAX_RANGE: usize = 1_000_000;
+
+/// Parses a sweep specification such as `z.re=0:2:0.01; precision=128,256`: axes
+/// separated by `;` or newlines, each `key=values` where the values are a single
+/// value, a comma separated list, or a range `start:stop:step` of decimal numbers
+/// that includes `stop` when the steps land on it. Lines starting with `#` are
+/// comments.
+pub fn parse_spec(spec: &str) -> Result<Vec<Axis>, String> {
+    let mut axes = Vec::new();
+    for part in spec.lines().filter(|l| !l.trim_start().starts_with('#')).flat_map(|l| l.split(';')) {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+        let (key, values) = part.split_once('=').ok_or_else(|| format!("expected key=values in {}", part))?;
+        let (key, values) = (key.trim(), values.trim());
+        let values = match values.split(':').collect::<Vec<_>>()[..] {
+            [start, stop, step] => decimal_range(start.trim(), stop.trim(), step.trim())?,
+            [_] => values.split(',').map(|v| v.trim().to_string()).collect(),
+            _ => return Err(format!("expected start:stop:step in {}", values)),
+        };
+        if values.is_empty() || values.iter().any(|v| v.is_empty()) {
+            return Err(format!("missing value for {}", key));
+        }
+        if axes.iter().any(|a: &Axis| a.key == key) {
+            return Err(format!("{} is given twice", key));
+        }
+        axes.push(Axis { key: key.to_string(), values });
+    }
+    Ok(axes)
+}
+
+/// Every combination of one value from each axis, in order, the last axis varying
+/// fastest. No axes give a single empty combination.
+pub fn combinations(axes: &[Axis]) -> Vec<Vec<(&str, &str)>> {
+    let mut out = vec![vec![]];
+    for axis in axes {
+        out = out.into_iter()
+            .flat_map(|prefix: Vec<(&str, &str)>| axis.values.iter().map(move |v| {
+                let mut point = prefix.clone();
+                point.push((axis.key.as_str(), v.as_str()));
+                point
+            }))
+            .collect();
+    }
+    out
+}
+
+/// A decimal number as an integer and the number of digits after the point.
+fn parse_decimal(s: &str) -> Option<(i128, u32)> {
+    let (sign, digits) = match s.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+    if int.is_empty() && frac.is_empty() || !(int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+    let mantissa: i128 = format!("{}{}", int, frac).parse().ok()?;
+    Some((sign * mantissa, frac.len() as u32))
+}
+
+fn format_decimal(x: i128, scale: u32) -> String {
+    let unit = 10i128.pow(scale);
+    let sign = if x < 0 { "-" } else { "" };
+    let (int, frac) = (x.abs() / unit, x.abs() % unit);
+    if frac == 0 {
+        return format!("{}{}", sign, int);
+    }
+    let frac = format!("{:0width$}", frac, width = scale as usize);
+    format!("{}{}.{}", sign, int, frac.trim_end_matches('0'))
+}
+
+/// The values start, start + step, ... up to stop, computed exactly in decimal.
+fn decimal_range(start: &str, stop: &str, step: &str) -> Result<Vec<String>, String> {
+    let parse = |s: &str| parse_decimal(s).ok_or_else(|| format!("invalid decimal number {}", s));
+    let (start, stop, step) = (parse(start)?, parse(stop)?, parse(step)?);
+    let scale = start.1.max(stop.1).max(step.1);
+    let overflow = || "range bounds are too large".to_string();
+    let rescale = |(x, s): (i128, u32)| 10i128.checked_pow(scale - s).and_then(|f| x.checked_mul(f)).ok_or_else(overflow);
+    let (start, stop, step) = (rescale(start)?, rescale(stop)?, rescale(step)?);
+    if step == 0 || (stop - start).signum() * step.signum() < 0 {
+        return Err("the step must be nonzero and go from start towards stop".to_string());
+    }
+    let count = (stop - start) / step + 1;
+    if count > MAX_RANGE as i128 {
+        return Err(format!("the range has more than {} values", MAX_RANGE));
+    }
+    Ok((0..count).map(|k| format_decimal(start + k * step, scale)).collect())
+}
@@ -0,0 +1,195 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// A code path timed by the bench subcommand. All three evaluate the Stern-Brocot word
+/// of the same slope, so they compute the same trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Routine {
+    /// `Generators::eval` of the letters of the word: the runs of a letter raised to
+    /// their powers by squaring, and the runs multiplied as a balanced tree.
+    Multiply,
+    /// `stern_brocot_word`: the descent down the Stern-Brocot tree to the slope, one
+    /// matrix product per step.
+    SternBrocot,
+    /// `stern_brocot_trace`: the trace recursion along the same descent, one complex
+    /// multiplication per step.
+    TraceRecursion,
+}
+
+impl Routine {
+    pub const ALL: [Routine; 3] = [Routine::Multiply, Routine::SternBrocot, Routine::TraceRecursion];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Routine::Multiply => "multiply",
+            Routine::SternBrocot => "stern-brocot",
+            Routine::TraceRecursion => "trace-recursion",
+        }
+    }
+}
+
+impl FromStr for Routine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        Routine::ALL.into_iter().find(|routine| routine.name() == s)
+            .ok_or_else(|| format!("unknown routine {} (expected multiply, stern-brocot or trace-recursion)", s))
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// The slope p/q whose Stern-Brocot word has `length` = p + q letters and the shortest
+/// descent: p is the integer prime to `length` nearest to length / phi^2, so that the
+/// partial quotients of p/q are small, like those of the golden ratio. `None` below 3
+/// letters, where only 1/1 is left and its word is a.
+pub fn slope(length: u64) -> Option<(u64, u64)> {
+    if length < 3 {
+        return None;
+    }
+    let target = length as f64 * 2.0 / (3.0 + 5f64.sqrt());
+    (1..length).filter(|&p| gcd(p, length) == 1)
+        .min_by(|&x, &y| (x as f64 - target).abs().total_cmp(&(y as f64 - target).abs()))
+        .map(|p| (p, length - p))
+}
+
+/// The steps of the descent down the Stern-Brocot tree to p/q, the sum of the partial
+/// quotients of its continued fraction, as in `stern_brocot_word`.
+pub fn descent_steps(p: u64, q: u64) -> u64 {
+    match p.checked_div(q) {
+        Some(quotient) => quotient + descent_steps(q, p % q),
+        None => 0,
+    }
+}
+
+/// The matrix products `Generators::eval` takes for a word: a run of n letters takes
+/// floor(log2 n) squarings and one product for each binary digit 1 of n, and the runs
+/// take one fewer product than there are of them.
+pub fn eval_products(word: &str) -> u64 {
+    let mut chars = word.chars().peekable();
+    let mut runs = 0u64;
+    let mut products = 0u64;
+    while let Some(c) = chars.next() {
+        let mut n = 1u64;
+        while chars.next_if_eq(&c).is_some() {
+            n += 1;
+        }
+        runs += 1;
+        products += n.ilog2() as u64 + n.count_ones() as u64;
+    }
+    products + runs.saturating_sub(1)
+}
+
+/// How long to run a routine before measuring it, how long to measure it for, and in
+/// how many samples, at least one.
+#[derive(Clone, Copy, Debug)]
+pub struct Settings {
+    pub warm_up: Duration,
+    pub measurement: Duration,
+    pub sample_size: usize,
+}
+
+/// The times of one routine: each sample runs it `iterations` times, and its time is
+/// divided by them.
+pub struct Measurement {
+    pub iterations: u64,
+    /// Seconds per iteration, one for each sample.
+    pub times: Vec<f64>,
+}
+
+/// Times `routine` in the manner of criterion. It runs for the warm-up time first, with
+/// doubling counts of iterations, which also estimates its time. The iterations per
+/// sample are then chosen so that the samples together take about the measurement time,
+/// and at least one.
+///
+/// Panics if `settings.sample_size` is 0.
+pub fn measure(settings: &Settings, mut routine: impl FnMut()) -> Measurement {
+    let start = Instant::now();
+    let (mut runs, mut batch) = (0u64, 1u64);
+    while start.elapsed() < settings.warm_up || runs == 0 {
+        for _ in 0..batch {
+            routine();
+        }
+        runs += batch;
+        batch *= 2;
+    }
+    let per_iteration = start.elapsed().as_secs_f64() / runs as f64;
+    let sample_size = settings.sample_size;
+    assert!(sample_size > 0, "a measurement needs at least one sample");
+    let per_sample = settings.measurement.as_secs_f64() / sample_size as f64;
+    let iterations = ((per_sample / per_iteration) as u64).max(1);
+    let times = (0..sample_size)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                routine();
+            }
+            start.elapsed().as_secs_f64() / iterations as f64
+        })
+        .collect();
+    Measurement { iterations, times }
+}
+
+/// The statistics criterion reports for a list of times.
+pub struct Statistics {
+    pub samples: usize,
+    pub mean: f64,
+    /// A 95% confidence interval for the mean, from the normal approximation.
+    pub mean_interval: (f64, f64),
+    /// The sample standard deviation.
+    pub std_dev: f64,
+    pub median: f64,
+    /// The median absolute deviation from the median, times 1.4826 so that it estimates
+    /// the standard deviation of a normal distribution while ignoring outliers.
+    pub mad: f64,
+    pub min: f64,
+    pub max: f64,
+    /// The times outside the inner Tukey fences (1.5 interquartile ranges beyond the
+    /// quartiles) but within the outer ones (3 ranges).
+    pub mild_outliers: usize,
+    /// The times outside the outer Tukey fences.
+    pub severe_outliers: usize,
+}
+
+/// The `p`-quantile of sorted values, interpolating linearly between them.
+fn quantile(sorted: &[f64], p: f64) -> f64 {
+    let position = p * (sorted.len() - 1) as f64;
+    let (below, above) = (position.floor() as usize, position.ceil() as usize);
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
+
+/// The statistics of a nonempty list of times.
+pub fn statistics(times: &[f64]) -> Statistics {
+    let n = times.len();
+    let mut sorted = times.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mean = times.iter().sum::<f64>() / n as f64;
+    let variance = if n > 1 {
+        times.iter().map(|t| (t - mean) * (t - mean)).sum::<f64>() / (n - 1) as f64
+    } else {
+        0.0
+    };
+    let std_dev = variance.sqrt();
+    let half_width = 1.96 * std_dev / (n as f64).sqrt();
+    let median = quantile(&sorted, 0.5);
+    let mut deviations: Vec<f64> = sorted.iter().map(|t| (t - median).abs()).collect();
+    deviations.sort_by(f64::total_cmp);
+    let (q1, q3) = (quantile(&sorted, 0.25), quantile(&sorted, 0.75));
+    let iqr = q3 - q1;
+    let outside = |k: f64| sorted.iter().filter(|&&t| t < q1 - k * iqr || t > q3 + k * iqr).count();
+    let severe_outliers = outside(3.0);
+    Statistics {
+        samples: n,
+        mean,
+        mean_interval: (mean - half_width, mean + half_width),
+        std_dev,
+        median,
+        mad: 1.4826 * quantile(&deviations, 0.5),
+        min: sorted[0],
+        max: sorted[n - 1],
+        mild_outliers: outside(1.5) - severe_outliers,
+        severe_outliers,
+    }
+}
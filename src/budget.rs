@@ -0,0 +1,94 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Why a search stopped before it was finished.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exhausted {
+    TimeLimit,
+    NodeBudget,
+}
+
+impl fmt::Display for Exhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Exhausted::TimeLimit => write!(f, "time_limit"),
+            Exhausted::NodeBudget => write!(f, "node_budget"),
+        }
+    }
+}
+
+/// A limit on the wall-clock time and the number of nodes (words evaluated) that
+/// the searches may use. Once it runs out it stays exhausted, and searches return
+/// what they have found so far.
+pub struct Budget {
+    deadline: Option<Instant>,
+    max_nodes: Option<u64>,
+    nodes: u64,
+    exhausted: Option<Exhausted>,
+}
+
+impl Budget {
+    pub fn new(time_limit: Option<Duration>, max_nodes: Option<u64>) -> Self {
+        Budget { deadline: time_limit.map(|t| Instant::now() + t), max_nodes, nodes: 0, exhausted: None }
+    }
+
+    pub fn unlimited() -> Self {
+        Budget::new(None, None)
+    }
+
+    /// Accounts for one more node, returning false if the budget has run out.
+    pub fn spend(&mut self) -> bool {
+        if self.exhausted.is_some() {
+            return false;
+        }
+        if self.max_nodes.is_some_and(|max| self.nodes >= max) {
+            self.exhausted = Some(Exhausted::NodeBudget);
+        } else if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            self.exhausted = Some(Exhausted::TimeLimit);
+        } else {
+            self.nodes += 1;
+        }
+        self.exhausted.is_none()
+    }
+
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    pub fn exhausted(&self) -> Option<Exhausted> {
+        self.exhausted
+    }
+}
+
+/// A cap on the number of steps of an iterative algorithm, so that an adversarial
+/// input (such as a slope with a huge partial quotient) fails with an error instead
+/// of running practically forever. `None` means no cap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IterationLimit(pub Option<u64>);
+
+/// The error of an algorithm that used up its `IterationLimit`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LimitReached {
+    pub algorithm: &'static str,
+    pub iterations: u64,
+}
+
+impl fmt::Display for LimitReached {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} stopped after {} iterations", self.algorithm, self.iterations)
+    }
+}
+
+impl IterationLimit {
+    pub fn unlimited() -> Self {
+        IterationLimit(None)
+    }
+
+    /// Counts `iterations` steps of `algorithm` so far against the limit.
+    pub fn check(&self, algorithm: &'static str, iterations: u64) -> Result<(), LimitReached> {
+        match self.0 {
+            Some(max) if iterations > max => Err(LimitReached { algorithm, iterations: max }),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+use rug::Float;
+
+use crate::matrix::{C, M};
+
+/// A computer algebra system (or LaTeX) to print results for, as code or markup that can
+/// be pasted into it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Mathematica,
+    Sage,
+    /// NumPy arrays of mpmath numbers, which keep the working precision.
+    Numpy,
+    Latex,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "mathematica" => Ok(Format::Mathematica),
+            "sage" => Ok(Format::Sage),
+            "numpy" => Ok(Format::Numpy),
+            "latex" => Ok(Format::Latex),
+            _ => Err(format!("unknown format {} (expected mathematica, sage, numpy or latex)", s)),
+        }
+    }
+}
+
+pub enum Value {
+    Number(C),
+    /// A column vector.
+    Vector([C; 2]),
+    Matrix(M<C>),
+}
+
+/// A named value: `name` is an identifier in every system, `symbol` its LaTeX.
+pub struct Entry {
+    pub name: &'static str,
+    pub symbol: &'static str,
+    pub value: Value,
+}
+
+/// The decimal digits of x, all of them at its precision, and its decimal exponent if it
+/// has one, as ("1.25", Some(-7)) for 1.25e-7.
+fn digits(x: &Float) -> (String, Option<i32>) {
+    let s = x.to_string_radix(10, None);
+    match s.split_once('e') {
+        Some((mantissa, exponent)) => (mantissa.to_string(), exponent.parse().ok()),
+        None => (s, None),
+    }
+}
+
+/// A real number in the syntax of `format`. Mathematica gets a precision mark, so that
+/// the number is read with the working precision rather than as a machine number; Sage
+/// and mpmath read it from a string at the precision set at the top of the output.
+fn real(format: Format, x: &Float) -> String {
+    let (mantissa, exponent) = digits(x);
+    match format {
+        Format::Mathematica => {
+            let precision = x.prec() as f64 * std::f64::consts::LOG10_2;
+            let exponent = exponent.map_or(String::new(), |e| format!("*^{}", e));
+            format!("{}`{:.1}{}", mantissa, precision, exponent)
+        }
+        Format::Sage | Format::Numpy => {
+            let exponent = exponent.map_or(String::new(), |e| format!("e{}", e));
+            format!("\"{}{}\"", mantissa, exponent)
+        }
+        Format::Latex => match exponent {
+            Some(e) => format!("{} \\times 10^{{{}}}", mantissa, e),
+            None => mantissa,
+        },
+    }
+}
+
+fn complex(format: Format, z: &C) -> String {
+    let (re, im) = (real(format, z.real()), real(format, z.imag()));
+    match format {
+        Format::Mathematica => format!("Complex[{}, {}]", re, im),
+        Format::Sage => format!("K({}, {})", re, im),
+        Format::Numpy => format!("mpc({}, {})", re, im),
+        Format::Latex => match im.strip_prefix('-') {
+            Some(im) => format!("{} - {}\\,i", re, im),
+            None => format!("{} + {}\\,i", re, im),
+        },
+    }
+}
+
+fn value(format: Format, v: &Value) -> String {
+    let list = |entries: Vec<String>| match format {
+        Format::Mathematica => format!("{{{}}}", entries.join(", ")),
+        _ => format!("[{}]", entries.join(", ")),
+    };
+    match (format, v) {
+        (_, Value::Number(z)) => complex(format, z),
+        (Format::Latex, Value::Vector([x, y])) =>
+            format!("\\begin{{pmatrix}} {} \\\\ {} \\end{{pmatrix}}", complex(format, x), complex(format, y)),
+        (Format::Latex, Value::Matrix(M([a, b, c, d]))) =>
+            format!("\\begin{{pmatrix}} {} & {} \\\\ {} & {} \\end{{pmatrix}}",
+                    complex(format, a), complex(format, b), complex(format, c), complex(format, d)),
+        (_, Value::Vector(v)) => {
+            let entries = list(v.iter().map(|x| complex(format, x)).collect());
+            match format {
+                Format::Sage => format!("vector(K, {})", entries),
+                Format::Numpy => format!("np.array({}, dtype=object)", entries),
+                _ => entries,
+            }
+        }
+        (_, Value::Matrix(m)) => {
+            let rows = m.0.chunks(2).map(|row| list(row.iter().map(|x| complex(format, x)).collect())).collect();
+            let rows = list(rows);
+            match format {
+                Format::Sage => format!("matrix(K, {})", rows),
+                Format::Numpy => format!("np.array({}, dtype=object)", rows),
+                _ => rows,
+            }
+        }
+    }
+}
+
+/// Writes the entries as assignments in `format`, with whatever the system needs first to
+/// read numbers of `precision` bits without rounding them to machine numbers, or as a
+/// LaTeX align* environment.
+pub fn write<W: Write>(out: &mut W, format: Format, precision: u32, entries: &[Entry]) -> io::Result<()> {
+    match format {
+        Format::Mathematica => {}
+        Format::Sage => writeln!(out, "K = ComplexField({})", precision)?,
+        Format::Numpy => {
+            writeln!(out, "import numpy as np")?;
+            writeln!(out, "from mpmath import mp, mpc")?;
+            writeln!(out, "mp.prec = {}", precision)?;
+        }
+        Format::Latex => writeln!(out, "\\begin{{align*}}")?,
+    }
+    for (k, entry) in entries.iter().enumerate() {
+        let v = value(format, &entry.value);
+        match format {
+            Format::Mathematica => writeln!(out, "{} = {};", entry.name, v)?,
+            Format::Sage | Format::Numpy => writeln!(out, "{} = {}", entry.name, v)?,
+            Format::Latex => {
+                let end = if k + 1 < entries.len() { " \\\\" } else { "" };
+                writeln!(out, "{} &= {}{}", entry.symbol, v, end)?
+            }
+        }
+    }
+    if format == Format::Latex {
+        writeln!(out, "\\end{{align*}}")?;
+    }
+    Ok(())
+}
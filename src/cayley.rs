@@ -0,0 +1,87 @@
+use std::io::{self, Write};
+
+use crate::budget::Budget;
+use crate::classify::{Classification, classify};
+use crate::context::RunContext;
+use crate::matrix::{C, M};
+use crate::mobius::fixed_points;
+use crate::rho::Generators;
+use crate::sweep::for_each_reduced_word;
+
+/// What to label the vertices of an exported Cayley graph with.
+#[derive(Clone, Copy)]
+pub enum Label {
+    Trace,
+    FixedPoints,
+}
+
+/// A vertex of the Cayley graph of F_2 with respect to {a, b}: a reduced word and
+/// the label of its matrix. The identity is the empty word.
+pub struct Vertex {
+    pub word: String,
+    pub label: String,
+}
+
+/// The vertices of the ball of the given radius about the identity, identity
+/// first. The edges join each nonempty word to the word without its last letter.
+pub fn ball(gens: &Generators, radius: usize, label: Label) -> Vec<Vertex> {
+    let precision = gens.precision;
+    let make_label = |m: &M<C>| match label {
+        Label::Trace => m.trace().to_string(),
+        Label::FixedPoints => match classify(m, precision) {
+            Classification::Identity => "all".to_string(),
+            Classification::Parabolic => fixed_points(m, precision)[0].to_string(),
+            _ => {
+                let [attracting, repelling] = fixed_points(m, precision);
+                format!("{} {}", attracting, repelling)
+            }
+        },
+    };
+    let mut vertices = vec![Vertex { word: String::new(), label: make_label(&M::identity(precision)) }];
+    for_each_reduced_word(gens, radius, &mut Budget::unlimited(), |word, m| {
+        vertices.push(Vertex { word: word.to_string(), label: make_label(m) });
+    });
+    vertices
+}
+
+fn name(word: &str) -> &str {
+    if word.is_empty() { "1" } else { word }
+}
+
+fn edges(vertices: &[Vertex]) -> impl Iterator<Item = (&str, &str, char)> {
+    vertices.iter().filter(|v| !v.word.is_empty()).map(|v| {
+        let (parent, letter) = v.word.split_at(v.word.len() - 1);
+        (name(parent), name(&v.word), letter.chars().next().unwrap())
+    })
+}
+
+pub fn write_dot<W: Write>(vertices: &[Vertex], out: &mut W, context: &RunContext) -> io::Result<()> {
+    context.write_comments(out)?;
+    writeln!(out, "graph cayley {{")?;
+    for v in vertices {
+        writeln!(out, r#"  "{}" [label="{}\n{}"];"#, name(&v.word), name(&v.word), v.label)?;
+    }
+    for (parent, child, letter) in edges(vertices) {
+        writeln!(out, r#"  "{}" -- "{}" [label="{}"];"#, parent, child, letter)?;
+    }
+    writeln!(out, "}}")
+}
+
+pub fn write_graphml<W: Write>(vertices: &[Vertex], out: &mut W, context: &RunContext) -> io::Result<()> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    context.write_xml_comment(out)?;
+    writeln!(out, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+    writeln!(out, r#"  <key id="word" for="node" attr.name="word" attr.type="string"/>"#)?;
+    writeln!(out, r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#)?;
+    writeln!(out, r#"  <key id="letter" for="edge" attr.name="letter" attr.type="string"/>"#)?;
+    writeln!(out, r#"  <graph id="cayley" edgedefault="undirected">"#)?;
+    for v in vertices {
+        writeln!(out, r#"    <node id="{0}"><data key="word">{0}</data><data key="label">{1}</data></node>"#,
+                 name(&v.word), v.label)?;
+    }
+    for (parent, child, letter) in edges(vertices) {
+        writeln!(out, r#"    <edge source="{}" target="{}"><data key="letter">{}</data></edge>"#, parent, child, letter)?;
+    }
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</graphml>")
+}
@@ -0,0 +1,221 @@
+use std::fmt;
+use std::io::{self, Write};
+use rug::{Complex, Float};
+
+use crate::ball::{self, Ball, RAD_PREC, add_up, div_up, sub_down};
+use crate::context::RunContext;
+use crate::matrix::M;
+use crate::pingpong::{Disk, Inclusion, PingPongFailure};
+use crate::word::{LETTERS, inverse_letter};
+
+/// A verified inclusion x(outside of D_{x^-1}) in D_x: the image is the disk with
+/// center in the ball `center` and radius at most `radius`, and `margin` is a lower
+/// bound for how far it stays inside D_x.
+pub struct CertifiedInclusion {
+    pub letter: char,
+    pub center: Ball,
+    pub radius: Float,
+    pub margin: Float,
+}
+
+/// A proof, checked in ball arithmetic, that the ping-pong lemma applies to the
+/// generators at every z in the ball `z`, so the group is free and discrete.
+pub struct Certificate {
+    pub precision: u32,
+    pub z: Ball,
+    pub disks: [Disk; 4],
+    pub inclusions: Vec<CertifiedInclusion>,
+}
+
+impl fmt::Display for Certificate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "certificate = free and discrete")?;
+        writeln!(f, "precision = {}", self.precision)?;
+        writeln!(f, "z = {}", self.z)?;
+        for (x, disk) in LETTERS.iter().zip(self.disks.iter()) {
+            writeln!(f, "disk_{} = {}", x, disk)?;
+        }
+        for inclusion in &self.inclusions {
+            writeln!(f, "image_{} = {} radius <= {}", inclusion.letter, inclusion.center, inclusion.radius)?;
+            write!(f, "margin_{} >= {}", inclusion.letter, inclusion.margin)?;
+            if inclusion.letter != *LETTERS.last().unwrap() {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Bounds for the image under g of the outside of the disk, which is a disk when
+/// the pole of g is inside. Writing g(w) = a/c - 1/(c^2 (w - p)) with p the pole and
+/// w - p running over the circle of radius R around delta = center - p, the image
+/// has center a/c - conj(delta) / (c^2 (|delta|^2 - R^2)) and radius
+/// R / (|c|^2 (R^2 - |delta|^2)).
+fn image_of_outside(g: &M<Ball>, disk: &Disk, letter: char) -> Result<(Ball, Float), PingPongFailure> {
+    let precision = disk.center.prec().0;
+    let [a, _, c, d] = &g.0;
+    let enclosure = || PingPongFailure::Enclosure(letter);
+
+    let pole = d.neg().div(c).ok_or_else(enclosure)?;
+    let delta = Ball::exact(disk.center.clone()).sub(&pole);
+    if delta.abs_upper() >= disk.radius {
+        return Err(PingPongFailure::PoleOutside(letter));
+    }
+
+    let r = Ball::exact(Complex::with_val(precision, &disk.radius));
+    let denominator = c.mul(c).mul(&delta.mul(&delta.conj()).sub(&r.mul(&r)));
+    let center = a.div(c).ok_or_else(enclosure)?
+        .sub(&delta.conj().div(&denominator).ok_or_else(enclosure)?);
+    let lower = denominator.abs_lower();
+    if lower.is_zero() {
+        return Err(enclosure());
+    }
+    Ok((center, div_up(&disk.radius, &lower)))
+}
+
+/// Checks the ping-pong lemma as in `pingpong::verify`, but with every quantity
+/// bounded rigorously in ball arithmetic and all inequalities required to hold
+/// strictly for the bounds. The generators are `rho_a(z)`, `rho_b(z)`.
+pub fn certify(precision: u32, z: Ball, disks: [Disk; 4]) -> Result<Certificate, PingPongFailure> {
+    let (a, b) = ball::rho(precision, &z).ok_or(PingPongFailure::Enclosure('a'))?;
+    let inclusions = certify_generators(&a, &b, &disks)?;
+    Ok(Certificate { precision, z, disks, inclusions })
+}
+
+/// The ping-pong check for arbitrary generators of determinant 1, given as balls.
+pub fn certify_generators(a: &M<Ball>, b: &M<Ball>, disks: &[Disk; 4]) -> Result<Vec<CertifiedInclusion>, PingPongFailure> {
+    let generators = [a.clone(), b.clone(), a.inv_sl2(), b.inv_sl2()];
+
+    for i in 0..4 {
+        for j in (i + 1)..4 {
+            let distance = Ball::exact(disks[i].center.clone()).sub(&Ball::exact(disks[j].center.clone()));
+            if distance.abs_lower() <= add_up(&disks[i].radius, &disks[j].radius) {
+                return Err(PingPongFailure::Overlap(LETTERS[i], LETTERS[j]));
+            }
+        }
+    }
+
+    let index = |x: char| LETTERS.iter().position(|&l| l == x).unwrap();
+    let mut inclusions = vec![];
+    for (i, &x) in LETTERS.iter().enumerate() {
+        let source = &disks[index(inverse_letter(x))];
+        let target = &disks[i];
+        let (center, radius) = image_of_outside(&generators[i], source, x)?;
+        let distance = center.sub(&Ball::exact(target.center.clone())).abs_upper();
+        let margin = sub_down(&target.radius, &add_up(&distance, &radius));
+        if margin <= 0 {
+            return Err(PingPongFailure::NotNested(Inclusion {
+                letter: x,
+                image: Disk { center: center.mid, radius },
+                margin,
+            }));
+        }
+        inclusions.push(CertifiedInclusion { letter: x, center, radius, margin });
+    }
+    Ok(inclusions)
+}
+
+/// Version of the certificate file format written by `Certificate::write`.
+pub const FORMAT_VERSION: u32 = 1;
+
+fn hex(x: &Float) -> String {
+    x.to_string_radix(16, None)
+}
+
+fn parse_hex(s: &str, precision: u32) -> Result<Float, String> {
+    Float::parse_radix(s, 16)
+        .map(|p| Float::with_val(precision, p))
+        .map_err(|_| format!("invalid hexadecimal number {}", s))
+}
+
+impl Certificate {
+    /// Writes the certificate as lines of space separated fields, after the comments of
+    /// `context`. All numbers are written exactly, in hexadecimal, so the file can be
+    /// checked independently:
+    ///
+    /// ```text
+    /// format 1
+    /// precision <bits>
+    /// z <re> <im> <radius>
+    /// disk <letter> <center re> <center im> <radius>              (for a, b, A, B)
+    /// image <letter> <center re> <center im> <center radius> <radius> <margin>
+    /// ```
+    pub fn write<W: Write>(&self, out: &mut W, context: &RunContext) -> io::Result<()> {
+        writeln!(out, "# repcalc ping-pong certificate: the group is free and discrete")?;
+        context.write_comments(out)?;
+        writeln!(out, "format {}", FORMAT_VERSION)?;
+        writeln!(out, "precision {}", self.precision)?;
+        writeln!(out, "z {} {} {}", hex(self.z.mid.real()), hex(self.z.mid.imag()), hex(&self.z.rad))?;
+        for (x, disk) in LETTERS.iter().zip(self.disks.iter()) {
+            writeln!(out, "disk {} {} {} {}", x, hex(disk.center.real()), hex(disk.center.imag()), hex(&disk.radius))?;
+        }
+        for inclusion in &self.inclusions {
+            writeln!(out, "image {} {} {} {} {} {}", inclusion.letter,
+                hex(inclusion.center.mid.real()), hex(inclusion.center.mid.imag()), hex(&inclusion.center.rad),
+                hex(&inclusion.radius), hex(&inclusion.margin))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a certificate written by `Certificate::write`, returning the precision,
+/// the ball of parameters and the disks. The recorded images are not trusted;
+/// `verify` recomputes them.
+pub fn read(input: &str) -> Result<(u32, Ball, [Disk; 4]), String> {
+    let mut precision: Option<u32> = None;
+    let mut z: Option<Ball> = None;
+    let mut disks: [Option<Disk>; 4] = Default::default();
+
+    for (n, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let error = |msg: &str| format!("line {}: {}", n + 1, msg);
+        // every number is read at least at the precision of the radii so nothing is rounded
+        let bits = precision.map(|p| p.max(RAD_PREC));
+        match (fields[0], bits) {
+            ("format", _) => {
+                if fields.get(1) != Some(&FORMAT_VERSION.to_string().as_str()) {
+                    return Err(error("unsupported format version"));
+                }
+            }
+            ("precision", _) => {
+                let p = fields.get(1).and_then(|p| p.parse::<u32>().ok())
+                    .ok_or_else(|| error("expected the precision in bits"))?;
+                precision = Some(p);
+            }
+            ("z", Some(bits)) if fields.len() == 4 => {
+                let re = parse_hex(fields[1], bits).map_err(|e| error(&e))?;
+                let im = parse_hex(fields[2], bits).map_err(|e| error(&e))?;
+                let rad = parse_hex(fields[3], bits).map_err(|e| error(&e))?;
+                z = Some(Ball { mid: Complex::with_val(bits, (re, im)), rad });
+            }
+            ("disk", Some(bits)) if fields.len() == 5 => {
+                let i = LETTERS.iter().position(|l| fields[1] == l.to_string())
+                    .ok_or_else(|| error("expected a letter in {a,b,A,B}"))?;
+                let re = parse_hex(fields[2], bits).map_err(|e| error(&e))?;
+                let im = parse_hex(fields[3], bits).map_err(|e| error(&e))?;
+                let radius = parse_hex(fields[4], bits).map_err(|e| error(&e))?;
+                disks[i] = Some(Disk { center: Complex::with_val(bits, (re, im)), radius });
+            }
+            ("image", Some(_)) if fields.len() == 7 => {}
+            ("z" | "disk" | "image", None) => return Err(error("the precision must come first")),
+            _ => return Err(error("unrecognized line")),
+        }
+    }
+
+    let precision = precision.ok_or("missing precision")?;
+    let z = z.ok_or("missing z")?;
+    if disks.iter().any(|d| d.is_none()) {
+        return Err("a disk is needed for each of a, b, A, B".to_string());
+    }
+    Ok((precision, z, disks.map(|d| d.unwrap())))
+}
+
+/// Re-checks a certificate from scratch in ball arithmetic.
+pub fn verify(input: &str) -> Result<Certificate, String> {
+    let (precision, z, disks) = read(input)?;
+    certify(precision, z, disks).map_err(|failure| failure.to_string())
+}
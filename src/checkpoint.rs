@@ -0,0 +1,109 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::context::RunContext;
+use crate::matrix::{C, M};
+use crate::snapshot::{matrix_hex, parse_matrix_hex, Snapshot};
+use crate::sweep::Frontier;
+
+/// How far a long computation got, saved by --checkpoint and taken up again by --resume
+/// as the `progress` of a `Snapshot`, next to the generators it was computed for.
+pub enum Progress {
+    /// The product of the first `position` letters of a word of `length` letters, with
+    /// the `digest` of the word, so that it is only taken up again for the same word.
+    Word { length: usize, digest: u64, position: usize, product: M<C> },
+    /// The enumeration of limit-set to `depth`: the reduced words enumerated, counting
+    /// those outside the shard, the points found, the bytes of the CSV file written so far
+    /// and the frontier of the enumeration.
+    LimitSet { depth: usize, words: u64, points: u64, csv_bytes: u64, frontier: Frontier },
+}
+
+/// The 64-bit FNV-1a hash of a word, which tells apart the words of different seeds or
+/// options without storing millions of letters.
+pub fn digest(word: &str) -> u64 {
+    word.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, c| (hash ^ c as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+impl Progress {
+    /// Writes the records of the progress in a snapshot, with every number written
+    /// exactly in hexadecimal:
+    ///
+    /// ```text
+    /// word <length> <digest> <position> <re im of the product>
+    /// limit-set <depth> <words> <points> <csv bytes> <last word, or 1 before the first>
+    /// prefix <re im of the matrix>                   (one for each prefix of the last word)
+    /// ```
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        match self {
+            Progress::Word { length, digest, position, product } =>
+                writeln!(out, "word {} {:016x} {} {}", length, digest, position, matrix_hex(product))?,
+            Progress::LimitSet { depth, words, points, csv_bytes, frontier } => {
+                let word = if frontier.word.is_empty() { "1" } else { &frontier.word };
+                writeln!(out, "limit-set {} {} {} {} {}", depth, words, points, csv_bytes, word)?;
+                for m in &frontier.prefixes {
+                    writeln!(out, "prefix {}", matrix_hex(m))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a record of `Progress::write` at `precision` into `progress`, and returns
+/// whether the fields were one.
+pub(crate) fn read_record(fields: &[&str], precision: u32, progress: &mut Option<Progress>) -> Result<bool, String> {
+    let number = |s: &str| s.parse::<u64>().map_err(|_| format!("invalid number {}", s));
+    match fields[0] {
+        "word" if fields.len() == 12 && progress.is_none() => {
+            let digest = u64::from_str_radix(fields[2], 16).map_err(|_| "invalid digest".to_string())?;
+            *progress = Some(Progress::Word {
+                length: number(fields[1])? as usize,
+                digest,
+                position: number(fields[3])? as usize,
+                product: parse_matrix_hex(&fields[4..], precision)?,
+            });
+        }
+        "limit-set" if fields.len() == 6 && progress.is_none() => {
+            let word = if fields[5] == "1" { String::new() } else { fields[5].to_string() };
+            *progress = Some(Progress::LimitSet {
+                depth: number(fields[1])? as usize,
+                words: number(fields[2])?,
+                points: number(fields[3])?,
+                csv_bytes: number(fields[4])?,
+                frontier: Frontier { word, prefixes: Vec::new() },
+            });
+        }
+        "prefix" if fields.len() == 9 => {
+            let Some(Progress::LimitSet { frontier, .. }) = progress else {
+                return Err("a prefix belongs to a limit-set line".to_string());
+            };
+            frontier.prefixes.push(parse_matrix_hex(&fields[1..], precision)?);
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Checks that a progress read back is whole: the frontier of limit-set has a matrix
+/// for each prefix of its last word.
+pub(crate) fn check(progress: &Progress) -> Result<(), String> {
+    if let Progress::LimitSet { frontier, .. } = progress {
+        if frontier.prefixes.len() != frontier.word.chars().count() {
+            return Err(format!("the last word {} needs {} prefixes, got {}",
+                               frontier.word, frontier.word.chars().count(), frontier.prefixes.len()));
+        }
+    }
+    Ok(())
+}
+
+/// Writes a snapshot with a progress to `path` by way of a temporary file next to it, so
+/// that an interruption while writing leaves the previous checkpoint whole.
+pub fn save(snapshot: &Snapshot, path: &Path, context: &RunContext) -> io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let mut file = BufWriter::new(File::create(&temporary)?);
+    snapshot.write(&mut file, context)?;
+    file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    fs::rename(&temporary, path)
+}
@@ -0,0 +1,125 @@
+use std::io::{self, Write};
+use rug::Float;
+use rug::float::Constant;
+
+use crate::classify::tolerance;
+use crate::context::RunContext;
+use crate::matrix::{C, M};
+use crate::rotation::is_real;
+
+/// The circle map induced on RP^1 = R/piZ by a real matrix, where the angle t
+/// stands for the line through (cos t, sin t).
+pub struct CircleMap {
+    /// Pairs (t, image of t) for t evenly spaced in [0, pi).
+    pub graph: Vec<(Float, Float)>,
+    /// Pairs (t, derivative of the map at t) for each fixed point t.
+    pub fixed_points: Vec<(Float, Float)>,
+}
+
+fn line_angle(x: &Float, y: &Float, pi: &Float) -> Float {
+    let mut t = y.clone().atan2(x);
+    if t < 0 {
+        t += pi;
+    }
+    if t >= *pi {
+        t -= pi;
+    }
+    t
+}
+
+/// Returns `None` if the matrix is not real.
+pub fn circle_map(m: &M<C>, precision: u32, samples: usize) -> Option<CircleMap> {
+    let tol = tolerance(precision);
+    if !is_real(m, &tol) {
+        return None;
+    }
+    let [a, b, c, d] = m.0.clone().map(|x| x.real().clone());
+    let pi = Float::with_val(precision, Constant::Pi);
+
+    let graph = (0..samples).map(|k| {
+        let t = pi.clone() * k as u64 / samples as u64;
+        let (x, y) = (t.clone().cos(), t.clone().sin());
+        let image = line_angle(&(a.clone() * &x + b.clone() * &y), &(c.clone() * &x + d.clone() * &y), &pi);
+        (t, image)
+    }).collect();
+
+    // fixed points are real eigenlines; the derivative there is det / lambda^2 = 1 / lambda^2
+    let trace = a.clone() + &d;
+    let disc = trace.clone().square() - 4u32;
+    let eigenvalues =
+        if disc.clone().abs() <= tol {
+            vec![trace / 2u32]
+        } else if disc < 0 {
+            vec![]
+        } else {
+            let s = disc.sqrt();
+            vec![(trace.clone() + &s) / 2u32, (trace - s) / 2u32]
+        };
+    let fixed_points = eigenvalues.into_iter().flat_map(|lambda| {
+        let lines =
+            if b.clone().abs() > tol {
+                vec![(b.clone(), lambda.clone() - &a)]
+            } else if c.clone().abs() > tol {
+                vec![(lambda.clone() - &d, c.clone())]
+            } else {
+                // diagonal: both coordinate axes are fixed
+                let zero = Float::with_val(precision, 0);
+                let one = Float::with_val(precision, 1);
+                if (lambda.clone() - &a).abs() <= tol {
+                    vec![(one, zero)]
+                } else {
+                    vec![(zero, one)]
+                }
+            };
+        let derivative = lambda.square().recip();
+        lines.into_iter()
+            .map(|(x, y)| (line_angle(&x, &y, &pi), derivative.clone()))
+            .collect::<Vec<_>>()
+    }).collect();
+
+    Some(CircleMap { graph, fixed_points })
+}
+
+pub fn write_csv<W: Write>(map: &CircleMap, out: &mut W, context: &RunContext) -> io::Result<()> {
+    context.write_comments(out)?;
+    writeln!(out, "theta,image")?;
+    for (t, image) in &map.graph {
+        writeln!(out, "{},{}", t, image)?;
+    }
+    Ok(())
+}
+
+/// Plots the graph of the map on [0, pi) x [0, pi) together with the diagonal,
+/// marking the fixed points.
+pub fn write_svg<W: Write>(map: &CircleMap, out: &mut W, context: &RunContext) -> io::Result<()> {
+    const SIZE: f64 = 400.0;
+    let scale = SIZE / std::f64::consts::PI;
+    let px = |t: &Float| t.to_f64() * scale;
+    let py = |t: &Float| SIZE - t.to_f64() * scale;
+
+    context.write_xml_comment(out)?;
+    writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#, SIZE)?;
+    writeln!(out, r#"<rect width="{0}" height="{0}" fill="white" stroke="black"/>"#, SIZE)?;
+    writeln!(out, r#"<line x1="0" y1="{0}" x2="{0}" y2="0" stroke="gray" stroke-dasharray="4"/>"#, SIZE)?;
+
+    // split the graph where the image wraps around from pi back to 0
+    let mut segments: Vec<Vec<String>> = vec![vec![]];
+    let mut prev: Option<f64> = None;
+    for (t, image) in &map.graph {
+        if let Some(p) = prev {
+            if (image.to_f64() - p).abs() > std::f64::consts::FRAC_PI_2 {
+                segments.push(vec![]);
+            }
+        }
+        prev = Some(image.to_f64());
+        segments.last_mut().unwrap().push(format!("{:.3},{:.3}", px(t), py(image)));
+    }
+    for segment in segments.iter().filter(|s| s.len() > 1) {
+        writeln!(out, r#"<polyline points="{}" fill="none" stroke="blue"/>"#, segment.join(" "))?;
+    }
+
+    for (t, _) in &map.fixed_points {
+        writeln!(out, r#"<circle cx="{:.3}" cy="{:.3}" r="4" fill="red"/>"#, px(t), py(t))?;
+    }
+    writeln!(out, "</svg>")
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+use rug::{Complex, Float};
+
+use crate::matrix::{C, M};
+
+/// The type of an element of SL(2,C), read off from its trace.
+pub enum Classification {
+    /// The matrix is ±I.
+    Identity,
+    /// tr = ±2 but the matrix is not ±I.
+    Parabolic,
+    /// tr is real with |tr| < 2; rotates by `angle` about its axis.
+    Elliptic { angle: Float },
+    /// Everything else; translates by `translation_length` along its axis.
+    Loxodromic { translation_length: Float },
+}
+
+impl fmt::Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Classification::Identity => write!(f, "identity"),
+            Classification::Parabolic => write!(f, "parabolic"),
+            Classification::Elliptic { .. } => write!(f, "elliptic"),
+            Classification::Loxodromic { .. } => write!(f, "loxodromic"),
+        }
+    }
+}
+
+/// Quantities closer than this are treated as equal when classifying.
+/// Half the working bits are allowed to be lost to rounding.
+pub fn tolerance(precision: u32) -> Float {
+    Float::with_val(precision, Float::u_exp(1, -((precision / 2) as i32)))
+}
+
+fn is_small(x: &C, tol: &Float) -> bool {
+    let abs = Float::with_val(tol.prec(), x.abs_ref());
+    abs <= *tol
+}
+
+pub fn classify(m: &M<C>, precision: u32) -> Classification {
+    let tol = tolerance(precision);
+    let t = m.trace();
+    let two = Complex::with_val(precision, 2);
+    let near_two = is_small(&(t.clone() - two.clone()), &tol);
+    let near_minus_two = is_small(&(t.clone() + two), &tol);
+
+    if near_two || near_minus_two {
+        let [a, b, c, d] = &m.0;
+        if is_small(b, &tol) && is_small(c, &tol) && is_small(&(a.clone() - d.clone()), &tol) {
+            return Classification::Identity;
+        }
+        return Classification::Parabolic;
+    }
+
+    if t.imag().clone().abs() <= tol && t.real().clone().abs() < 2 {
+        // tr = 2 cos(angle / 2)
+        let half = Float::with_val(precision, t.real() / 2u32);
+        return Classification::Elliptic { angle: half.acos() * 2u32 };
+    }
+
+    let length = complex_length(m);
+    Classification::Loxodromic { translation_length: length.real().clone().abs() }
+}
+
+/// The complex translation length L = 2 arccosh(tr / 2), so that tr = 2 cosh(L / 2).
+/// Re L is the hyperbolic translation length along the axis and Im L the rotation
+/// about it.
+pub fn complex_length(m: &M<C>) -> C {
+    trace_length(&m.trace())
+}
+
+/// The complex translation length of `complex_length` from the trace alone.
+pub fn trace_length(trace: &C) -> C {
+    let half = trace.clone() / 2u32;
+    half.acosh() * 2u32
+}
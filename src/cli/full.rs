@@ -0,0 +1,3259 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use rand::{rngs::StdRng, SeedableRng, Rng};
+use rug::float::Constant;
+use rug::{Complex, Float, Integer, Rational};
+use clap::{ArgAction, ArgGroup, Parser, Subcommand, ValueEnum};
+
+#[cfg(feature = "rigorous")]
+use repcalc::ball::{self, Ball};
+use repcalc::batch;
+use repcalc::bench::{self, Routine};
+use repcalc::budget::{Budget, IterationLimit, LimitReached};
+use repcalc::cas::{self, Entry, Format, Value};
+use repcalc::cayley;
+#[cfg(feature = "rigorous")]
+use repcalc::certificate;
+use repcalc::checkpoint::{self, Progress};
+use repcalc::circle::{self, circle_map};
+use repcalc::classify::{Classification, classify, complex_length, tolerance, trace_length};
+use repcalc::context::RunContext;
+use repcalc::cost;
+use repcalc::continued_fraction;
+use repcalc::cusp::{self, CuspCache, Source};
+use repcalc::find_word::find_word;
+use repcalc::jorgensen::{JorgensenReport, jorgensen, jorgensen_sum};
+#[cfg(feature = "render")]
+use repcalc::density::{self, Density, Window};
+use repcalc::display::{Notation, Style};
+use repcalc::dual;
+use repcalc::end_invariant::{Confidence, EndInvariant, cusp_candidates, end_invariant, growth_candidates};
+use repcalc::error::{Error, STATUS_FAILED, STATUS_INCOMPLETE, STATUS_INPUT};
+use repcalc::exact;
+use repcalc::limit_set::{self, for_each_limit_point_from};
+use repcalc::markov::markov_spectrum;
+use repcalc::matrix::{C, EigenDecomposition, M, parse_complex, parse_complex_args, parse_sl2};
+use repcalc::mcshane;
+use repcalc::mobius::{self, Point, fixed_points};
+use repcalc::modular;
+use repcalc::orbit::{self, Basepoint, for_each_orbit_point, for_each_random_orbit_point};
+use repcalc::pingpong::{self, Disk, isometric_disks};
+use repcalc::pleating::pleating_candidates;
+use repcalc::plot::{self, Plot};
+use repcalc::real_form::real_form;
+use repcalc::results::{self, Body, Results};
+use repcalc::rho::{self, Generators};
+use repcalc::rotation::rotation_number;
+use repcalc::scalar;
+use repcalc::scan::{self, Class, Grid};
+use repcalc::sampling::{Sampler, small_trace_probability};
+use repcalc::shard::Shard;
+use repcalc::snapshot::{self, Snapshot};
+use repcalc::spectrum::length_spectrum;
+use repcalc::sweep::{Check, CheckResult, Frontier, sweep};
+use repcalc::trace_map::{self, Move};
+use repcalc::trace_polynomial::{self, trace_polynomial};
+use repcalc::stats::{histogram, linear_fit, log_abs_trace, log_norm, summarize};
+use repcalc::stern_brocot::{
+    ContinuedFraction, ExtendedRational, continued_fraction_letters, convergent, for_each_farey,
+    SternBrocotCache, stern_brocot_letters, stern_brocot_trace, stern_brocot_word,
+};
+use repcalc::word;
+
+/// The style of --digits, --notation and --polar, set once the arguments are parsed.
+static STYLE: OnceLock<Style> = OnceLock::new();
+
+/// println!, with the numbers in the line written in the style of --digits, --notation
+/// and --polar. Certificates are printed with println! so that they keep every digit.
+macro_rules! out {
+    ($($arg:tt)*) => {
+        match STYLE.get() {
+            Some(style) => println!("{}", style.line(&format!($($arg)*))),
+            None => println!($($arg)*),
+        }
+    };
+}
+
+/// Prints the error and exits with its status, see `Error::exit_code`.
+fn fail(e: Error) -> ! {
+    match &e {
+        Error::Limit(_) => eprintln!("{}. Raise --iteration-limit, or pass --iteration-limit 0 to continue without a limit.", e),
+        _ => eprintln!("{}", e),
+    }
+    std::process::exit(e.exit_code())
+}
+
+/// `expect` for reading and writing files, which exits with `Error::Io` instead of
+/// panicking.
+trait OrFail<T> {
+    fn or_fail(self, what: &str) -> T;
+}
+
+impl<T, E: std::fmt::Display> OrFail<T> for Result<T, E> {
+    fn or_fail(self, what: &str) -> T {
+        self.unwrap_or_else(|e| fail(Error::Io(format!("{}: {}", what, e))))
+    }
+}
+
+fn parse_word(input: &str) -> Result<String, String> {
+    // Check that every character is a letter; whether it names a generator is checked
+    // once the generators of --matrix and --name are known
+    if input.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok(input.to_string())
+    } else {
+        Err("Value must contain only the letters 'a', 'b', 'A', 'B' and those of --name.".to_string())
+    }
+}
+
+/// Exits with an error if `word` uses a generator other than a and b, for the options
+/// built on the two-generator families.
+fn require_two_generators(word: &str, option: &str) {
+    if let Some(c) = word.chars().find(|c| !matches!(c, 'a' | 'b' | 'A' | 'B')) {
+        fail(Error::Input(format!("{} only works with words in a and b, not {}.", option, c)))
+    }
+}
+
+/// Parses the system of --format.
+fn parse_format(input: &str) -> Result<Format, String> {
+    input.parse()
+}
+
+fn parse_notation(input: &str) -> Result<Notation, String> {
+    input.parse()
+}
+
+fn parse_plot(input: &str) -> Result<Plot, String> {
+    input.parse()
+}
+
+fn parse_routine(input: &str) -> Result<Routine, String> {
+    input.parse()
+}
+
+/// Parses a positive slope p/q in lowest terms.
+fn parse_slope(input: &str) -> Result<(u64, u64), String> {
+    let (p, q) = input.split_once('/').ok_or("expected a slope p/q")?;
+    let parse = |x: &str| x.trim().parse::<u64>().map_err(|_| format!("invalid integer {}", x));
+    let (p, q) = (parse(p)?, parse(q)?);
+    if p == 0 || q == 0 || Integer::from(p).gcd(&Integer::from(q)) != 1 {
+        return Err("expected a positive slope p/q in lowest terms".to_string());
+    }
+    Ok((p, q))
+}
+
+fn parse_moves(input: &str) -> Result<String, String> {
+    Move::parse_all(input)?;
+    Ok(input.to_string())
+}
+
+#[derive(Parser, Clone, Debug)]
+#[command(version, about, long_about = None, subcommand_negates_reqs = true)]
+#[command(group(ArgGroup::new("generator_source").args(["z", "random_z", "matrix_a", "restore"])))]
+#[command(group(ArgGroup::new("word_source").args(["word", "random_word", "r", "cf"])))]
+#[command(group(ArgGroup::new("random").args(["random_z", "random_word"]).multiple(true)))]
+#[command(group(ArgGroup::new("histogram_words").args(["random_word", "clt_fit"]).multiple(true)))]
+#[command(group(ArgGroup::new("checkpointing").args(["checkpoint", "resume"]).multiple(true)
+    .conflicts_with_all(["det_drift", "renormalize", "r", "samples", "trace_histogram"])))]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// z parameter, x + i y, read at the working precision. Give x and y, or a single
+    /// value such as 1.5+0.3i or (1.5 0.3); write -z=-1.5+0.3i if it starts with a minus sign
+    #[arg(
+        short,
+        num_args = 1..=2,
+        value_names = ["x", "y"],
+        allow_negative_numbers = true,
+    )]
+    z: Option<Vec<String>>,
+
+    /// How the generators are built from the parameter given by -z: the rho_a, rho_b family,
+    /// the Maskit slice (-z is mu), the Riley slice (-z is rho), or Grandma's recipe from
+    /// the trace coordinates (-z is tr a, and --tb is tr b for trace or --tab is tr ab for
+    /// grandma)
+    #[arg(long, value_enum, default_value = "z")]
+    param: Parameterization,
+
+    /// Use this matrix [[a, b], [c, d]] in SL(2,C) for the generator a instead of a
+    /// parameterization. Each entry is read at the working precision, as x, (x y) or x+yi
+    #[arg(long, num_args = 4, value_names = ["a", "b", "c", "d"], allow_negative_numbers = true,
+          requires = "matrix_b")]
+    matrix_a: Option<Vec<String>>,
+
+    /// The matrix for the generator b, as with --matrix-a
+    #[arg(long, num_args = 4, value_names = ["a", "b", "c", "d"], allow_negative_numbers = true,
+          requires = "matrix_a")]
+    matrix_b: Option<Vec<String>>,
+
+    /// A further generator [[a, b], [c, d]] in SL(2,C), read like --matrix-a, next to a and b
+    /// from --param or --matrix-a; can be repeated
+    #[arg(long, num_args = 4, value_names = ["a", "b", "c", "d"], allow_negative_numbers = true,
+          action = ArgAction::Append)]
+    matrix: Vec<String>,
+
+    /// The letter naming each --matrix in turn, lowercase and other than a and b, with the
+    /// uppercase letter for its inverse; by default c, d, e, ...
+    #[arg(long, action = ArgAction::Append)]
+    name: Vec<char>,
+
+    /// Conjugate every generator g to p g p^-1 by the invertible matrix p = [[a, b], [c, d]],
+    /// read like --matrix-a; traces are unchanged, matrices and fixed points move
+    #[arg(long, num_args = 4, value_names = ["a", "b", "c", "d"], allow_negative_numbers = true)]
+    conjugate_by: Option<Vec<String>>,
+
+    /// tr b for --param trace, given like -z
+    #[arg(long, num_args = 1..=2, value_names = ["x", "y"], allow_negative_numbers = true)]
+    tb: Option<Vec<String>>,
+
+    /// tr ab for --param grandma, given like -z
+    #[arg(long, num_args = 1..=2, value_names = ["x", "y"], allow_negative_numbers = true)]
+    tab: Option<Vec<String>>,
+
+    /// Number of bits of precision for floating point arithmetic
+    #[arg(
+        short,
+        long,
+        value_parser = clap::value_parser!(u32).range(1..),
+        required_unless_present_any = ["sweep", "sweep_file", "exact", "gaussian", "auto_precision", "restore"],
+    )]
+    precision: Option<u32>,
+
+    /// Double the precision, starting from --precision (or 64), until the word's
+    /// determinant error |det - 1| and the residual of its dominant eigenvector are below
+    /// --residual-tolerance, then run at that precision
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with_all = ["samples", "trace_histogram"])]
+    auto_precision: bool,
+
+    /// Stop the iterative algorithms, such as the Stern-Brocot descent of -r, after this many
+    /// steps with an error instead of running for practically ever on inputs like huge
+    /// partial quotients; 0 means no limit
+    #[arg(long, default_value_t = 10_000_000)]
+    iteration_limit: u64,
+
+    /// The tolerance for --auto-precision
+    #[arg(long, default_value_t = 1e-12)]
+    residual_tolerance: f64,
+
+    /// The largest precision --auto-precision tries before giving up
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..), default_value_t = 65536)]
+    max_precision: u32,
+
+    /// The word to calculate the value of, a string in {a,b,A,B} and the generators of --name
+    #[arg(long, value_parser = parse_word)]
+    word: Option<String>,
+
+    /// Obtain the word by locating the rational p/q in the Stern-Brocot tree
+    #[arg(short, num_args = 2, value_names = ["p", "q"])]
+    r: Option<Vec<u64>>,
+
+    /// Obtain the word from the continued fraction [a0; a1, ..., an] of the slope, as with -r.
+    /// A final parenthesized period, as in [1; (2, 1)], is repeated --cf-periods times
+    #[arg(long, value_name = "CF", value_parser = ContinuedFraction::parse)]
+    cf: Option<ContinuedFraction>,
+
+    /// Number of times to repeat the period of --cf
+    #[arg(long, default_value_t = 8)]
+    cf_periods: usize,
+
+    /// Use a random value for z
+    #[arg(long, action = ArgAction::SetTrue)]
+    random_z: bool,
+
+    /// Seed for --random-z, --random-word and the other random modes; by default a
+    /// fresh seed is drawn, and printed so that the run can be repeated
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Draw this many independent random words (--random-word) and/or values of z
+    /// (--random-z) and print statistics of their traces and dominant eigenvalues
+    #[arg(long, requires = "random")]
+    samples: Option<usize>,
+
+    /// Use a uniform random (unreduced) word of the given length
+    #[arg(long)]
+    random_word: Option<usize>,
+
+    /// Freely (or cyclically) reduce the word before evaluating it. With --random-word,
+    /// draw a uniform random reduced word of the given length instead
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "free")]
+    reduce: Option<Reduction>,
+
+    /// Classify the result as elliptic, parabolic or loxodromic, with its rotation
+    /// angle or translation length
+    #[arg(long, action = ArgAction::SetTrue)]
+    classify: bool,
+
+    /// Print the complex translation length 2 arccosh(tr/2) of the result, with its
+    /// real (translation length) and imaginary (rotation) parts
+    #[arg(long, action = ArgAction::SetTrue)]
+    complex_length: bool,
+
+    /// Print the derivatives d(tr)/dz and, with --complex-length, dL/dz with respect to the
+    /// parameter of --param (z, maskit or riley), computed exactly with dual numbers
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with = "matrix_a")]
+    derivative: bool,
+
+    /// Evaluate the word exactly for --param z, maskit or riley, with the parameter a root z
+    /// of the integer polynomial with these coefficients (highest degree first), in
+    /// Q(i)[z]/(f); the entries are printed as polynomials in z of degree less than deg f.
+    /// For --param z they lie in the extension by s = sqrt(z^2 - 1) and are printed as
+    /// x + (y) s; b is rho_b's up to sign, which differs where z^2 - 1 is a negative real
+    #[arg(long, value_name = "COEFFICIENT", num_args = 2.., allow_negative_numbers = true)]
+    exact: Option<Vec<Integer>>,
+
+    /// Evaluate the word exactly in Q(i) for --param z, maskit or riley at the Gaussian
+    /// rational parameter RE + IM i (each an integer, p/q or a decimal), and check the
+    /// floating point evaluation at --precision (or 64) against it; --param z needs
+    /// z^2 - 1 to be a square in Q(i), as it is for z = (t + 1/t)/2
+    #[arg(long, num_args = 2, value_names = ["RE", "IM"], allow_negative_numbers = true,
+          conflicts_with_all = ["exact", "generator_source", "matrix"])]
+    gaussian: Option<Vec<String>>,
+
+    /// Print the cusp of the Maskit slice of slope p/q at the working precision: the mu
+    /// where its Stern-Brocot word is parabolic, from the catalogue of closed forms, from
+    /// --cusp-cache or solved from its trace polynomial
+    #[arg(long, value_name = "p/q", value_parser = parse_slope)]
+    cusp_catalog: Option<(u64, u64)>,
+
+    /// Look the cusp of --cusp-catalog up in this file first, and add it if it was solved
+    #[arg(long, value_name = "PATH", requires = "cusp_catalog")]
+    cusp_cache: Option<PathBuf>,
+
+    /// Track |det - 1| of the partial products while multiplying out the word and print the
+    /// final and the largest drift
+    #[arg(long, action = ArgAction::SetTrue)]
+    det_drift: bool,
+
+    /// Divide the partial product by sqrt(det) after every K multiplications, and print the
+    /// drift as with --det-drift
+    #[arg(long, value_name = "K")]
+    renormalize: Option<usize>,
+
+    /// Also evaluate the word in ball arithmetic and print each entry, the trace and the
+    /// dominant eigenvalue with a certified error radius, and whether the trace is
+    /// provably not +/-2 and the word provably loxodromic
+    #[cfg(feature = "rigorous")]
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with = "matrix_a")]
+    rigorous: bool,
+
+    /// Compute the rotation number of the projective action of the word on RP^1,
+    /// averaged over the given number of repetitions (real representations only)
+    #[arg(long, value_name = "ITERATIONS", num_args = 0..=1, default_missing_value = "1000",
+          value_parser = clap::value_parser!(u64).range(1..))]
+    rotation_number: Option<u64>,
+
+    /// Print the attracting and repelling fixed points of the result acting on CP^1
+    #[arg(long, action = ArgAction::SetTrue)]
+    fixed_points: bool,
+
+    /// Apply the result as a Möbius transformation z -> (az + b)/(cz + d) to the point
+    /// x + i y, or to infinity for inf inf, and print its image; can be repeated
+    #[arg(long, num_args = 2, value_names = ["x", "y"], allow_negative_numbers = true,
+          action = ArgAction::Append)]
+    apply: Vec<String>,
+
+    /// For a result in SL(2,Z), map the point tau = x + i y of the upper half-plane by it and
+    /// compare j(gamma tau) with j(tau), which agree for a modular transformation
+    #[arg(long, num_args = 2, value_names = ["x", "y"], allow_negative_numbers = true)]
+    tau: Option<Vec<String>>,
+
+    /// Print tr(W^n) for the result W from its trace alone, by the Chebyshev recurrence,
+    /// without forming W^n; stable for huge n, where the matrix power is not
+    #[arg(long, value_name = "N")]
+    power_trace: Option<u64>,
+
+    /// Print a normal form of the conjugacy class of the result, diag(lambda1, lambda2) or
+    /// [[lambda, 1], [0, lambda]] for a parabolic, with a conjugator P of determinant 1
+    /// taking the result to it, which can be passed to --conjugate-by
+    #[arg(long, action = ArgAction::SetTrue)]
+    normal_form: bool,
+
+    /// Print the parameters, the result, its trace and its eigenvalues and eigenvectors as
+    /// code for Mathematica, SageMath or NumPy with mpmath, or as LaTeX, keeping every digit,
+    /// instead of the matrix and eigen-data lines
+    #[arg(long, value_name = "SYSTEM", value_parser = parse_format)]
+    format: Option<Format>,
+
+    /// Round printed values to N significant decimal digits. The computation keeps the
+    /// full precision of -p, and so do the files written by the other options
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    digits: Option<u32>,
+
+    /// Write printed values as positional or scientific numbers: auto, scientific or fixed
+    #[arg(long, value_name = "NOTATION", default_value = "auto", value_parser = parse_notation)]
+    notation: Notation,
+
+    /// Print complex numbers in polar form, (modulus ∠argument) with the argument in radians
+    #[arg(long, action = ArgAction::SetTrue)]
+    polar: bool,
+
+    /// Also draw sequences in the terminal, as a sparkline or an ascii plot: the growth of
+    /// log|M| along the prefixes of the word (which multiplies it out once more, from the
+    /// left), the orbit of trace-map, the traces along trace-ray and the histogram of
+    /// --trace-histogram
+    #[arg(long, value_name = "STYLE", value_parser = parse_plot)]
+    plot: Option<Plot>,
+
+    /// Columns of --plot; a longer sequence is averaged over runs of values
+    #[arg(long, value_name = "N", default_value_t = 60, requires = "plot")]
+    plot_width: usize,
+
+    /// Rows of --plot ascii
+    #[arg(long, value_name = "N", default_value_t = 12, requires = "plot")]
+    plot_height: usize,
+
+    /// Write the circle map induced on RP^1 by the result (real representations only)
+    /// to the given file, as SVG if the name ends in .svg and as CSV otherwise
+    #[arg(long, value_name = "PATH")]
+    circle_map: Option<PathBuf>,
+
+    /// Number of sample points for --circle-map
+    #[arg(long, default_value_t = 360)]
+    circle_samples: usize,
+
+    /// Instead of evaluating one word, sample the given number of random words of length
+    /// --random-word and print summary statistics and a histogram of log|tr|
+    #[arg(long, value_name = "SAMPLES", requires = "histogram_words")]
+    trace_histogram: Option<usize>,
+
+    /// With --trace-histogram, sample log|M| for words of each of the given lengths
+    /// instead, and fit its mean and variance linearly in the length (the Lyapunov
+    /// exponent and diffusion coefficient of the central limit theorem)
+    #[arg(long, value_name = "LENGTHS", num_args = 1.., requires = "trace_histogram")]
+    clt_fit: Vec<usize>,
+
+    /// Number of bins for --trace-histogram
+    #[arg(long, default_value_t = 20)]
+    bins: usize,
+
+    /// Estimate the probability that a random word of length --random-word has
+    /// |tr| < 2 + EPSILON, with a branching sampler that favours words of small norm
+    #[arg(long, value_name = "EPSILON", requires = "random_word")]
+    small_trace: Option<f64>,
+
+    /// Number of particles for --small-trace
+    #[arg(long, default_value_t = 1000)]
+    particles: usize,
+
+    /// Number of independent runs for --small-trace, from which the error bar is computed
+    #[arg(long, default_value_t = 10)]
+    replicates: usize,
+
+    /// Strength of the bias towards small norms for --small-trace; 0 is naive Monte Carlo
+    #[arg(long, default_value_t = 1.0)]
+    tilt: f64,
+
+    /// Evaluate every reduced word up to the given length and check the properties
+    /// selected by the --assert flags (all of them if none is given), exiting with
+    /// status 1 if any fails
+    #[arg(long, value_name = "LENGTH")]
+    verify_up_to: Option<usize>,
+
+    /// With --verify-up-to, check that no word is elliptic
+    #[arg(long, action = ArgAction::SetTrue, requires = "verify_up_to")]
+    assert_no_elliptic: bool,
+
+    /// With --verify-up-to, check that no primitive word has trace in [-2, 2]
+    #[arg(long, action = ArgAction::SetTrue, requires = "verify_up_to")]
+    assert_bowditch: bool,
+
+    /// With --verify-up-to, check that every word has determinant 1 up to the tolerance
+    #[arg(long, action = ArgAction::SetTrue, requires = "verify_up_to")]
+    assert_det: bool,
+
+    /// Print the trace coordinates (x, y, z) = (tr a, tr b, tr ab) and check the Markov
+    /// identity x^2 + y^2 + z^2 = xyz
+    #[arg(long, action = ArgAction::SetTrue)]
+    trace_coords: bool,
+
+    /// Print the traces in the conventions of Indra's Pearls: ta, tb and tab, and the
+    /// Maskit parameter mu = i ta (taking Im mu >= 0) when tb = 2 and tr[a, b] = -2
+    #[arg(long, action = ArgAction::SetTrue)]
+    indra: bool,
+
+    /// Conjugate the generators into SL(2,R) if the representation is conjugate to a real
+    /// one, and print the conjugating matrix and the real generators
+    #[arg(long, action = ArgAction::SetTrue)]
+    real_form: bool,
+
+    /// Print tr[a, b] and its distance from -2, the condition for the representation to
+    /// be a type-preserving representation of the once-punctured torus
+    #[arg(long, action = ArgAction::SetTrue)]
+    check_relator: bool,
+
+    /// Test Jørgensen's inequality |tr^2 A - 4| + |tr[A, B] - 2| >= 1, which holds for every
+    /// discrete non-elementary pair, on the generators and on the pairs of --jorgensen-length
+    #[arg(long, action = ArgAction::SetTrue)]
+    jorgensen: bool,
+
+    /// With --jorgensen, also test the pairs (a, w b w^-1) and (b, w a w^-1) for every reduced
+    /// word w up to this length
+    #[arg(long, value_name = "LENGTH", default_value_t = 0, requires = "jorgensen")]
+    jorgensen_length: usize,
+
+    /// Stop the searches (spectrum, limit-set, --verify-up-to, --jorgensen-length) after this many
+    /// seconds and print what was found so far
+    #[arg(long, value_name = "SECONDS")]
+    time_limit: Option<f64>,
+
+    /// Stop the searches after evaluating this many words and print what was found so far
+    #[arg(long, value_name = "NODES")]
+    node_budget: Option<u64>,
+
+    /// Run only the part i of n (counting from 0) of the searches (spectrum, limit-set,
+    /// --verify-up-to, --jorgensen-length), so that they can be split across machines and the outputs
+    /// concatenated
+    #[arg(long, value_name = "i/n", value_parser = Shard::parse, default_value = "0/1")]
+    shard: Shard,
+
+    /// Instead of running the subcommand, predict its cost with the current options: the
+    /// nodes it visits, the complex multiplications it takes and their time at the working
+    /// precision from a quick calibration. For spectrum, find-word, farey,
+    /// enumerate-primitives, mcshane and scan
+    #[arg(long, action = ArgAction::SetTrue)]
+    estimate: bool,
+
+    /// Also write the results of the search (spectrum, --verify-up-to, --jorgensen) to this JSON
+    /// file, which the merge subcommand combines with those of other shards
+    #[arg(long, value_name = "PATH")]
+    results: Option<PathBuf>,
+
+    /// Write a snapshot of the run to this file: every generator exactly, the cusps of
+    /// --restore, and the results of a search once it finishes or is stopped
+    #[arg(long, value_name = "PATH")]
+    snapshot: Option<PathBuf>,
+
+    /// Take the generators (and the precision, unless --precision is given) from a file
+    /// written with --snapshot instead of from -z or --matrix-a
+    #[arg(long, value_name = "PATH")]
+    restore: Option<PathBuf>,
+
+    /// Save the progress of a long computation (the product of --word or --random-word,
+    /// or the enumeration of limit-set) to this file every --checkpoint-interval seconds
+    /// and when it stops, with every number exactly in hexadecimal
+    #[arg(long, value_name = "PATH")]
+    checkpoint: Option<PathBuf>,
+
+    /// Seconds between the saves of --checkpoint
+    #[arg(long, value_name = "SECONDS", default_value_t = 600.0, requires = "checkpoint")]
+    checkpoint_interval: f64,
+
+    /// Carry on a computation from a file written with --checkpoint, given the same
+    /// options (and --seed for --random-word)
+    #[arg(long, value_name = "PATH")]
+    resume: Option<PathBuf>,
+
+    /// Run once for every combination of parameters in a sweep specification, such as
+    /// "z.re=0:2:0.01; precision=128,256". The keys are z.re, z.im, tb.re, tb.im, tab.re,
+    /// tab.im, precision, seed, word and r (a slope p/q); each takes a value, a list a,b,c or
+    /// a range start:stop:step
+    #[arg(long, value_name = "SPEC", conflicts_with = "sweep_file")]
+    sweep: Option<String>,
+
+    /// Read the sweep specification from a file, one key=values per line
+    #[arg(long, value_name = "PATH")]
+    sweep_file: Option<PathBuf>,
+
+    /// Write the ball of radius --cayley-radius in the Cayley graph of F_2 to the given file,
+    /// with each vertex labeled by its matrix, as GraphML if the name ends in .graphml and
+    /// as DOT otherwise
+    #[arg(long, value_name = "PATH")]
+    cayley_ball: Option<PathBuf>,
+
+    /// Radius of the ball for --cayley-ball
+    #[arg(long, default_value_t = 3)]
+    cayley_radius: usize,
+
+    /// What to label the vertices of --cayley-ball with
+    #[arg(long, value_enum, default_value = "trace")]
+    cayley_label: CayleyLabel,
+
+    /// Check the ping-pong lemma for the generators with the disks given by --disk
+    /// (by default the isometric disks), instead of evaluating a word
+    #[arg(long, action = ArgAction::SetTrue)]
+    ping_pong: bool,
+
+    /// A disk D_x for --ping-pong, where x is one of a, b, A, B; give one for each letter
+    #[arg(long, num_args = 4, value_names = ["letter", "x", "y", "r"], action = ArgAction::Append,
+          allow_negative_numbers = true)]
+    disk: Vec<String>,
+
+    /// With --ping-pong, redo the check in ball arithmetic and print a certificate
+    /// that the group is free and discrete
+    #[cfg(feature = "rigorous")]
+    #[arg(long, action = ArgAction::SetTrue, requires = "ping_pong")]
+    certify: bool,
+
+    /// With --certify, also write the certificate to this file for `repcalc verify`
+    #[cfg(feature = "rigorous")]
+    #[arg(long, value_name = "PATH", requires = "certify")]
+    certificate: Option<PathBuf>,
+}
+
+/// The options of `limit-set` that draw the points.
+#[cfg(feature = "render")]
+#[derive(clap::Args, Clone, Debug)]
+struct PngArgs {
+    /// Rasterize the points to this PNG file, colored by the number of points per pixel
+    #[arg(long, value_name = "PATH")]
+    png: Option<PathBuf>,
+    /// Width of the PNG in pixels; the height follows from the window
+    #[arg(long, default_value_t = 1000)]
+    width: u32,
+    /// The region of C to draw, by default a square around the points
+    #[arg(long, num_args = 4, value_names = ["x0", "y0", "x1", "y1"], allow_negative_numbers = true)]
+    window: Option<Vec<f64>>,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum Command {
+    /// Re-check a certificate written with --certify --certificate, using only ball arithmetic
+    #[cfg(feature = "rigorous")]
+    Verify {
+        certificate: PathBuf,
+    },
+    /// List the translation lengths of all conjugacy classes up to a word length, shortest first
+    Spectrum {
+        /// Largest cyclically reduced word length to enumerate
+        #[arg(long)]
+        max_length: usize,
+    },
+    /// List the conjugacy classes up to a word length whose traces are nearest to a target,
+    /// nearest first, with all ties
+    FindWord {
+        /// The target trace, read like -z
+        #[arg(long, value_name = "TRACE", allow_negative_numbers = true)]
+        target: String,
+        /// Largest cyclically reduced word length to search
+        #[arg(long)]
+        max_length: usize,
+        /// How many classes to print, not counting ties with the last
+        #[arg(long, default_value_t = 10)]
+        count: usize,
+    },
+    /// Print the trace of the Stern-Brocot word of every slope p/q with p and q at most
+    /// --max-height, in increasing order, using the trace recursion down the Farey tree
+    Farey {
+        #[arg(long)]
+        max_height: u64,
+        /// Also multiply out the matrices of the words and check the recursion against them
+        #[arg(long, action = ArgAction::SetTrue)]
+        check_matrices: bool,
+        /// Write the attracting fixed point of each word to a CSV file of points x,y in C,
+        /// the endpoints of the pleating laminations
+        #[arg(long, value_name = "PATH")]
+        fixed_points: Option<PathBuf>,
+    },
+    /// Print the Stern-Brocot word of every slope p/q in (0, 1] with q at most --max-q, in
+    /// increasing order, with its trace from the recursion down the Farey tree and its
+    /// translation length
+    EnumeratePrimitives {
+        #[arg(long)]
+        max_q: u64,
+    },
+    /// Estimate the slope of the pleating lamination at the parameter of --param maskit or
+    /// --param riley, as the slope whose trace is nearest to real
+    Pleating {
+        /// Largest height max(p, q) of the slopes p/q to search
+        #[arg(long)]
+        max_height: u64,
+    },
+    /// Guess the end invariant at a point near the boundary of the Maskit or Riley slice,
+    /// from the slopes whose traces are nearest to +/-2 and the slopes whose traces grow slowest
+    EndInvariant {
+        /// Largest height max(p, q) of the slopes p/q to search
+        #[arg(long)]
+        max_height: u64,
+        /// How close to +/-2 a trace must be to suggest a cusp when it is not +/-2 up to
+        /// the tolerance
+        #[arg(long, default_value_t = 0.1)]
+        near: f64,
+    },
+    /// Sample a rectangle of the parameter plane of --param on a grid and classify each point
+    /// by the traces of the primitive words of all slopes p/q with |p|, q at most --max-height,
+    /// other than the parabolic generators of --param maskit or riley: fails if one is in
+    /// [-2, 2], small if the smallest |tr| is below --threshold, and bounded otherwise
+    Scan {
+        /// The rectangle of the plane to sample
+        #[arg(long, num_args = 4, value_names = ["x0", "y0", "x1", "y1"], allow_negative_numbers = true,
+              required = true)]
+        window: Vec<f64>,
+        /// Number of samples along each axis
+        #[arg(long, num_args = 2, value_names = ["columns", "rows"], required = true)]
+        resolution: Vec<u32>,
+        /// Largest height max(|p|, q) of the slopes p/q to check
+        #[arg(long)]
+        max_height: u64,
+        /// Lower bound on the trace moduli for a point to be bounded
+        #[arg(long, default_value_t = 2.0)]
+        threshold: f64,
+        /// Write each sample to a CSV file with columns x,y,class,min_abs_trace,slope,small_traces
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+        /// Draw the classes to a PNG with one pixel per sample
+        #[cfg(feature = "render")]
+        #[arg(long, value_name = "PATH")]
+        png: Option<PathBuf>,
+    },
+    /// Sum the McShane series 1/(1 + e^l) over the simple closed curves of all slopes p/q with
+    /// |p|, q at most --max-height, which converges to 1/2 for a quasi-Fuchsian
+    /// once-punctured torus group
+    #[command(name = "mcshane")]
+    McShane {
+        /// Largest height max(|p|, q) of the slopes p/q to sum over
+        #[arg(long)]
+        max_height: u64,
+    },
+    /// Iterate the Markov trace map on the trace coordinates (tr a, tr b, tr ab) of the
+    /// representation and print the orbit
+    TraceMap {
+        /// Number of moves to apply
+        #[arg(long)]
+        steps: usize,
+        /// The moves, applied in order and repeated: R is (x, y, z) -> (x, z, xz - y),
+        /// L is (x, y, z) -> (z, y, yz - x) and F is (x, y, z) -> (x, y, xy - z)
+        #[arg(long, default_value = "R", value_parser = parse_moves)]
+        moves: String,
+    },
+    /// Write the attracting fixed points of all words up to a length, which approximate the
+    /// limit set, to a CSV file of points x,y in C and/or render them as a PNG
+    LimitSet {
+        /// Largest word length to enumerate
+        #[arg(long)]
+        depth: usize,
+        #[cfg_attr(feature = "render", arg(long, value_name = "PATH", required_unless_present = "png"))]
+        #[cfg_attr(not(feature = "render"), arg(long, value_name = "PATH", required = true))]
+        out: Option<PathBuf>,
+        #[cfg(feature = "render")]
+        #[command(flatten)]
+        png: PngArgs,
+    },
+    /// Apply the reduced words up to a length, or random reduced words, to a basepoint on
+    /// CP^1 or in upper half-space H^3 and write the orbit to a CSV file and/or a PNG
+    Orbit {
+        /// The basepoint x + i y on CP^1 (inf inf for infinity), or x y t for the point of
+        /// H^3 at height t > 0 above x + i y
+        #[arg(long, num_args = 2..=3, value_names = ["x", "y", "t"], allow_negative_numbers = true,
+              required = true)]
+        basepoint: Vec<String>,
+        /// Apply every reduced word up to this length
+        #[arg(long, required_unless_present = "random", conflicts_with = "random")]
+        depth: Option<usize>,
+        /// Apply this many random reduced words of length --length instead
+        #[arg(long, requires = "length")]
+        random: Option<usize>,
+        /// Length of the words of --random
+        #[arg(long, requires = "random")]
+        length: Option<usize>,
+        /// Write the orbit to this CSV file, with columns x,y,word on CP^1 and x,y,t,word in H^3
+        #[cfg_attr(feature = "render", arg(long, value_name = "PATH", required_unless_present = "png"))]
+        #[cfg_attr(not(feature = "render"), arg(long, value_name = "PATH", required = true))]
+        out: Option<PathBuf>,
+        /// Rasterize the points of the orbit, or the points below them for H^3
+        #[cfg(feature = "render")]
+        #[command(flatten)]
+        png: PngArgs,
+    },
+    /// Combine the --results files of the shards (or of repeated runs) of a search, checking
+    /// that they agree on the parameters and dropping work done twice
+    Merge {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Write the merged results to this file
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+    /// Find a parameter where the trace of a word takes a given value, by Newton's method in
+    /// the parameter of --param (z, maskit or riley) starting from -z, or without -z from
+    /// the cusp of a Stern-Brocot word (maskit) or a base point, continued to the target
+    Solve {
+        #[arg(long, value_parser = parse_word)]
+        word: String,
+        /// The target trace, read like -z; 2 or -2 makes the word parabolic
+        #[arg(long, value_name = "TRACE", allow_negative_numbers = true, required_unless_present = "order")]
+        target: Option<String>,
+        /// Target 2 cos(pi / n) instead, which makes the word elliptic of order n
+        #[arg(long, conflicts_with = "target")]
+        order: Option<u32>,
+        #[arg(long, default_value_t = 100)]
+        max_iterations: usize,
+    },
+    /// Follow the rational pleating ray of a word, where its trace is real with |tr| >= 2,
+    /// from -z (or from where |tr| = --from) down to the cusp where the trace is +-2, and
+    /// print the trace and the parameter at each step
+    TraceRay {
+        #[arg(long, value_parser = parse_word)]
+        word: String,
+        /// Without -z, start where the trace is --from, with the sign of the trace at the
+        /// cusp, continued from the cusp (--param maskit) or a base point as for solve
+        #[arg(long, default_value_t = 10.0)]
+        from: f64,
+        /// Number of equal steps in the trace from the start to the cusp
+        #[arg(long, default_value_t = 100)]
+        steps: u32,
+        #[arg(long, default_value_t = 100)]
+        max_iterations: usize,
+        /// Also write the ray to a CSV file of points x,y in C with their traces
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+    /// Trace the boundary of the Maskit slice from the cusp of one slope to that of another:
+    /// print the cusps of the slopes between them in order, each solved from its Farey
+    /// neighbours above it in the Stern-Brocot tree
+    BoundaryArc {
+        /// The slope p/q to start from
+        #[arg(value_parser = parse_slope)]
+        from: (u64, u64),
+        /// The slope p/q to end at, above FROM
+        #[arg(value_parser = parse_slope)]
+        to: (u64, u64),
+        /// Largest height max(p, q) of the slopes on the arc
+        #[arg(long, default_value_t = 16)]
+        max_height: u64,
+        #[arg(long, default_value_t = 100)]
+        max_iterations: usize,
+        /// Also write the arc to a CSV file of points x,y in C with their slopes
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+    /// Print the degree and leading coefficient of the trace of the Stern-Brocot word of p/q
+    /// as a polynomial in the parameter of --param maskit or --param riley
+    TraceDegree {
+        p: u64,
+        q: u64,
+    },
+    /// Evaluate the continued fraction b0 + a1 / (b1 + a2 / (b2 + ...)) to --terms terms as a
+    /// product of 2x2 matrices, with estimates of its truncation and rounding errors
+    ContinuedFraction {
+        /// b0, read like -z
+        #[arg(long, default_value = "0", allow_negative_numbers = true)]
+        b0: String,
+        /// a1, a2, ..., each read like -z; repeated periodically up to --terms
+        #[arg(long, num_args = 1.., required = true, allow_negative_numbers = true)]
+        a: Vec<String>,
+        /// b1, b2, ..., each read like -z; repeated periodically up to --terms
+        #[arg(long, num_args = 1.., required = true, allow_negative_numbers = true)]
+        b: Vec<String>,
+        /// The number of terms, by default the length of the longer of --a and --b
+        #[arg(long)]
+        terms: Option<usize>,
+    },
+    /// Print the eigenvalues of the simple random walk operator on the ball of the given
+    /// radius in the Cayley graph of F_2, the 4-regular tree
+    MarkovSpectrum {
+        #[arg(long)]
+        radius: usize,
+    },
+    /// Time the evaluation of words of each of --lengths at each of --precisions along the
+    /// code paths of --routines, and print criterion-style statistics and the throughput
+    Bench {
+        /// Word lengths, at least 3; the word of length n is the Stern-Brocot word of a
+        /// slope p/q with p + q = n
+        #[arg(long, num_args = 1.., default_values_t = [16, 256, 4096])]
+        lengths: Vec<u64>,
+        /// Precisions in bits
+        #[arg(long, num_args = 1.., value_parser = clap::value_parser!(u32).range(1..),
+              default_values_t = [64, 256, 1024, 4096])]
+        precisions: Vec<u32>,
+        /// multiply (the letters of the word), stern-brocot (the descent to the slope) or
+        /// trace-recursion (the trace alone along the descent)
+        #[arg(long, num_args = 1.., value_parser = parse_routine,
+              default_values = ["multiply", "stern-brocot", "trace-recursion"])]
+        routines: Vec<Routine>,
+        /// Seconds to run each routine before measuring it
+        #[arg(long, value_name = "SECONDS", default_value_t = 0.2)]
+        warm_up: f64,
+        /// Seconds to measure each routine for, split among the samples
+        #[arg(long, value_name = "SECONDS", default_value_t = 1.0)]
+        measurement_time: f64,
+        /// Samples of each routine
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..), default_value_t = 20)]
+        sample_size: u64,
+        /// Also write the statistics to a CSV file, one row per routine, length and precision
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Parameterization {
+    Z,
+    Maskit,
+    Riley,
+    Trace,
+    Grandma,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum CayleyLabel {
+    Trace,
+    FixedPoints,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Reduction {
+    Free,
+    Cyclic,
+}
+
+fn random_z<R: Rng>(rng: &mut R, precision: u32) -> C {
+    Complex::with_val(precision, (rng.gen::<f64>(), rng.gen::<f64>()))
+}
+
+fn random_word<R: Rng>(rng: &mut R, n: usize, reduce: Option<Reduction>) -> String {
+    match reduce {
+        None => word::random_word(rng, n),
+        Some(Reduction::Free) => word::random_reduced_word(rng, n),
+        Some(Reduction::Cyclic) => word::random_cyclically_reduced_word(rng, n),
+    }
+}
+
+/// Draws a sequence under `name` as --plot asks, with the range of the plot. The
+/// horizontal axis of an ascii plot runs from 0 to `last`.
+fn print_plot(args: &Args, name: &str, values: &[f64], last: usize) {
+    let Some(style) = args.plot else {
+        return;
+    };
+    let columns = plot::columns(values, args.plot_width);
+    match style {
+        Plot::Sparkline => out!("{} = {}", name, plot::sparkline(&columns)),
+        Plot::Ascii => {
+            out!("{} =", name);
+            for line in plot::ascii(&columns, last, args.plot_height) {
+                out!("{}", line);
+            }
+        }
+    }
+    if let Some((lo, hi)) = plot::range(&columns) {
+        out!("{}_range = {} {}", name, lo, hi);
+    }
+}
+
+/// log|M| of the prefixes of a word at up to `samples` evenly spaced lengths, the last
+/// of them the whole word: the growth of the product along the word.
+fn growth(gens: &Generators, word: &str, samples: usize) -> Vec<f64> {
+    let mut values = Vec::with_capacity(samples);
+    let mut m = M::identity(gens.precision);
+    for (k, c) in word.chars().enumerate() {
+        m = m.mul(gens.letter(c).clone());
+        if (k + 1) * samples >= (values.len() + 1) * word.len() {
+            values.push(log_norm(&m).to_f64());
+        }
+    }
+    values
+}
+
+fn trace_histogram<R: Rng>(args: &Args, gens: &Generators, rng: &mut R, length: usize, reduce: Option<Reduction>,
+                           samples: usize, bins: usize) {
+    let precision = gens.precision;
+    let mut zero_traces = 0;
+    let mut values = Vec::with_capacity(samples);
+    for _ in 0..samples {
+        match log_abs_trace(&gens.eval(&random_word(rng, length, reduce))) {
+            Some(x) => values.push(x),
+            None => zero_traces += 1,
+        }
+    }
+    if values.is_empty() {
+        fail(Error::Degenerate("Every sampled word has trace zero.".to_string()))
+    }
+    let summary = summarize(&values, precision);
+    out!("samples = {}", summary.samples);
+    out!("zero_traces = {}", zero_traces);
+    out!("word_length = {}", length);
+    out!("mean = {}", summary.mean);
+    out!("std_dev = {}", summary.std_dev);
+    out!("skewness = {}", summary.skewness);
+    out!("excess_kurtosis = {}", summary.excess_kurtosis);
+    out!("min = {}", summary.min);
+    out!("max = {}", summary.max);
+    // log|tr| of a random product of n matrices is roughly normal with mean and
+    // variance growing linearly in n; these are the per-letter rates
+    if length > 0 {
+        let letters = Float::with_val(precision, length);
+        out!("mean_per_letter = {}", summary.mean.clone() / &letters);
+        out!("variance_per_letter = {}", summary.std_dev.clone().square() / &letters);
+    }
+    let histogram = histogram(&values, bins.max(1), precision);
+    for (i, count) in histogram.counts.iter().enumerate() {
+        out!("bin = {} {} {}", histogram.edges[i], histogram.edges[i + 1], count);
+    }
+    let counts: Vec<f64> = histogram.counts.iter().map(|&count| count as f64).collect();
+    print_plot(args, "plot_histogram", &counts, counts.len().saturating_sub(1));
+}
+
+fn clt_fit<R: Rng>(gens: &Generators, rng: &mut R, lengths: &[usize], reduce: Option<Reduction>, samples: usize) {
+    if lengths.iter().all(|&n| n == lengths[0]) {
+        fail(Error::Input("--clt-fit needs at least two distinct lengths.".to_string()))
+    }
+    let precision = gens.precision;
+    let mut xs = Vec::with_capacity(lengths.len());
+    let mut means = Vec::with_capacity(lengths.len());
+    let mut variances = Vec::with_capacity(lengths.len());
+    for &n in lengths {
+        let values: Vec<Float> = (0..samples)
+            .map(|_| log_norm(&gens.eval(&random_word(rng, n, reduce))))
+            .collect();
+        let summary = summarize(&values, precision);
+        let variance = summary.std_dev.square();
+        out!("length = {} {} {}", n, summary.mean, variance);
+        xs.push(Float::with_val(precision, n));
+        means.push(summary.mean);
+        variances.push(variance);
+    }
+    // log|M_n| is approximately normal with mean lambda n and variance sigma^2 n
+    let (lyapunov, mean_intercept) = linear_fit(&xs, &means, precision);
+    let (diffusion, variance_intercept) = linear_fit(&xs, &variances, precision);
+    out!("lyapunov_exponent = {}", lyapunov);
+    out!("mean_intercept = {}", mean_intercept);
+    out!("diffusion_coefficient = {}", diffusion);
+    out!("variance_intercept = {}", variance_intercept);
+}
+
+fn sample_statistics<R: Rng>(args: &Args, precision: u32, rng: &mut R, z: &C, fixed_word: Option<&str>,
+                             samples: usize) {
+    let mut trace_moduli = Vec::with_capacity(samples);
+    let mut log_eigenvalues = Vec::with_capacity(samples);
+    let mut largest: Option<(Float, C, String)> = None;
+    for _ in 0..samples {
+        let z = if args.random_z { random_z(rng, precision) } else { z.clone() };
+        let word = match args.random_word {
+            Some(n) => random_word(rng, n, args.reduce),
+            None => fixed_word.unwrap_or_default().to_string(),
+        };
+        let m = generators(args, precision, &z).eval(&word);
+        let modulus = Float::with_val(precision, m.trace().abs_ref());
+        let eigenvalue = match m.eigen_decomposition(precision) {
+            EigenDecomposition::Diagonalizable([(lambda, _), _]) => lambda,
+            EigenDecomposition::Jordan { eigenvalue, .. } | EigenDecomposition::Scalar(eigenvalue) => eigenvalue,
+        };
+        log_eigenvalues.push(Float::with_val(precision, eigenvalue.abs_ref()).ln());
+        if largest.as_ref().map_or(true, |(max, _, _)| modulus > *max) {
+            largest = Some((modulus.clone(), z, word));
+        }
+        trace_moduli.push(modulus);
+    }
+    if samples == 0 {
+        return;
+    }
+    let traces = summarize(&trace_moduli, precision);
+    out!("samples = {}", samples);
+    out!("mean_trace_modulus = {}", traces.mean);
+    out!("max_trace_modulus = {}", traces.max);
+    if let Some((_, z, word)) = largest {
+        if args.random_z {
+            out!("max_trace_z = {}", z);
+        }
+        if args.random_word.is_some() {
+            out!("max_trace_word = {}", word);
+        }
+    }
+    // log |lambda| of the dominant eigenvalue is half the translation length
+    let eigenvalues = summarize(&log_eigenvalues, precision);
+    out!("mean_log_eigenvalue_modulus = {}", eigenvalues.mean);
+    out!("std_dev_log_eigenvalue_modulus = {}", eigenvalues.std_dev);
+    let histogram = histogram(&log_eigenvalues, args.bins.max(1), precision);
+    for (i, count) in histogram.counts.iter().enumerate() {
+        out!("bin = {} {} {}", histogram.edges[i], histogram.edges[i + 1], count);
+    }
+}
+
+fn limit_set(args: &Args, gens: &Generators, depth: usize, out: Option<&Path>, #[cfg(feature = "render")] png: &PngArgs,
+             budget: &mut Budget, context: &RunContext) {
+    let resumed = args.resume.as_deref().map(|path| match read_checkpoint(path, gens) {
+        Progress::LimitSet { depth: d, words, points, csv_bytes, frontier } if d == depth => {
+            out!("resumed_at = {}", words);
+            (words, points, csv_bytes, frontier)
+        }
+        Progress::LimitSet { depth: d, .. } => {
+            fail(Error::Input(format!("Checkpoint {} is for --depth {}.", path.display(), d)))
+        }
+        Progress::Word { .. } => {
+            fail(Error::Input(format!("Checkpoint {} is for a word, not for limit-set.", path.display())))
+        }
+    });
+    #[cfg(feature = "render")]
+    if resumed.is_some() && png.png.is_some() {
+        fail(Error::Input("--resume carries on the CSV file of --out; the points of --png from before it are gone.".to_string()))
+    }
+    #[cfg(feature = "render")]
+    let mut raster = Raster::new(png);
+    let csv_bytes = resumed.as_ref().map_or(0, |(_, _, bytes, _)| *bytes);
+    let mut csv = out.map(|path| {
+        if csv_bytes == 0 {
+            let mut file = BufWriter::new(File::create(path).or_fail("could not create limit set file"));
+            limit_set::write_csv_header(&mut file, context).or_fail("could not write limit set file");
+            return file;
+        }
+        // the rows after the checkpoint are written again
+        let mut file = OpenOptions::new().write(true).open(path).unwrap_or_else(|e| {
+            fail(Error::Io(format!("Could not open {} to carry it on: {}", path.display(), e)))
+        });
+        file.set_len(csv_bytes).and_then(|_| file.seek(SeekFrom::End(0))).or_fail("could not truncate limit set file");
+        BufWriter::new(file)
+    });
+    let mut count = resumed.as_ref().map_or(0, |(_, points, _, _)| *points);
+    let mut words = resumed.as_ref().map_or(0, |(words, _, _, _)| *words);
+    let mut last = resumed.as_ref().map_or(String::new(), |(_, _, _, frontier)| frontier.word.clone());
+    let interval = Duration::from_secs_f64(args.checkpoint_interval);
+    let mut saved = Instant::now();
+    let save = |csv: &mut Option<BufWriter<File>>, count: u64, words: u64, frontier: Frontier| {
+        let Some(path) = &args.checkpoint else {
+            return;
+        };
+        let csv_bytes = match csv {
+            Some(file) => file.flush().and_then(|_| file.get_mut().stream_position()).or_fail("could not write limit set file"),
+            None => 0,
+        };
+        save_checkpoint(path, gens, Progress::LimitSet { depth, words, points: count, csv_bytes, frontier }, context);
+    };
+    let start = resumed.as_ref().map(|(words, _, _, frontier)| (*words, frontier));
+    for_each_limit_point_from(gens, depth, args.shard, start, budget, |progress, point| {
+        if let Some(point) = point {
+            count += 1;
+            if let Some(file) = &mut csv {
+                limit_set::write_csv_row(file, progress.word, point).or_fail("could not write limit set file");
+            }
+            #[cfg(feature = "render")]
+            if let Some(raster) = &mut raster {
+                raster.add(point);
+            }
+        }
+        words = progress.words;
+        last.clear();
+        last.push_str(progress.word);
+        if saved.elapsed() >= interval {
+            let frontier = Frontier { word: last.clone(), prefixes: progress.prefixes.to_vec() };
+            save(&mut csv, count, words, frontier);
+            saved = Instant::now();
+        }
+    });
+    save(&mut csv, count, words, Frontier::after(gens, &last));
+    if let Some(file) = &mut csv {
+        file.flush().or_fail("could not write limit set file");
+    }
+    out!("limit_points = {}", count);
+    #[cfg(feature = "render")]
+    if let Some(raster) = raster {
+        raster.write();
+    }
+    print_budget(budget);
+}
+
+/// The words of an orbit: all reduced words up to a length in a shard, or random ones.
+enum OrbitWords<'a> {
+    Depth(usize, Shard),
+    Random { samples: usize, length: usize, rng: &'a mut StdRng },
+}
+
+fn parse_basepoint(values: &[String], precision: u32) -> Result<Basepoint, String> {
+    match values {
+        [x, y, t] => {
+            let z = parse_complex_args(&[x.clone(), y.clone()], precision)?;
+            let t = Float::parse(t.trim())
+                .map(|t| Float::with_val(precision, t))
+                .map_err(|_| format!("invalid number {}", t))?;
+            if t <= 0 {
+                return Err("the height t must be positive".to_string());
+            }
+            Ok(Basepoint::Space(z, t))
+        }
+        _ => Point::parse(values, precision).map(Basepoint::Sphere),
+    }
+}
+
+fn orbit(gens: &Generators, basepoint: &Basepoint, words: OrbitWords, out: Option<&Path>,
+         #[cfg(feature = "render")] png: &PngArgs, budget: &mut Budget, context: &RunContext) {
+    #[cfg(feature = "render")]
+    let mut raster = Raster::new(png);
+    let mut csv = out.map(|path| {
+        let mut file = BufWriter::new(File::create(path).or_fail("could not create orbit file"));
+        orbit::write_csv_header(&mut file, basepoint, context).or_fail("could not write orbit file");
+        file
+    });
+    let mut count = 0u64;
+    let mut add = |word: &str, point: &Basepoint| {
+        count += 1;
+        if let Some(file) = &mut csv {
+            orbit::write_csv_row(file, word, point).or_fail("could not write orbit file");
+        }
+        #[cfg(feature = "render")]
+        if let (Some(raster), Some(z)) = (&mut raster, point.shadow()) {
+            raster.add(z);
+        }
+    };
+    match words {
+        OrbitWords::Depth(depth, shard) => for_each_orbit_point(gens, basepoint, depth, shard, budget, &mut add),
+        OrbitWords::Random { samples, length, rng } =>
+            for_each_random_orbit_point(gens, basepoint, length, samples, rng, &mut add),
+    }
+    if let Some(file) = &mut csv {
+        file.flush().or_fail("could not write orbit file");
+    }
+    out!("orbit_points = {}", count);
+    #[cfg(feature = "render")]
+    if let Some(raster) = raster {
+        raster.write();
+    }
+    print_budget(budget);
+}
+
+/// The image of --png, binned into a window as the points are found, or from the points
+/// kept until their extent is known when no --window is given.
+#[cfg(feature = "render")]
+struct Raster<'a> {
+    path: &'a Path,
+    width: u32,
+    density: Option<Density>,
+    points: Vec<(f64, f64)>,
+}
+
+#[cfg(feature = "render")]
+impl Raster<'_> {
+    /// `None` without --png.
+    fn new(args: &PngArgs) -> Option<Raster<'_>> {
+        let path = args.png.as_deref()?;
+        let window = args.window.as_deref();
+        if args.width == 0 || window.is_some_and(|w| !(w[0] < w[2] && w[1] < w[3])) {
+            fail(Error::Input("The PNG needs a positive width and a window with x0 < x1 and y0 < y1.".to_string()))
+        }
+        let density = window.map(|w| Density::new(Window { x0: w[0], y0: w[1], x1: w[2], y1: w[3] }, args.width));
+        Some(Raster { path, width: args.width, density, points: Vec::new() })
+    }
+
+    fn add(&mut self, point: &C) {
+        let (x, y) = (point.real().to_f64(), point.imag().to_f64());
+        match &mut self.density {
+            Some(density) => density.add(x, y),
+            None => self.points.push((x, y)),
+        }
+    }
+
+    fn write(self) {
+        let (points, width) = (self.points, self.width);
+        let density = self.density.or_else(|| {
+            let mut density = Density::new(Window::around(&points)?, width);
+            for &(x, y) in &points {
+                density.add(x, y);
+            }
+            Some(density)
+        });
+        match density {
+            Some(density) => {
+                let file = BufWriter::new(File::create(self.path).or_fail("could not create PNG file"));
+                density.write_png(file).or_fail("could not write PNG file");
+                let w = density.window;
+                out!("window = {} {} {} {}", w.x0, w.y0, w.x1, w.y1);
+                out!("image_size = {} {}", density.width, density.height);
+            }
+            None => eprintln!("No points to draw."),
+        }
+    }
+}
+
+/// Prints the outcome of --jorgensen; `incomplete` if the search stopped early.
+fn print_jorgensen(report: &JorgensenReport, incomplete: bool) {
+    out!("jorgensen_pairs = {} ({} skipped as reducible)", report.checked, report.skipped);
+    if let Some(min) = &report.min {
+        out!("jorgensen_min = {} {} {}", min.sum, min.first, min.second);
+    }
+    match &report.first_violation {
+        None if incomplete => out!("jorgensen = incomplete"),
+        None => out!("jorgensen = ok"),
+        Some(pair) => {
+            out!("jorgensen_violation = {} {} {}", pair.sum, pair.first, pair.second);
+            out!("jorgensen = violated ({} pairs)", report.violations);
+        }
+    }
+}
+
+/// Prints the outcome of --verify-up-to and returns the exit status: 0 if every
+/// check passed, 1 if one failed and 2 if the search stopped before either was known.
+fn print_verify(results: &[CheckResult], incomplete: bool) -> i32 {
+    for result in results {
+        out!("{} = {}", result.check, result);
+    }
+    if !results.iter().all(|r| r.passed()) {
+        out!("verify = fail");
+        STATUS_FAILED
+    } else if incomplete {
+        out!("verify = incomplete");
+        STATUS_INCOMPLETE
+    } else {
+        out!("verify = pass");
+        0
+    }
+}
+
+fn write_results(path: Option<&Path>, results: &Results, context: &RunContext) {
+    if let Some(path) = path {
+        let mut file = BufWriter::new(File::create(path).or_fail("could not create results file"));
+        results.write(&mut file, context).and_then(|_| file.flush()).or_fail("could not write results file");
+    }
+}
+
+/// The file of --restore.
+fn read_snapshot(path: &Path) -> Snapshot {
+    let input = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        fail(Error::Io(format!("Could not read {}: {}", path.display(), e)))
+    });
+    snapshot::read(&input).unwrap_or_else(|e| {
+        fail(Error::Input(format!("Invalid snapshot {}: {}", path.display(), e)))
+    })
+}
+
+/// --snapshot, carrying over the cusps of --restore and, unless the run has results of
+/// its own, the restored results.
+fn write_snapshot(args: &Args, gens: &Generators, results: Option<Results>, context: &RunContext) {
+    let Some(path) = &args.snapshot else {
+        return;
+    };
+    let restored = args.restore.as_deref().map(read_snapshot);
+    let (cusps, restored_results) = match restored {
+        Some(r) => (r.cusps, r.results),
+        None => (CuspCache { cusps: Vec::new() }, None),
+    };
+    let snapshot = Snapshot { generators: gens.clone(), cusps, results: results.or(restored_results), progress: None };
+    let mut file = BufWriter::new(File::create(path).or_fail("could not create snapshot file"));
+    snapshot.write(&mut file, context).and_then(|_| file.flush()).or_fail("could not write snapshot file");
+}
+
+/// The progress in the snapshot of --resume, which has to be for the generators of the run.
+fn read_checkpoint(path: &Path, gens: &Generators) -> Progress {
+    let input = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        fail(Error::Io(format!("Could not read {}: {}", path.display(), e)))
+    });
+    let snapshot = snapshot::read(&input).unwrap_or_else(|e| {
+        fail(Error::Input(format!("Invalid checkpoint {}: {}", path.display(), e)))
+    });
+    if !snapshot.is_for(gens) {
+        fail(Error::Input(format!("Checkpoint {} was written for other generators or another precision.", path.display())))
+    }
+    snapshot.progress.unwrap_or_else(|| {
+        fail(Error::Input(format!("{} is a snapshot without progress; --resume takes a file written with --checkpoint.",
+                                  path.display())))
+    })
+}
+
+/// --checkpoint: a snapshot of the generators with the progress.
+fn save_checkpoint(path: &Path, gens: &Generators, progress: Progress, context: &RunContext) {
+    let snapshot = Snapshot { generators: gens.clone(), cusps: CuspCache { cusps: Vec::new() }, results: None,
+                              progress: Some(progress) };
+    checkpoint::save(&snapshot, path, context).unwrap_or_else(|e| {
+        fail(Error::Io(format!("Could not write checkpoint {}: {}", path.display(), e)))
+    });
+}
+
+/// Letters multiplied out at a time by `checkpointed_eval`, between looks at the clock.
+const CHECKPOINT_CHUNK: usize = 4096;
+
+/// The matrix of a word for --checkpoint and --resume: the word is multiplied out from
+/// the left a chunk at a time, each chunk as by `Generators::eval`, and the product so far
+/// is saved every --checkpoint-interval seconds and once more at the end.
+fn checkpointed_eval(args: &Args, gens: &Generators, word: &str, context: &RunContext) -> M<C> {
+    let (length, digest) = (word.len(), checkpoint::digest(word));
+    let (mut position, mut product) = match &args.resume {
+        None => (0, M::identity(gens.precision)),
+        Some(path) => match read_checkpoint(path, gens) {
+            Progress::Word { length: l, digest: d, position, product } if l == length && d == digest => {
+                out!("resumed_at = {}", position);
+                (position, product)
+            }
+            Progress::Word { .. } => {
+                fail(Error::Input(format!("Checkpoint {} is for another word; pass the --seed that it printed for --random-word.",
+                                          path.display())))
+            }
+            Progress::LimitSet { .. } => {
+                fail(Error::Input(format!("Checkpoint {} is for limit-set, not for a word.", path.display())))
+            }
+        },
+    };
+    let save = |position: usize, product: &M<C>| if let Some(path) = &args.checkpoint {
+        save_checkpoint(path, gens, Progress::Word { length, digest, position, product: product.clone() }, context);
+    };
+    let interval = Duration::from_secs_f64(args.checkpoint_interval);
+    let mut saved = Instant::now();
+    while position < length {
+        let end = (position + CHECKPOINT_CHUNK).min(length);
+        product = product.mul(gens.eval(&word[position..end]));
+        position = end;
+        if saved.elapsed() >= interval {
+            save(position, &product);
+            saved = Instant::now();
+        }
+    }
+    save(position, &product);
+    product
+}
+
+/// Reads and merges result files from --results, prints the combined outcome and
+/// optionally writes it to `out` for further merging.
+fn merge(files: &[PathBuf], out: Option<&Path>) {
+    let runs = files.iter().map(|path| {
+        let input = std::fs::read_to_string(path).unwrap_or_else(|e| {
+            fail(Error::Io(format!("Could not read {}: {}", path.display(), e)))
+        });
+        results::read(&input).unwrap_or_else(|e| {
+            fail(Error::Input(format!("Invalid results file {}: {}", path.display(), e)))
+        })
+    }).collect();
+    let merged = results::merge(runs).unwrap_or_else(|e| {
+        fail(Error::Input(format!("Cannot merge: {}", e)))
+    });
+    out!("results = {}", merged.search());
+    out!("shards = {} of {}", merged.shards.len(), merged.shard_count);
+    out!("nodes = {}", merged.nodes);
+    out!("complete = {}", merged.complete());
+    let status = match &merged.body {
+        Body::Spectrum(entries) => {
+            out!("classes = {}", entries.len());
+            0
+        }
+        Body::Verify(checked) => print_verify(checked, !merged.complete()),
+        Body::Jorgensen(report) => {
+            print_jorgensen(report, !merged.complete());
+            0
+        }
+    };
+    // the representation is recorded in the results themselves
+    let context = RunContext { family: "-".to_string(), parameter: "-".to_string(), precision: Some(merged.precision) };
+    write_results(out, &merged, &context);
+    std::process::exit(status)
+}
+
+/// Reports a search that ran out of budget; nothing is printed if it finished.
+fn print_budget(budget: &Budget) {
+    if let Some(reason) = budget.exhausted() {
+        out!("search = stopped ({} after {} nodes)", reason, budget.nodes());
+    }
+}
+
+/// Checks the ping-pong lemma with the disks of --disk, or the isometric disks by
+/// default, and returns the disks.
+fn ping_pong(gens: &Generators, disk_args: &[String]) -> [Disk; 4] {
+    let precision = gens.precision;
+    let disks =
+        if disk_args.is_empty() {
+            match isometric_disks(gens) {
+                Some(disks) => disks,
+                None => {
+                    fail(Error::Degenerate("A generator fixes infinity, so it has no isometric disk; pass --disk.".to_string()))
+                }
+            }
+        } else {
+            let mut disks: [Option<Disk>; 4] = Default::default();
+            for chunk in disk_args.chunks(4) {
+                let i = word::LETTERS.iter().position(|l| chunk[0] == l.to_string());
+                let values: Result<Vec<f64>, _> = chunk[1..].iter().map(|v| v.parse::<f64>()).collect();
+                match (i, values) {
+                    (Some(i), Ok(v)) => disks[i] = Some(Disk {
+                        center: Complex::with_val(precision, (v[0], v[1])),
+                        radius: Float::with_val(precision, v[2]),
+                    }),
+                    _ => {
+                        fail(Error::Input(format!("Invalid --disk {}: expected a letter in {{a,b,A,B}} and three numbers.", chunk.join(" "))))
+                    }
+                }
+            }
+            let [Some(a), Some(b), Some(inv_a), Some(inv_b)] = disks else {
+                fail(Error::Input("--disk must be given once for each of a, b, A, B.".to_string()))
+            };
+            [a, b, inv_a, inv_b]
+        };
+
+    for (x, disk) in word::LETTERS.iter().zip(disks.iter()) {
+        out!("disk_{} = {}", x, disk);
+    }
+    match pingpong::verify(gens, &disks) {
+        Ok(inclusions) => {
+            for inclusion in inclusions {
+                out!("image_{} = {}", inclusion.letter, inclusion.image);
+                out!("margin_{} = {}", inclusion.letter, inclusion.margin);
+            }
+            out!("ping_pong = verified");
+        }
+        Err(failure) => out!("ping_pong = failed: {}", failure),
+    }
+    disks
+}
+
+/// Redoes the ping-pong check in ball arithmetic for --certify.
+#[cfg(feature = "rigorous")]
+fn certify(precision: u32, z: &C, disks: [Disk; 4], certificate_path: Option<&Path>, context: &RunContext) {
+    match certificate::certify(precision, Ball::rounded(z.clone()), disks) {
+        Ok(certificate) => {
+            println!("{}", certificate);
+            if let Some(path) = certificate_path {
+                let mut file = BufWriter::new(File::create(path).or_fail("could not create certificate file"));
+                certificate.write(&mut file, context).or_fail("could not write certificate file");
+            }
+        }
+        Err(failure) => out!("certificate = failed: {}", failure),
+    }
+}
+
+#[cfg(feature = "rigorous")]
+fn verify(path: &Path) {
+    let input = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        fail(Error::Io(format!("Could not read {}: {}", path.display(), e)))
+    });
+    match certificate::verify(&input) {
+        Ok(certificate) => {
+            println!("{}", certificate);
+            out!("verified = true");
+        }
+        Err(reason) => {
+            out!("verified = false: {}", reason);
+            std::process::exit(STATUS_FAILED)
+        }
+    }
+}
+
+/// Prints the traces of the generators as in the tables of Indra's Pearls.
+fn print_indra(gens: &Generators) {
+    let precision = gens.precision;
+    let small = |x: C| Float::with_val(precision, x.abs_ref()) <= tolerance(precision);
+    let [ta, tb, tab] = gens.trace_coordinates();
+    out!("ta = {}", ta);
+    out!("tb = {}", tb);
+    out!("tab = {}", tab);
+    // the sign of each generator is lost in PSL(2,C), so tb = -2 is as good as 2
+    let maskit = small(tb.clone().square() - 4u32) && small(gens.commutator_trace() + 2u32);
+    if maskit {
+        let i = C::with_val(precision, (0, 1));
+        let mu = i * ta;
+        let mu = if mu.imag().is_sign_negative() { -mu } else { mu };
+        out!("mu = {}", mu);
+    } else {
+        out!("mu = none (tb is not 2 or tr[a, b] is not -2)");
+    }
+}
+
+/// --tau: the action of the result on the upper half-plane, checked against the
+/// invariance of j under SL(2,Z).
+fn print_modular(res: &M<C>, tau: &[String], precision: u32) {
+    let tau = parse_complex_args(tau, precision).unwrap_or_else(|e| {
+        fail(Error::Input(format!("Invalid --tau: {}", e)))
+    });
+    let Some(j) = modular::j_invariant(&tau) else {
+        fail(Error::Input("--tau must be in the upper half-plane.".to_string()))
+    };
+    out!("tau = {}", tau);
+    out!("j_tau = {}", j);
+    if !modular::is_integral(res) {
+        out!("modular = none (the result is not in SL(2,Z))");
+        return;
+    }
+    let Point::Finite(image) = mobius::apply(res, &Point::Finite(tau)) else {
+        unreachable!("a real matrix does not send the upper half-plane to infinity")
+    };
+    let j_image = modular::j_invariant(&image).expect("SL(2,Z) preserves the upper half-plane");
+    let scale = Float::with_val(precision, j.abs_ref()).max(&Float::with_val(precision, 1));
+    let error = Float::with_val(precision, (j_image.clone() - &j).abs_ref()) / scale;
+    out!("gamma_tau = {}", image);
+    out!("j_gamma_tau = {}", j_image);
+    out!("j_relative_error = {}", error);
+    out!("modular = {}", if error <= tolerance(precision) { "ok" } else { "failed" });
+}
+
+/// The parameters of the generators, the result, its trace and its eigen-data for --format.
+fn print_cas(args: &Args, precision: u32, gens: &Generators, z: &C, res: &M<C>, format: Format) {
+    let entry = |name, symbol, value| Entry { name, symbol, value };
+    let parse = |values: &Option<Vec<String>>| {
+        let values = values.as_deref().expect("checked by generators");
+        Value::Number(parse_complex_args(values, precision).expect("checked by generators"))
+    };
+    let mut entries = if args.matrix_a.is_some() {
+        vec![entry("a", "a", Value::Matrix(gens.a.clone())), entry("b", "b", Value::Matrix(gens.b.clone()))]
+    } else {
+        match args.param {
+            Parameterization::Z => vec![entry("z", "z", Value::Number(z.clone()))],
+            Parameterization::Maskit => vec![entry("mu", "\\mu", Value::Number(z.clone()))],
+            Parameterization::Riley => vec![entry("rho", "\\rho", Value::Number(z.clone()))],
+            Parameterization::Trace =>
+                vec![entry("ta", "t_a", Value::Number(z.clone())), entry("tb", "t_b", parse(&args.tb))],
+            Parameterization::Grandma =>
+                vec![entry("ta", "t_a", Value::Number(z.clone())), entry("tab", "t_{ab}", parse(&args.tab))],
+        }
+    };
+    entries.push(entry("m", "M", Value::Matrix(res.clone())));
+    entries.push(entry("trace", "\\operatorname{tr} M", Value::Number(res.trace())));
+    match res.eigen_decomposition(precision) {
+        EigenDecomposition::Diagonalizable([(lambda1, v1), (lambda2, v2)]) => {
+            entries.push(entry("lambda1", "\\lambda_1", Value::Number(lambda1)));
+            entries.push(entry("v1", "v_1", Value::Vector(v1)));
+            entries.push(entry("lambda2", "\\lambda_2", Value::Number(lambda2)));
+            entries.push(entry("v2", "v_2", Value::Vector(v2)));
+        }
+        EigenDecomposition::Jordan { eigenvalue, eigenvector, generalized } => {
+            entries.push(entry("lambda1", "\\lambda_1", Value::Number(eigenvalue)));
+            entries.push(entry("v1", "v_1", Value::Vector(eigenvector)));
+            entries.push(entry("w1", "w_1", Value::Vector(generalized)));
+        }
+        EigenDecomposition::Scalar(lambda) => entries.push(entry("lambda1", "\\lambda_1", Value::Number(lambda))),
+    }
+    cas::write(&mut std::io::stdout().lock(), format, precision, &entries).or_fail("could not write to stdout");
+}
+
+fn print_real_form(gens: &Generators) {
+    match real_form(gens) {
+        Ok(form) => {
+            let entries = |m: &M<C>| m.0.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(" ");
+            let real_entries = |m: &M<Float>| m.0.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(" ");
+            out!("real_form = found");
+            out!("real_form_conjugator = {}", entries(&form.conjugator));
+            out!("real_a = {}", real_entries(&form.a));
+            out!("real_b = {}", real_entries(&form.b));
+            out!("real_form_residual = {}", form.residual);
+        }
+        Err(e) => out!("real_form = none: {}", e),
+    }
+}
+
+/// --normal-form: N = P res P^-1, with the largest entry of P res P^-1 - N as computed.
+fn print_normal_form(res: &M<C>, precision: u32) {
+    let entries = |m: &M<C>| m.0.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(" ");
+    let (normal, p) = res.normal_form(precision);
+    let conjugated = p.clone().mul(res.clone()).mul(p.clone().inv());
+    let residual = conjugated.0.iter().zip(&normal.0)
+        .map(|(x, y)| Float::with_val(precision, (x.clone() - y).abs_ref()))
+        .fold(Float::with_val(precision, 0), |r, x| r.max(&x));
+    out!("normal_form = {}", entries(&normal));
+    out!("normal_form_conjugator = {}", entries(&p));
+    out!("normal_form_residual = {}", residual);
+}
+
+/// The metadata for the files of a run at the parameter z, or without one for a scan.
+fn run_context(args: &Args, precision: u32, z: Option<&C>) -> RunContext {
+    let (family, parameter) = match (&args.matrix_a, &args.matrix_b) {
+        _ if args.restore.is_some() =>
+            ("snapshot".to_string(), args.restore.as_deref().expect("checked").display().to_string()),
+        (Some(a), Some(b)) => ("matrices".to_string(), format!("a = {}; b = {}", a.join(" "), b.join(" "))),
+        _ => {
+            let family = args.param.to_possible_value().expect("no skipped values").get_name().to_string();
+            let parameter = match (z, args.param) {
+                (None, _) => "-".to_string(),
+                (Some(z), Parameterization::Trace) => format!("{}; tb = {}", z, args.tb.as_deref().unwrap_or_default().join(" ")),
+                (Some(z), Parameterization::Grandma) =>
+                    format!("{}; tab = {}", z, args.tab.as_deref().unwrap_or_default().join(" ")),
+                (Some(z), _) => z.to_string(),
+            };
+            (family, parameter)
+        }
+    };
+    let extra: Vec<String> = extra_names(args).iter().zip(args.matrix.chunks(4))
+        .map(|(name, entries)| format!("; {} = {}", name, entries.join(" ")))
+        .collect();
+    let conjugated = args.conjugate_by.as_ref()
+        .map_or(String::new(), |p| format!("; conjugated by {}", p.join(" ")));
+    RunContext { family, parameter: parameter + &extra.concat() + &conjugated, precision: Some(precision) }
+}
+
+/// The generators given by --restore, by --matrix-a/--matrix-b or by --param applied to z.
+fn generators(args: &Args, precision: u32, z: &C) -> Generators {
+    let gens = if let Some(path) = &args.restore {
+        let restored = read_snapshot(path).generators;
+        let at = |m: &M<C>| M(m.0.clone().map(|x| Complex::with_val(precision, x)));
+        let gens = Generators::from_matrices(precision, at(&restored.a), at(&restored.b));
+        restored.extra.iter().fold(gens, |gens, g| gens.with_extra(g.name, at(&g.m)))
+    } else if let (Some(a), Some(b)) = (&args.matrix_a, &args.matrix_b) {
+        let parse = |entries: &[String], name: &str| parse_sl2(entries, precision).unwrap_or_else(|e| {
+            fail(Error::Input(format!("Invalid --{}: {}", name, e)))
+        });
+        Generators::from_matrices(precision, parse(a, "matrix-a"), parse(b, "matrix-b"))
+    } else {
+        match args.param {
+            Parameterization::Z => {
+                rho::check_z(z).unwrap_or_else(|e| fail(e));
+                Generators::new(precision, z.clone())
+            }
+            Parameterization::Maskit => {
+                let (a, b) = rho::maskit(precision, z.clone());
+                Generators::from_matrices(precision, a, b)
+            }
+            Parameterization::Riley => {
+                let (a, b) = rho::riley(precision, z.clone());
+                Generators::from_matrices(precision, a, b)
+            }
+            Parameterization::Trace => {
+                let Some(tb) = &args.tb else {
+                    fail(Error::Input("--param trace needs --tb.".to_string()))
+                };
+                let tb = parse_complex_args(tb, precision).unwrap_or_else(|e| {
+                    fail(Error::Input(format!("Invalid --tb: {}", e)))
+                });
+                let (a, b) = rho::from_traces(precision, z.clone(), tb);
+                Generators::from_matrices(precision, a, b)
+            }
+            Parameterization::Grandma => {
+                let Some(tab) = &args.tab else {
+                    fail(Error::Input("--param grandma needs --tab.".to_string()))
+                };
+                let tab = parse_complex_args(tab, precision).unwrap_or_else(|e| {
+                    fail(Error::Input(format!("Invalid --tab: {}", e)))
+                });
+                let (a, b) = rho::from_ta_tab(precision, z.clone(), tab);
+                Generators::from_matrices(precision, a, b)
+            }
+        }
+    };
+    gens.check_finite().unwrap_or_else(|e| fail(e));
+    let gens = extra_generators(args, precision, gens);
+    match &args.conjugate_by {
+        Some(p) => gens.conjugate(&conjugator(p, precision)),
+        None => gens,
+    }
+}
+
+/// The matrix of --conjugate-by, which must be invertible.
+fn conjugator(entries: &[String], precision: u32) -> M<C> {
+    let entries: Vec<C> = entries.iter().map(|e| parse_complex(e, precision)).collect::<Result<_, _>>()
+        .unwrap_or_else(|e| {
+            fail(Error::Input(format!("Invalid --conjugate-by: {}", e)))
+        });
+    let p = M([entries[0].clone(), entries[1].clone(), entries[2].clone(), entries[3].clone()]);
+    if Float::with_val(precision, p.det().abs_ref()) <= tolerance(precision) {
+        fail(Error::Input("Invalid --conjugate-by: the matrix is not invertible.".to_string()))
+    }
+    p
+}
+
+/// The names of the generators of --matrix: those of --name, or c, d, e, ... by default.
+fn extra_names(args: &Args) -> Vec<char> {
+    let count = args.matrix.len() / 4;
+    if !args.name.is_empty() && args.name.len() != count {
+        fail(Error::Input("Give one --name for each --matrix, or none.".to_string()))
+    }
+    if args.name.is_empty() { ('c'..='z').take(count).collect() } else { args.name.clone() }
+}
+
+/// Adds the generators of --matrix and --name, and checks that --word uses no others.
+fn extra_generators(args: &Args, precision: u32, gens: Generators) -> Generators {
+    let mut gens = gens;
+    for (name, entries) in extra_names(args).into_iter().zip(args.matrix.chunks(4)) {
+        if !name.is_ascii_lowercase() || name == 'a' || name == 'b' || gens.has_letter(name) {
+            fail(Error::Input(format!("Invalid --name {}: generators are named by distinct lowercase letters other than a and b.", name)))
+        }
+        let m = parse_sl2(entries, precision).unwrap_or_else(|e| {
+            fail(Error::Input(format!("Invalid --matrix for {}: {}", name, e)))
+        });
+        gens = gens.with_extra(name, m);
+    }
+    if let Some(c) = args.word.as_deref().and_then(|w| w.chars().find(|&c| !gens.has_letter(c))) {
+        fail(Error::Input(format!("--word uses {}, which is not a generator; add it with --matrix and --name.", c)))
+    }
+    gens
+}
+
+pub fn main() {
+    let args = Args::try_parse().unwrap_or_else(|e| {
+        // --help and --version are not errors
+        if !e.use_stderr() {
+            e.exit()
+        }
+        let _ = e.print();
+        std::process::exit(STATUS_INPUT)
+    });
+    STYLE.get_or_init(|| Style { digits: args.digits.map(|d| d as usize), notation: args.notation, polar: args.polar });
+    if args.estimate {
+        estimate(&args, args.command.as_ref());
+        return;
+    }
+    match &args.command {
+        #[cfg(feature = "rigorous")]
+        Some(Command::Verify { certificate }) => {
+            verify(certificate);
+            return;
+        }
+        Some(Command::Merge { files, out }) => {
+            merge(files, out.as_deref());
+            return;
+        }
+        Some(Command::BoundaryArc { from, to, max_height, max_iterations, out }) => {
+            boundary_arc(&args, *from, *to, *max_height, *max_iterations, out.as_deref());
+            return;
+        }
+        Some(Command::TraceDegree { p, q }) => {
+            trace_degree(&args, *p, *q);
+            return;
+        }
+        Some(Command::ContinuedFraction { b0, a, b, terms }) => {
+            continued_fraction(&args, b0, a, b, terms.unwrap_or(a.len().max(b.len())));
+            return;
+        }
+        Some(Command::Bench { lengths, precisions, routines, warm_up, measurement_time, sample_size, out }) => {
+            let seconds = |t: f64, name: &str| {
+                if !(t >= 0.0 && t.is_finite()) {
+                    fail(Error::Input(format!("--{} must be a nonnegative number of seconds.", name)))
+                }
+                Duration::from_secs_f64(t)
+            };
+            let settings = bench::Settings {
+                warm_up: seconds(*warm_up, "warm-up"),
+                measurement: seconds(*measurement_time, "measurement-time"),
+                sample_size: *sample_size as usize,
+            };
+            bench(&args, lengths, precisions, routines, &settings, out.as_deref());
+            return;
+        }
+        Some(Command::MarkovSpectrum { radius }) => {
+            let eigenvalues = markov_spectrum(*radius);
+            out!("vertices = {}", eigenvalues.len());
+            out!("spectral_radius = {}", eigenvalues.iter().fold(0.0f64, |r, x| r.max(x.abs())));
+            out!("kesten_bound = {}", 3f64.sqrt() / 2.0);
+            for x in eigenvalues {
+                out!("eigenvalue = {}", x);
+            }
+            return;
+        }
+        _ => {}
+    }
+    if let Some(coefficients) = &args.exact {
+        exact(&args, coefficients);
+        return;
+    }
+    if let Some(parameter) = &args.gaussian {
+        std::process::exit(gaussian(&args, parameter));
+    }
+    if let Some((p, q)) = args.cusp_catalog {
+        cusp_catalog(&args, p, q);
+        return;
+    }
+    let spec = match (&args.sweep, &args.sweep_file) {
+        (Some(spec), _) => Some(spec.clone()),
+        (None, Some(path)) => Some(std::fs::read_to_string(path).unwrap_or_else(|e| {
+            fail(Error::Io(format!("Could not read {}: {}", path.display(), e)))
+        })),
+        (None, None) => None,
+    };
+    let status = match spec {
+        Some(spec) => run_sweep(&args, &spec),
+        None => run(args, &mut None),
+    };
+    if status != 0 {
+        std::process::exit(status)
+    }
+}
+
+fn cusp_catalog(args: &Args, p: u64, q: u64) {
+    let Some(precision) = args.precision else {
+        fail(Error::Input("--precision must be provided.".to_string()))
+    };
+    let mut cache = args.cusp_cache.as_deref().filter(|path| path.exists()).map(|path| {
+        let input = std::fs::read_to_string(path).unwrap_or_else(|e| {
+            fail(Error::Io(format!("Could not read {}: {}", path.display(), e)))
+        });
+        CuspCache::read(&input).unwrap_or_else(|e| {
+            fail(Error::Input(format!("Invalid cusp cache {}: {}", path.display(), e)))
+        })
+    }).unwrap_or(CuspCache { cusps: Vec::new() });
+    let cusp = match cache.get(p, q, precision) {
+        Some(cusp) => cusp,
+        None => match cusp::maskit_cusp(p, q, precision, iteration_limit(args)).unwrap_or_else(|e| limit_reached(e)) {
+            Some(cusp) => cusp,
+            None => {
+                fail(Error::Degenerate("The roots of the trace polynomial did not converge; increase --precision.".to_string()))
+            }
+        },
+    };
+    let slope = ExtendedRational::R(Rational::from((p, q)));
+    let word = slope_letters(args, slope.clone());
+    let (a, b) = rho::maskit(precision, cusp.mu.clone());
+    let trace = Generators::from_matrices(precision, a, b).eval(&word).trace();
+    out!("slope = {}", slope);
+    out!("word = {}", word);
+    out!("cusp = {}", cusp.mu);
+    out!("cusp_trace = {}", trace);
+    out!("cusp_source = {}", cusp.source);
+    if let (Some(path), Source::Solved) = (&args.cusp_cache, cusp.source) {
+        cache.insert(precision, cusp);
+        let context = RunContext { family: "maskit".to_string(), parameter: "-".to_string(), precision: None };
+        let mut file = BufWriter::new(File::create(path).or_fail("could not create cusp cache file"));
+        cache.write(&mut file, &context).and_then(|_| file.flush()).or_fail("could not write cusp cache file");
+    }
+}
+
+fn boundary_arc(args: &Args, from: (u64, u64), to: (u64, u64), max_height: u64, max_iterations: usize,
+                out: Option<&Path>) {
+    let Some(precision) = args.precision else {
+        fail(Error::Input("--precision must be provided.".to_string()))
+    };
+    if from.0 as u128 * to.1 as u128 >= to.0 as u128 * from.1 as u128 {
+        fail(Error::Input("The slope to end at must be above the slope to start from.".to_string()))
+    }
+    if from.0.max(from.1).max(to.0).max(to.1) > max_height {
+        fail(Error::Input("--max-height must be at least the heights of both ends.".to_string()))
+    }
+    let arc = cusp::boundary_arc(from, to, max_height, precision, max_iterations).unwrap_or_else(|(p, q)| {
+        fail(Error::Degenerate(format!("Newton's method did not converge for the cusp of {}/{}; increase --max-iterations.", p, q)))
+    });
+    let slope = |node: &cusp::Node| Rational::from((node.p, node.q));
+    for node in &arc {
+        out!("{} {}", slope(node), node.mu.as_ref().expect("only 1/0 has no cusp"));
+    }
+    out!("points = {}", arc.len());
+    if let (Some(first), Some(last)) = (arc.first(), arc.last()) {
+        out!("from_word = {}", first.word);
+        out!("to_word = {}", last.word);
+    }
+    if let Some(path) = out {
+        let context = RunContext { family: "maskit".to_string(), parameter: "-".to_string(), precision: Some(precision) };
+        let mut file = BufWriter::new(File::create(path).or_fail("could not create arc file"));
+        context.write_comments(&mut file).or_fail("could not write arc file");
+        writeln!(file, "x,y,slope").or_fail("could not write arc file");
+        for node in &arc {
+            let mu = node.mu.as_ref().expect("only 1/0 has no cusp");
+            writeln!(file, "{},{},{}", mu.real(), mu.imag(), slope(node)).or_fail("could not write arc file");
+        }
+        file.flush().or_fail("could not write arc file");
+    }
+}
+
+/// --estimate: each job is counted in the nodes it visits and the complex multiplications
+/// it does, leaving out the cheaper additions and the few square roots and logarithms.
+fn estimate(args: &Args, job: Option<&Command>) {
+    let Some(precision) = args.precision else {
+        fail(Error::Input("--precision must be provided.".to_string()))
+    };
+    let rank = 2 + args.matrix.len() as u128 / 4;
+    let matrix = cost::MATRIX_MULTIPLICATION;
+    let (name, nodes, multiplications) = match job {
+        Some(Command::Spectrum { max_length }) => {
+            // a class of length n is multiplied out in at most n - 1 products
+            let classes: Vec<(u128, u128)> = (1..=*max_length)
+                .map(|n| (cost::conjugacy_classes(n) / args.shard.count as u128, n as u128 - 1))
+                .collect();
+            let nodes = classes.iter().map(|&(c, _)| c).fold(0, u128::saturating_add);
+            let products = classes.iter().map(|&(c, n)| c.saturating_mul(n)).fold(0, u128::saturating_add);
+            ("spectrum", nodes, products.saturating_mul(matrix))
+        }
+        // an upper bound: the trace bound prunes some subtrees
+        Some(Command::FindWord { max_length, .. }) => {
+            let nodes = cost::reduced_words(rank, *max_length);
+            ("find-word", nodes, nodes.saturating_mul(matrix))
+        }
+        Some(Command::Farey { max_height, check_matrices, fixed_points }) => {
+            let nodes = cost::farey_nodes(*max_height);
+            let per_node = if *check_matrices || fixed_points.is_some() { 1 + matrix } else { 1 };
+            ("farey", nodes, nodes.saturating_mul(per_node))
+        }
+        Some(Command::EnumeratePrimitives { max_q }) => {
+            let nodes = cost::farey_nodes(*max_q);
+            ("enumerate-primitives", nodes, nodes)
+        }
+        // the positive and the negative slopes
+        Some(Command::McShane { max_height }) => {
+            let nodes = cost::farey_nodes(*max_height).saturating_mul(2);
+            ("mcshane", nodes, nodes)
+        }
+        Some(Command::Scan { resolution, max_height, .. }) => {
+            let points = resolution.iter().map(|&n| n as u128).product::<u128>();
+            let nodes = points.saturating_mul(cost::farey_nodes(*max_height).saturating_mul(2));
+            ("scan", nodes, nodes)
+        }
+        _ => {
+            fail(Error::Input("--estimate supports spectrum, find-word, farey, enumerate-primitives, mcshane and scan.".to_string()))
+        }
+    };
+    let multiplication = cost::calibrate(precision, Duration::from_millis(100));
+    out!("job = {}", name);
+    out!("nodes = {}", nodes);
+    out!("complex_multiplications = {}", multiplications);
+    out!("seconds_per_multiplication = {:e}", multiplication.as_secs_f64());
+    out!("predicted_seconds = {}", multiplications as f64 * multiplication.as_secs_f64());
+}
+
+/// The bench subcommand. The time of a complex multiplication is calibrated at each
+/// precision, as for --estimate, and `arithmetic_share` is the part of the mean time that
+/// the multiplications of a routine would take at that rate; the rest goes to additions,
+/// allocations and copies.
+fn bench(args: &Args, lengths: &[u64], precisions: &[u32], routines: &[Routine], settings: &bench::Settings,
+         out: Option<&Path>) {
+    let slopes: Vec<(u64, u64, u64)> = lengths.iter()
+        .map(|&length| match bench::slope(length) {
+            Some((p, q)) => (length, p, q),
+            None => fail(Error::Input(format!("Invalid --lengths {}: a word needs at least 3 letters.", length))),
+        })
+        .collect();
+    let seed = args.seed.unwrap_or_else(rand::random);
+    if args.random_z {
+        out!("seed = {}", seed);
+    }
+    let parameter = |precision: u32| {
+        if args.random_z {
+            random_z(&mut StdRng::seed_from_u64(seed), precision)
+        } else if let Some(z) = &args.z {
+            parse_complex_args(z, precision).unwrap_or_else(|e| fail(Error::Input(format!("Invalid -z: {}", e))))
+        } else if args.matrix_a.is_some() || args.restore.is_some() {
+            // unused: the generators are given directly
+            Complex::with_val(precision, 0)
+        } else {
+            fail(Error::Input("At least one of z, random-z, matrix-a, restore must be provided.".to_string()))
+        }
+    };
+    let mut csv = out.map(|path| {
+        let mut file = BufWriter::new(File::create(path).or_fail("could not create bench file"));
+        let context = RunContext { precision: None, ..run_context(args, 64, Some(&parameter(64))) };
+        context.write_comments(&mut file).or_fail("could not write bench file");
+        writeln!(file, "routine,length,precision,slope,complex_multiplications,iterations,samples,mean,mean_low,mean_high,\
+                        std_dev,median,mad,min,max,mild_outliers,severe_outliers,letters_per_second,arithmetic_share")
+            .or_fail("could not write bench file");
+        file
+    });
+    let limit = iteration_limit(args);
+    for &precision in precisions {
+        let gens = generators(args, precision, &parameter(precision));
+        let (a, b) = (gens.a.clone(), gens.b.clone());
+        let (ta, tb, tab) = (a.trace(), b.trace(), a.clone().mul(b.clone()).trace());
+        let multiplication = cost::calibrate(precision, Duration::from_millis(100)).as_secs_f64();
+        out!("precision = {}", precision);
+        out!("seconds_per_multiplication = {:e}", multiplication);
+        for &(length, p, q) in &slopes {
+            let slope = ExtendedRational::R(Rational::from((p, q)));
+            let letters = stern_brocot_letters(slope.clone(), limit).unwrap_or_else(|e| limit_reached(e));
+            let steps = bench::descent_steps(p, q);
+            for &routine in routines {
+                // complex multiplications
+                let (multiplications, measurement) = match routine {
+                    Routine::Multiply => (
+                        bench::eval_products(&letters) * cost::MATRIX_MULTIPLICATION as u64,
+                        bench::measure(settings, || {
+                            std::hint::black_box(gens.eval(&letters));
+                        }),
+                    ),
+                    Routine::SternBrocot => (
+                        steps * cost::MATRIX_MULTIPLICATION as u64,
+                        bench::measure(settings, || {
+                            let m = stern_brocot_word(slope.clone(), a.clone(), b.clone(), limit);
+                            std::hint::black_box(m.unwrap_or_else(|e| limit_reached(e)));
+                        }),
+                    ),
+                    // the last step returns the trace without multiplying
+                    Routine::TraceRecursion => (
+                        steps - 1,
+                        bench::measure(settings, || {
+                            let trace = stern_brocot_trace(&slope, ta.clone(), tb.clone(), tab.clone(),
+                                                           |x, y| x.clone() * y, |x, y| x.clone() - y, limit);
+                            std::hint::black_box(trace.unwrap_or_else(|e| limit_reached(e)));
+                        }),
+                    ),
+                };
+                let statistics = bench::statistics(&measurement.times);
+                let letters_per_second = length as f64 / statistics.mean;
+                let arithmetic_share = multiplications as f64 * multiplication / statistics.mean;
+                out!("bench = {} {} {}", routine.name(), length, precision);
+                out!("slope = {}/{}", p, q);
+                out!("complex_multiplications = {}", multiplications);
+                out!("iterations = {}", measurement.iterations);
+                out!("samples = {}", statistics.samples);
+                out!("mean = {:e}", statistics.mean);
+                out!("mean_interval = {:e} {:e}", statistics.mean_interval.0, statistics.mean_interval.1);
+                out!("std_dev = {:e}", statistics.std_dev);
+                out!("median = {:e}", statistics.median);
+                out!("mad = {:e}", statistics.mad);
+                out!("min = {:e}", statistics.min);
+                out!("max = {:e}", statistics.max);
+                out!("outliers = {} {}", statistics.mild_outliers, statistics.severe_outliers);
+                out!("letters_per_second = {:e}", letters_per_second);
+                out!("arithmetic_share = {}", arithmetic_share);
+                if let Some(file) = &mut csv {
+                    writeln!(file, "{},{},{},{}/{},{},{},{},{:e},{:e},{:e},{:e},{:e},{:e},{:e},{:e},{},{},{:e},{}",
+                             routine.name(), length, precision, p, q, multiplications, measurement.iterations,
+                             statistics.samples, statistics.mean, statistics.mean_interval.0,
+                             statistics.mean_interval.1, statistics.std_dev, statistics.median, statistics.mad,
+                             statistics.min, statistics.max, statistics.mild_outliers, statistics.severe_outliers,
+                             letters_per_second, arithmetic_share)
+                        .or_fail("could not write bench file");
+                }
+            }
+        }
+    }
+    if let Some(file) = &mut csv {
+        file.flush().or_fail("could not write bench file");
+    }
+}
+
+/// The continued-fraction subcommand. The rounding error is estimated by evaluating again
+/// at twice the precision.
+fn continued_fraction(args: &Args, b0: &str, a: &[String], b: &[String], terms: usize) {
+    let Some(precision) = args.precision else {
+        fail(Error::Input("--precision must be provided.".to_string()))
+    };
+    let evaluate = |precision: u32| {
+        let parse = |input: &str, name: &str| parse_complex(input, precision).unwrap_or_else(|e| {
+            fail(Error::Input(format!("Invalid --{}: {}", name, e)))
+        });
+        let a: Vec<C> = a.iter().cycle().take(terms).map(|x| parse(x, "a")).collect();
+        let b: Vec<C> = b.iter().cycle().take(terms).map(|x| parse(x, "b")).collect();
+        continued_fraction::convergents(&parse(b0, "b0"), &a, &b)
+    };
+    let (Some(convergents), Some(precise)) = (evaluate(precision), evaluate(2 * precision)) else {
+        fail(Error::Input("A convergent has a zero denominator.".to_string()))
+    };
+    let rounding_error = Float::with_val(precision, (precise.value - &convergents.value).abs_ref());
+    out!("terms = {}", terms);
+    out!("value = {}", convergents.value);
+    out!("previous_convergent = {}", convergents.previous);
+    out!("truncation_error = {}", convergents.truncation_error);
+    out!("rounding_error = {}", rounding_error);
+}
+
+fn trace_degree(args: &Args, p: u64, q: u64) {
+    let family = match args.param {
+        Parameterization::Maskit => trace_polynomial::Family::Maskit,
+        Parameterization::Riley => trace_polynomial::Family::Riley,
+        Parameterization::Z | Parameterization::Trace | Parameterization::Grandma => {
+            fail(Error::Input("The traces are polynomials only for --param maskit and --param riley.".to_string()))
+        }
+    };
+    if p == 0 || q == 0 {
+        fail(Error::Input("p and q must be positive.".to_string()))
+    }
+    let slope = ExtendedRational::R(Rational::from((p, q)));
+    let polynomial = trace_polynomial(&slope, family, iteration_limit(args)).unwrap_or_else(|e| limit_reached(e));
+    out!("slope = {}", slope);
+    out!("word_length = {}", slope_letters(args, slope.clone()).len());
+    match (polynomial.degree(), polynomial.leading_coefficient()) {
+        (Some(degree), Some(leading)) => {
+            out!("trace_degree = {}", degree);
+            out!("trace_leading_coefficient = {}", leading);
+        }
+        _ => out!("trace_degree = none (the trace is 0)"),
+    }
+}
+
+fn exact(args: &Args, coefficients: &[Integer]) {
+    let field = exact::Field::new(coefficients).unwrap_or_else(|e| {
+        fail(Error::Input(format!("Invalid --exact: {}", e)))
+    });
+    if matches!(args.param, Parameterization::Trace | Parameterization::Grandma) {
+        fail(Error::Input("--exact needs --param z, maskit or riley, whose entries are algebraic in the parameter.".to_string()))
+    }
+    if let Some(cf) = &args.cf {
+        out!("slope = {}", convergent(&cf.expand(args.cf_periods)));
+    }
+    let word = word_letters(args).unwrap_or_else(|| {
+        fail(Error::Input("--exact needs one of --word, -r, --cf.".to_string()))
+    });
+    require_two_generators(&word, "--exact");
+    if args.param == Parameterization::Z {
+        let root_field = exact::RootField::new(&field);
+        let (a, b) = root_field.rho().unwrap_or_else(|e| fail(e));
+        let m = root_field.eval_word(&a, &b, &word).unwrap_or_else(|e| fail(e));
+        let [x, y, z, w] = &m.0;
+        out!("field = Q(i)[z]/({})[s]/(s^2 - z^2 + 1)", field);
+        out!("m11 = {}\nm12 = {}\nm21 = {}\nm22 = {}", x, y, z, w);
+        out!("trace = {}", root_field.add(x, w));
+        return;
+    }
+    let (a, b) = if args.param == Parameterization::Maskit { field.maskit() } else { field.riley() };
+    let m = field.eval_word(&a, &b, &word).unwrap_or_else(|e| fail(e));
+    let [x, y, z, w] = &m.0;
+    out!("field = Q(i)[z]/({})", field);
+    // one entry per line, since the entries contain spaces
+    out!("m11 = {}\nm12 = {}\nm21 = {}\nm22 = {}", x, y, z, w);
+    out!("trace = {}", field.add(x, w));
+}
+
+/// --gaussian; returns the exit status, 1 if the floating point evaluation differs from
+/// the exact one by more than the tolerance.
+fn gaussian(args: &Args, parameter: &[String]) -> i32 {
+    let z = exact::GaussianRational::parse(&parameter[0], &parameter[1]).unwrap_or_else(|e| {
+        fail(Error::Input(format!("Invalid --gaussian: {}", e)))
+    });
+    let precision = args.precision.unwrap_or(64);
+    let zc = z.to_complex(precision);
+    let ((a, b), gens) = match args.param {
+        Parameterization::Z => {
+            exact::rho_parameter(&z).unwrap_or_else(|e| fail(e));
+            (scalar::rho(0, &z), Generators::new(precision, zc))
+        }
+        Parameterization::Maskit => {
+            let (a, b) = rho::maskit(precision, zc);
+            (scalar::maskit(0, &z), Generators::from_matrices(precision, a, b))
+        }
+        Parameterization::Riley => {
+            let (a, b) = rho::riley(precision, zc);
+            (scalar::riley(0, &z), Generators::from_matrices(precision, a, b))
+        }
+        Parameterization::Trace | Parameterization::Grandma => {
+            fail(Error::Input("--gaussian needs --param z, maskit or riley, whose generators have entries in Q(i).".to_string()))
+        }
+    };
+    if let Some(cf) = &args.cf {
+        out!("slope = {}", convergent(&cf.expand(args.cf_periods)));
+    }
+    let word = word_letters(args).unwrap_or_else(|| {
+        fail(Error::Input("--gaussian needs one of --word, -r, --cf.".to_string()))
+    });
+    require_two_generators(&word, "--gaussian");
+    let m = exact::eval_word(&a, &b, &word).unwrap_or_else(|e| fail(e));
+    let [x, y, u, v] = &m.0;
+    out!("z = {}", z);
+    out!("m11 = {}\nm12 = {}\nm21 = {}\nm22 = {}", x, y, u, v);
+    out!("trace = {}", x.add(v));
+    let det = exact::det(&m);
+    out!("det = {}", det);
+    out!("commutator_trace = {}", scalar::trace(&exact::eval_word(&a, &b, "abAB").unwrap_or_else(|e| fail(e))));
+
+    // the error of the floating point entries relative to the largest exact one, since
+    // the small entries of a long product are differences of large ones
+    let expected = exact::to_complex(&m, precision);
+    let computed = gens.eval(&word);
+    let largest = expected.0.iter().map(|e| Float::with_val(precision, e.abs_ref()))
+        .fold(Float::with_val(precision, 1), |x, y| x.max(&y));
+    let deviation = expected.0.iter().zip(&computed.0)
+        .map(|(e, c)| Float::with_val(precision, (c.clone() - e).abs_ref()))
+        .fold(Float::with_val(precision, 0), |x, y| x.max(&y)) / largest;
+    out!("float_deviation = {}", deviation);
+    let one = exact::GaussianRational::new(Rational::from(1), Rational::new());
+    let ok = det == one && deviation <= tolerance(precision);
+    out!("exact_check = {}", if ok { "ok" } else { "failed" });
+    if ok { 0 } else { STATUS_FAILED }
+}
+
+/// The farey subcommand; returns the exit status, 1 if --check-matrices found a trace
+/// that differs from its matrix by more than the tolerance.
+fn farey(gens: &Generators, max_height: u64, check_matrices: bool, fixed_points: Option<&Path>,
+         context: &RunContext) -> i32 {
+    let precision = gens.precision;
+    let ab = gens.a.clone().mul(gens.b.clone());
+    let (ta, tb, tab) = (gens.a.trace(), gens.b.trace(), ab.trace());
+    let mut nodes = 0u64;
+    if !check_matrices && fixed_points.is_none() {
+        for_each_farey(max_height, ta, tb, tab, |u, v, w| u.clone() * v - w, |q, trace| {
+            nodes += 1;
+            out!("{} {}", q, trace);
+        });
+        out!("nodes = {}", nodes);
+        return 0;
+    }
+    let mut csv = fixed_points.map(|path| {
+        let mut file = BufWriter::new(File::create(path).or_fail("could not create fixed point file"));
+        context.write_comments(&mut file).or_fail("could not write fixed point file");
+        writeln!(file, "x,y,slope").or_fail("could not write fixed point file");
+        file
+    });
+    let mut points = 0u64;
+    // each word carries its trace from the recursion, its matrix and its attracting fixed
+    // point, which is found with the fixed points of the two parents as seeds
+    let node = |m: M<C>, trace: C, seeds: &[&Point]| {
+        let point = fixed_points.map(|_| mobius::attracting_fixed_point(&m, precision, seeds));
+        (trace, m, point)
+    };
+    let (a, b) = (node(gens.a.clone(), ta, &[]), node(gens.b.clone(), tb, &[]));
+    let ab = node(ab, tab, &[]);
+    let mut max_discrepancy = Float::with_val(precision, 0);
+    for_each_farey(
+        max_height, a, b, ab,
+        |(tu, u, pu), (tv, v, pv), (tw, _, _)| {
+            let seeds: Vec<&Point> = pu.iter().chain(pv.iter()).collect();
+            node(u.clone().mul(v.clone()), tu.clone() * tv - tw, &seeds)
+        },
+        |q, (trace, m, point)| {
+            nodes += 1;
+            // relative to the size of the trace, like the tolerance of the backends test
+            let size = Float::with_val(precision, trace.abs_ref()).max(&Float::with_val(precision, 1));
+            let discrepancy = Float::with_val(precision, (m.trace() - trace).abs_ref()) / size;
+            if discrepancy > max_discrepancy {
+                max_discrepancy = discrepancy;
+            }
+            if let (Some(file), Some(Point::Finite(z))) = (&mut csv, point) {
+                points += 1;
+                writeln!(file, "{},{},{}", z.real(), z.imag(), q).or_fail("could not write fixed point file");
+            }
+            out!("{} {}", q, trace);
+        },
+    );
+    out!("nodes = {}", nodes);
+    if let Some(file) = &mut csv {
+        file.flush().or_fail("could not write fixed point file");
+        out!("fixed_points = {}", points);
+    }
+    if !check_matrices {
+        return 0;
+    }
+    out!("max_discrepancy = {}", max_discrepancy);
+    if max_discrepancy > tolerance(precision) {
+        out!("check = failed");
+        STATUS_FAILED
+    } else {
+        out!("check = ok");
+        0
+    }
+}
+
+fn iteration_limit(args: &Args) -> IterationLimit {
+    IterationLimit((args.iteration_limit > 0).then_some(args.iteration_limit))
+}
+
+/// Exits with the error of an algorithm that ran into --iteration-limit.
+fn limit_reached(e: LimitReached) -> ! {
+    fail(e.into())
+}
+
+/// The Stern-Brocot word of a slope, within --iteration-limit.
+fn slope_letters(args: &Args, q: ExtendedRational) -> String {
+    stern_brocot_letters(q, iteration_limit(args)).unwrap_or_else(|e| limit_reached(e))
+}
+
+/// The word of a continued fraction, within --iteration-limit.
+fn cf_letters(args: &Args, terms: &[u64]) -> String {
+    continued_fraction_letters(terms, iteration_limit(args)).unwrap_or_else(|e| limit_reached(e))
+}
+
+/// The word of --word (reduced as with --reduce), --cf or -r, without evaluating it.
+fn word_letters(args: &Args) -> Option<String> {
+    if let Some(cf) = &args.cf {
+        Some(cf_letters(args, &cf.expand(args.cf_periods)))
+    } else if let Some(w) = &args.word {
+        Some(match args.reduce {
+            None => w.clone(),
+            Some(Reduction::Free) => word::free_reduce(w),
+            Some(Reduction::Cyclic) => word::cyclic_reduce(w),
+        })
+    } else {
+        args.r.as_ref().map(|r| {
+            let slope = if r[1] == 0 { ExtendedRational::Infinity } else { ExtendedRational::R(Rational::from((r[0], r[1]))) };
+            slope_letters(args, slope)
+        })
+    }
+}
+
+/// The smallest precision of the form p 2^k, for p from --precision (or 64), at which the
+/// word has |det - 1| and eigenvector residual below --residual-tolerance. The random
+/// choices are drawn from `seed` the same way as `run` draws them, so they do not change
+/// between attempts.
+fn auto_precision(args: &Args, seed: u64) -> u32 {
+    let mut precision = args.precision.unwrap_or(64);
+    loop {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let z = if args.random_z {
+            random_z(rng, precision)
+        } else if let Some(z) = &args.z {
+            parse_complex_args(z, precision).unwrap_or_else(|e| {
+                fail(Error::Input(format!("Invalid -z: {}", e)))
+            })
+        } else {
+            Complex::with_val(precision, 0)
+        };
+        let gens = generators(args, precision, &z);
+        let letters = match args.random_word {
+            Some(n) => random_word(rng, n, args.reduce),
+            None => word_letters(args).unwrap_or_else(|| {
+                fail(Error::Input("At least one of --word, --random-word, -r, --cf must be provided.".to_string()))
+            }),
+        };
+        let m = gens.eval(&letters);
+        let (det_error, residual) = (m.det_error(precision), m.eigenvector_residual(precision));
+        if det_error <= args.residual_tolerance && residual <= args.residual_tolerance {
+            out!("auto_precision = {}", precision);
+            out!("det_error = {}", det_error);
+            out!("eigenvector_residual = {}", residual);
+            return precision;
+        }
+        if precision >= args.max_precision {
+            fail(Error::Degenerate(format!("Still not within --residual-tolerance at precision {}: det_error = {}, eigenvector_residual = {}.",
+                      precision, det_error, residual)))
+        }
+        precision = precision.saturating_mul(2).min(args.max_precision);
+    }
+}
+
+/// The word evaluated in ball arithmetic from the parameter of --param, or `None` if
+/// the parameter is too close to a branch point of the generators.
+#[cfg(feature = "rigorous")]
+fn rigorous_word(args: &Args, precision: u32, z: &C, letters: &str) -> Option<M<Ball>> {
+    require_two_generators(letters, "--rigorous");
+    // z was correctly rounded when it was parsed
+    let z = Ball::rounded(z.clone());
+    let (a, b) = match args.param {
+        Parameterization::Z => ball::rho(precision, &z)?,
+        Parameterization::Maskit => ball::maskit(precision, &z),
+        Parameterization::Riley => ball::riley(precision, &z),
+        Parameterization::Trace | Parameterization::Grandma => {
+            fail(Error::Input("--rigorous is not implemented for --param trace and grandma.".to_string()))
+        }
+    };
+    Some(ball::eval_word(precision, &a, &b, letters).unwrap_or_else(|e| fail(e)))
+}
+
+#[cfg(feature = "rigorous")]
+fn print_rigorous(m: Option<&M<Ball>>) {
+    let Some(m) = m else {
+        out!("rigorous = failed (the parameter is too close to a branch point)");
+        return;
+    };
+    let [a, b, c, d] = &m.0;
+    let trace = a.add(d);
+    out!("rigorous_m11 = {}", a);
+    out!("rigorous_m12 = {}", b);
+    out!("rigorous_m21 = {}", c);
+    out!("rigorous_m22 = {}", d);
+    out!("rigorous_trace = {}", trace);
+    match ball::eigenvalue(&trace) {
+        Some(lambda) => out!("rigorous_eigenvalue = {}", lambda),
+        None => out!("rigorous_eigenvalue = unknown (the eigenvalues are too close)"),
+    }
+    let proved = |p: bool| if p { "proved" } else { "unknown" };
+    out!("rigorous_trace_not_pm2 = {}", proved(ball::avoids_pm2(&trace)));
+    out!("rigorous_loxodromic = {}", proved(ball::avoids_segment(&trace)));
+}
+
+fn pleating(gens: &Generators, max_height: u64) {
+    let candidates = pleating_candidates(gens, max_height);
+    for c in &candidates {
+        out!("pleating_candidate = {} {} {} {}", c.height, c.slope, c.value, c.trace);
+    }
+    let Some(best) = candidates.last() else {
+        out!("pleating_slope = none");
+        return;
+    };
+    out!("pleating_slope = {}", best.slope);
+    out!("pleating_slope_decimal = {}", best.slope.numer().to_f64() / best.slope.denom().to_f64());
+    out!("pleating_trace = {}", best.trace);
+    out!("pleating_realness = {}", best.value);
+    // a real trace up to rounding puts the parameter on the ray of that slope
+    let kind = if best.value <= tolerance(gens.precision) { "rational" } else { "approximate" };
+    out!("pleating = {}", kind);
+    // how many of the largest heights agree on the slope
+    let stable = candidates.iter().rev().take_while(|c| c.slope == best.slope).count();
+    out!("pleating_stable_heights = {}/{}", stable, candidates.len());
+}
+
+/// The scan subcommand; returns the exit status.
+fn scan(args: &Args, precision: u32, grid: Grid, max_height: u64, threshold: f64, out: Option<&Path>,
+        #[cfg(feature = "render")] png: Option<&Path>) -> i32 {
+    if grid.columns == 0 || grid.rows == 0 || !(grid.x0 < grid.x1 && grid.y0 < grid.y1) || max_height == 0 {
+        fail(Error::Input("The scan needs a positive resolution and --max-height, and a window with x0 < x1 and y0 < y1."
+                          .to_string()))
+    }
+    let mut csv = out.map(|path| {
+        let mut file = BufWriter::new(File::create(path).or_fail("could not create scan file"));
+        let context = RunContext {
+            parameter: format!("grid of {}x{} over {} {} {} {}", grid.columns, grid.rows, grid.x0, grid.y0, grid.x1, grid.y1),
+            ..run_context(args, precision, None)
+        };
+        scan::write_csv_header(&mut file, &context).or_fail("could not write scan file");
+        file
+    });
+    let threshold = Float::with_val(precision, threshold);
+    // the generators that are parabolic by construction
+    let zero = ExtendedRational::R(Rational::ZERO.clone());
+    let skip = match args.param {
+        _ if args.matrix_a.is_some() => vec![],
+        Parameterization::Maskit => vec![ExtendedRational::Infinity],
+        Parameterization::Riley => vec![zero, ExtendedRational::Infinity],
+        Parameterization::Z | Parameterization::Trace | Parameterization::Grandma => vec![],
+    };
+    let mut classes = Vec::with_capacity(grid.columns as usize * grid.rows as usize);
+    for j in 0..grid.rows {
+        for i in 0..grid.columns {
+            let (x, y) = grid.point(i, j);
+            let gens = generators(args, precision, &Complex::with_val(precision, (x, y)));
+            let verdict = scan::bowditch(&gens, max_height, &threshold, &skip);
+            if let Some(file) = &mut csv {
+                scan::write_csv_row(file, x, y, &verdict).or_fail("could not write scan file");
+            }
+            classes.push(verdict.class);
+        }
+    }
+    if let Some(file) = &mut csv {
+        file.flush().or_fail("could not write scan file");
+    }
+    out!("samples = {}", classes.len());
+    for class in [Class::Bounded, Class::Small, Class::Fails] {
+        out!("{} = {}", class, classes.iter().filter(|&&c| c == class).count());
+    }
+    #[cfg(feature = "render")]
+    if let Some(path) = png {
+        let data: Vec<u8> = classes.iter()
+            .flat_map(|class| match class {
+                Class::Bounded => [255, 255, 255],
+                Class::Small => [240, 190, 60],
+                Class::Fails => [130, 20, 30],
+            })
+            .collect();
+        let file = BufWriter::new(File::create(path).or_fail("could not create PNG file"));
+        density::write_rgb_png(file, grid.columns, grid.rows, &data).or_fail("could not write PNG file");
+    }
+    0
+}
+
+/// The slopes p/q <= 1 have p <= q, so they are the nodes of `for_each_farey` up to height
+/// max_q left of 1/1, and each word is carried down the tree next to its trace.
+fn enumerate_primitives(gens: &Generators, max_q: u64) {
+    let ab = gens.a.clone().mul(gens.b.clone());
+    let node = |m: &M<C>, word: &str| (m.trace(), word.to_string());
+    let mut slopes = 0u64;
+    for_each_farey(
+        max_q, node(&gens.a, "a"), node(&gens.b, "b"), node(&ab, "ab"),
+        |(tu, u), (tv, v), (tw, _)| (tu.clone() * tv - tw, u.clone() + v),
+        |q, (trace, word)| {
+            if q.numer() > q.denom() {
+                return;
+            }
+            slopes += 1;
+            out!("{} {} {} {}", q, word, trace, trace_length(trace).real());
+        },
+    );
+    out!("slopes = {}", slopes);
+}
+
+fn mcshane(gens: &Generators, max_height: u64) {
+    if max_height == 0 {
+        fail(Error::Input("--max-height must be positive.".to_string()))
+    }
+    let precision = gens.precision;
+    let commutator = trace_map::commutator_trace(&gens.trace_coordinates());
+    out!("commutator_trace = {}", commutator);
+    let residual = Float::with_val(precision, (commutator + 2u32).abs_ref());
+    if residual > tolerance(precision) {
+        out!("warning = tr[a, b] is not -2, so the identity does not apply");
+    }
+    let half = Float::with_val(precision, 0.5);
+    let sums = mcshane::partial_sums(gens, max_height);
+    for s in &sums {
+        let error = Float::with_val(precision, (s.sum.clone() - &half).abs_ref());
+        out!("partial_sum = {} {} {} {}", s.height, s.curves, s.sum, error);
+    }
+    let last = sums.last().expect("at least one height");
+    out!("curves = {}", last.curves);
+    out!("mcshane_sum = {}", last.sum);
+    out!("mcshane_error = {}", Float::with_val(precision, (last.sum.clone() - &half).abs_ref()));
+}
+
+fn trace_map(args: &Args, gens: &Generators, steps: usize, moves: &str) {
+    let moves = Move::parse_all(moves).expect("checked by clap");
+    let start = gens.trace_coordinates();
+    let invariant = trace_map::commutator_trace(&start);
+    out!("commutator_trace = {}", invariant);
+    let mut end = None;
+    // every move puts the new trace last
+    let mut newest = Vec::new();
+    trace_map::orbit(start, &moves, steps, |n, [x, y, z]| {
+        out!("{} {} {} {}", n, x, y, z);
+        if args.plot.is_some() {
+            newest.push(Float::with_val(gens.precision, z.abs_ref()).ln().to_f64());
+        }
+        if n == steps {
+            end = Some(trace_map::commutator_trace(&[x.clone(), y.clone(), z.clone()]));
+        }
+    });
+    let drift = end.expect("the orbit ends at the last step") - invariant;
+    out!("commutator_trace_drift = {}", Float::with_val(gens.precision, drift.abs_ref()));
+    print_plot(args, "plot_log_abs_trace", &newest, steps);
+}
+
+fn print_end_invariant(gens: &Generators, max_height: u64, near: f64) {
+    let cusps = cusp_candidates(gens, max_height);
+    let growth = growth_candidates(gens, max_height);
+    for c in &cusps {
+        out!("cusp_candidate = {} {} {} {}", c.height, c.slope, c.value, c.trace);
+    }
+    for c in &growth {
+        out!("growth_candidate = {} {} {} {}", c.height, c.slope, c.value, c.trace);
+    }
+    let confidence = |c: &Confidence| match c {
+        Confidence::High => "high",
+        Confidence::Medium => "medium",
+        Confidence::Low => "low",
+    };
+    match end_invariant(&cusps, &growth, &tolerance(gens.precision), near) {
+        Some(EndInvariant::Rational { slope, confidence: c }) => {
+            out!("end_invariant = {}", slope);
+            out!("end_invariant_kind = rational");
+            out!("confidence = {}", confidence(&c));
+        }
+        Some(EndInvariant::Irrational { estimate, confidence: c }) => {
+            out!("end_invariant = {}", estimate);
+            out!("end_invariant_decimal = {}", estimate.numer().to_f64() / estimate.denom().to_f64());
+            out!("end_invariant_kind = irrational");
+            out!("confidence = {}", confidence(&c));
+        }
+        None => out!("end_invariant = none"),
+    }
+}
+
+/// The generators of --param as functions of the parameter, for differentiating.
+fn family(args: &Args) -> dual::Family {
+    match args.param {
+        _ if args.matrix_a.is_some() => {
+            fail(Error::Input("Derivatives need a parameterization, not --matrix-a/--matrix-b.".to_string()))
+        }
+        Parameterization::Z => scalar::rho,
+        Parameterization::Maskit => scalar::maskit,
+        Parameterization::Riley => scalar::riley,
+        Parameterization::Trace | Parameterization::Grandma => {
+            fail(Error::Input("Derivatives are not implemented for --param trace and grandma.".to_string()))
+        }
+    }
+}
+
+/// A starting point for Newton's method when -z is not given, and where it came from. For
+/// a Stern-Brocot word of --param maskit it is the cusp of the word, followed down the
+/// Stern-Brocot tree by `cusp::continue_cusp`; otherwise it is a base point with real
+/// traces, z = 2, rho = 8 (Fuchsian), or mu = 4i on the symmetry axis of the Maskit slice.
+/// Either way the trace is then moved to the target by `dual::continue_trace`.
+fn solve_start(args: &Args, precision: u32, family: dual::Family, word: &str, target: &C,
+               max_iterations: usize) -> (C, &'static str) {
+    let (base, from) = match args.param {
+        Parameterization::Maskit => match cusp::continue_cusp(word, precision, max_iterations) {
+            Some(mu) => (mu, "cusp"),
+            None => (Complex::with_val(precision, (0, 4)), "base"),
+        },
+        Parameterization::Riley => (Complex::with_val(precision, 8), "base"),
+        _ => (Complex::with_val(precision, 2), "base"),
+    };
+    (dual::continue_trace(family, precision, word, target, base, 64).unwrap_or_else(|e| fail(e)), from)
+}
+
+/// Newton's method for the solve subcommand; returns the exit status, 1 if it did
+/// not converge.
+fn solve(args: &Args, precision: u32, z: Option<C>, word: &str, target: Option<&str>, order: Option<u32>,
+         max_iterations: usize) -> i32 {
+    require_two_generators(word, "solve");
+    let family = family(args);
+    let target: C = match (target, order) {
+        (Some(t), _) => parse_complex(t, precision).unwrap_or_else(|e| {
+            fail(Error::Input(format!("Invalid --target: {}", e)))
+        }),
+        (None, Some(n)) if n > 0 => {
+            let angle = Float::with_val(precision, Constant::Pi) / n;
+            Complex::with_val(precision, angle.cos() * 2u32)
+        }
+        _ => {
+            fail(Error::Input("--order must be positive.".to_string()))
+        }
+    };
+    let (start, from) = match z {
+        Some(z) => (z, "given"),
+        None => solve_start(args, precision, family, word, &target, max_iterations),
+    };
+    out!("start = {}", start);
+    out!("start_from = {}", from);
+    let solution = dual::solve_trace(family, precision, word, &target, start, max_iterations).unwrap_or_else(|e| fail(e));
+    out!("target = {}", target);
+    out!("z = {}", solution.z);
+    out!("trace = {}", solution.trace);
+    out!("residual = {}", solution.residual);
+    out!("iterations = {}", solution.iterations);
+    out!("converged = {}", solution.converged);
+    if solution.converged { 0 } else { STATUS_FAILED }
+}
+
+/// The trace-ray subcommand; returns the exit status, 1 if Newton's method failed on the way.
+fn trace_ray(args: &Args, precision: u32, z: Option<C>) -> i32 {
+    let Some(Command::TraceRay { word, from, steps, max_iterations, out }) = &args.command else {
+        unreachable!("dispatched on trace-ray")
+    };
+    let (from, steps, max_iterations) = (*from, *steps, *max_iterations);
+    require_two_generators(word, "trace-ray");
+    if steps == 0 || !(from.abs() > 2.0 && from.is_finite()) {
+        fail(Error::Input("--steps must be positive and --from beyond 2 in absolute value.".to_string()))
+    }
+    let family = family(args);
+    let (start, start_from) = match z {
+        Some(z) => (z, "given"),
+        None => {
+            // the ray leaves the cusp on the side of its trace, 2 or -2
+            let cusp = matches!(args.param, Parameterization::Maskit)
+                .then(|| cusp::continue_cusp(word, precision, max_iterations))
+                .flatten();
+            let negative = cusp.is_some_and(|mu| {
+                let (trace, _) = dual::trace_derivative(family, precision, &mu, word).unwrap_or_else(|e| fail(e));
+                trace.real().is_sign_negative()
+            });
+            let target = Complex::with_val(precision, if negative { -from.abs() } else { from.abs() });
+            solve_start(args, precision, family, word, &target, max_iterations)
+        }
+    };
+    out!("start = {}", start);
+    out!("start_from = {}", start_from);
+    let ray = match dual::trace_ray(family, precision, word, start, steps, max_iterations) {
+        Ok(ray) => ray,
+        Err(e @ Error::Degenerate(_)) => {
+            out!("ray = failed: {}", e);
+            return e.exit_code();
+        }
+        Err(e) => fail(e),
+    };
+    for point in &ray {
+        out!("{} {}", point.trace, point.z);
+    }
+    let traces: Vec<f64> = ray.iter().map(|point| point.trace.to_f64()).collect();
+    print_plot(args, "plot_trace", &traces, traces.len() - 1);
+    let cusp = ray.last().expect("at least the start");
+    out!("points = {}", ray.len());
+    out!("cusp = {}", cusp.z);
+    if let Some(path) = out {
+        let context = run_context(args, precision, None);
+        let mut file = BufWriter::new(File::create(path).or_fail("could not create ray file"));
+        context.write_comments(&mut file).or_fail("could not write ray file");
+        writeln!(file, "x,y,trace").or_fail("could not write ray file");
+        for point in &ray {
+            writeln!(file, "{},{},{}", point.z.real(), point.z.imag(), point.trace).or_fail("could not write ray file");
+        }
+        file.flush().or_fail("could not write ray file");
+    }
+    0
+}
+
+/// Runs once for each point of the sweep, with the swept options replaced, and
+/// returns the largest exit status.
+fn run_sweep(args: &Args, spec: &str) -> i32 {
+    let axes = batch::parse_spec(spec).unwrap_or_else(|e| {
+        fail(Error::Input(format!("Invalid sweep: {}", e)))
+    });
+    let mut status = 0;
+    // the words of swept slopes share their prefixes as long as the generators stay the same
+    let mut cache = None;
+    for point in batch::combinations(&axes) {
+        let mut point_args = args.clone();
+        for &(key, value) in &point {
+            if let Err(e) = set_sweep_parameter(&mut point_args, key, value) {
+                fail(Error::Input(format!("Invalid sweep value {}={}: {}", key, value, e)))
+            }
+        }
+        let description: Vec<String> = point.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        out!("sweep_point = {}", description.join(" "));
+        status = status.max(run(point_args, &mut cache));
+    }
+    status
+}
+
+fn set_sweep_parameter(args: &mut Args, key: &str, value: &str) -> Result<(), String> {
+    // a swept part of -z or --tb replaces that part of the two values given
+    let set_part = |values: &mut Option<Vec<String>>, index: usize| match values {
+        Some(v) if v.len() == 2 => {
+            v[index] = value.to_string();
+            Ok(())
+        }
+        Some(_) => Err("give the base value as two numbers x y to sweep one part".to_string()),
+        None => {
+            let mut v = vec!["0".to_string(), "0".to_string()];
+            v[index] = value.to_string();
+            *values = Some(v);
+            Ok(())
+        }
+    };
+    match key {
+        "z.re" => set_part(&mut args.z, 0),
+        "z.im" => set_part(&mut args.z, 1),
+        "tb.re" => set_part(&mut args.tb, 0),
+        "tb.im" => set_part(&mut args.tb, 1),
+        "tab.re" => set_part(&mut args.tab, 0),
+        "tab.im" => set_part(&mut args.tab, 1),
+        "precision" => {
+            let bits: u32 = value.parse().map_err(|_| "expected a number of bits".to_string())?;
+            if bits == 0 {
+                return Err("expected a positive number of bits".to_string());
+            }
+            args.precision = Some(bits);
+            Ok(())
+        }
+        "seed" => {
+            args.seed = Some(value.parse().map_err(|_| "expected an integer".to_string())?);
+            Ok(())
+        }
+        "word" => {
+            args.word = Some(parse_word(value)?);
+            Ok(())
+        }
+        "r" => {
+            let (p, q) = value.split_once('/').ok_or("expected a slope p/q")?;
+            let parse = |x: &str| x.trim().parse::<u64>().map_err(|_| format!("invalid integer {}", x));
+            args.r = Some(vec![parse(p)?, parse(q)?]);
+            Ok(())
+        }
+        _ => Err("unknown key; expected z.re, z.im, tb.re, tb.im, tab.re, tab.im, precision, seed, word or r".to_string()),
+    }
+}
+
+/// Everything but the subcommands that need no representation; returns the exit status.
+/// The words of slopes are evaluated through `cache`, which is replaced when the
+/// generators change.
+fn run(args: Args, cache: &mut Option<SternBrocotCache<M<C>>>) -> i32 {
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let precision = match args.precision {
+        _ if args.auto_precision => auto_precision(&args, seed),
+        Some(precision) => precision,
+        None if args.restore.is_some() => read_snapshot(args.restore.as_deref().expect("checked")).generators.precision,
+        None => {
+            fail(Error::Input("--precision must be provided.".to_string()))
+        }
+    };
+    let rng = &mut StdRng::seed_from_u64(seed);
+    let random_orbit = matches!(args.command, Some(Command::Orbit { random: Some(_), .. }));
+    if args.random_z || args.random_word.is_some() || args.samples.is_some() || !args.clt_fit.is_empty() || random_orbit {
+        out!("seed = {}", seed);
+    }
+    if args.checkpoint.is_some() || args.resume.is_some() {
+        if !matches!(args.command, None | Some(Command::LimitSet { .. })) {
+            fail(Error::Input("--checkpoint and --resume work with a single --word or --random-word, and with limit-set.".to_string()))
+        }
+        if !(args.checkpoint_interval > 0.0 && args.checkpoint_interval.is_finite()) {
+            fail(Error::Input("--checkpoint-interval must be a positive number of seconds.".to_string()))
+        }
+    }
+
+    #[cfg(feature = "render")]
+    if let Some(Command::Scan { window, resolution, max_height, threshold, out, png }) = &args.command {
+        let grid = Grid { x0: window[0], y0: window[1], x1: window[2], y1: window[3],
+                          columns: resolution[0], rows: resolution[1] };
+        return scan(&args, precision, grid, *max_height, *threshold, out.as_deref(), png.as_deref());
+    }
+    #[cfg(not(feature = "render"))]
+    if let Some(Command::Scan { window, resolution, max_height, threshold, out }) = &args.command {
+        let grid = Grid { x0: window[0], y0: window[1], x1: window[2], y1: window[3],
+                          columns: resolution[0], rows: resolution[1] };
+        return scan(&args, precision, grid, *max_height, *threshold, out.as_deref());
+    }
+
+    let given: Option<C> =
+        if args.random_z {
+            Some(random_z(rng, precision))
+        } else {
+            args.z.as_ref().map(|z| parse_complex_args(z, precision).unwrap_or_else(|e| {
+                fail(Error::Input(format!("Invalid -z: {}", e)))
+            }))
+        };
+    if let Some(Command::Solve { word, target, order, max_iterations }) = &args.command {
+        return solve(&args, precision, given, word, target.as_deref(), *order, *max_iterations);
+    }
+    if let Some(Command::TraceRay { .. }) = &args.command {
+        return trace_ray(&args, precision, given);
+    }
+    let z: C = given.unwrap_or_else(|| {
+        if args.matrix_a.is_none() && args.restore.is_none() {
+            fail(Error::Input("At least one of z, random-z, matrix-a, restore must be provided.".to_string()))
+        }
+        // unused: the generators are given directly
+        Complex::with_val(precision, 0)
+    });
+    let gens = generators(&args, precision, &z);
+    let context = run_context(&args, precision, Some(&z));
+    write_snapshot(&args, &gens, None, &context);
+    if args.time_limit.is_some_and(|t| !(t >= 0.0 && t.is_finite())) {
+        fail(Error::Input("--time-limit must be a nonnegative number of seconds.".to_string()))
+    }
+    let budget = &mut Budget::new(args.time_limit.map(Duration::from_secs_f64), args.node_budget);
+
+    if args.check_relator {
+        let error = gens.relator_error();
+        out!("commutator_trace = {}", gens.commutator_trace());
+        out!("relator_error = {}", error);
+        if error > tolerance(precision) {
+            out!("relator = failed");
+        } else {
+            out!("relator = ok");
+        }
+    }
+
+    if args.indra {
+        print_indra(&gens);
+    }
+    if args.real_form {
+        print_real_form(&gens);
+    }
+    if args.trace_coords {
+        let [x, y, xy] = gens.trace_coordinates();
+        let residual = gens.markov_residual();
+        out!("trace_x = {}", x);
+        out!("trace_y = {}", y);
+        out!("trace_z = {}", xy);
+        out!("markov_residual = {}", residual);
+        if Float::with_val(precision, residual.abs_ref()) > tolerance(precision) {
+            out!("markov = failed");
+        } else {
+            out!("markov = ok");
+        }
+    }
+
+    if args.jorgensen {
+        let report = jorgensen(&gens, args.jorgensen_length, args.shard, budget);
+        out!("jorgensen_a_b = {}", jorgensen_sum(&gens.a, &gens.b, precision));
+        out!("jorgensen_b_a = {}", jorgensen_sum(&gens.b, &gens.a, precision));
+        print_budget(budget);
+        print_jorgensen(&report, budget.exhausted().is_some());
+        let results = Results::new(&gens, args.jorgensen_length, args.shard, budget, Body::Jorgensen(report));
+        write_results(args.results.as_deref(), &results, &context);
+        write_snapshot(&args, &gens, Some(results), &context);
+    }
+
+    if let Some(path) = &args.cayley_ball {
+        let label = match args.cayley_label {
+            CayleyLabel::Trace => cayley::Label::Trace,
+            CayleyLabel::FixedPoints => cayley::Label::FixedPoints,
+        };
+        let vertices = cayley::ball(&gens, args.cayley_radius, label);
+        let mut file = BufWriter::new(File::create(path).or_fail("could not create Cayley graph file"));
+        let result = if path.extension().is_some_and(|e| e == "graphml") {
+            cayley::write_graphml(&vertices, &mut file, &context)
+        } else {
+            cayley::write_dot(&vertices, &mut file, &context)
+        };
+        result.or_fail("could not write Cayley graph file");
+        out!("cayley_vertices = {}", vertices.len());
+        return 0;
+    }
+
+    #[cfg(feature = "rigorous")]
+    if args.certify && (args.param != Parameterization::Z || args.matrix_a.is_some()) {
+        fail(Error::Input("--certify is only implemented for --param z.".to_string()))
+    }
+    if args.ping_pong {
+        #[cfg_attr(not(feature = "rigorous"), allow(unused_variables))]
+        let disks = ping_pong(&gens, &args.disk);
+        #[cfg(feature = "rigorous")]
+        if args.certify {
+            certify(precision, &z, disks, args.certificate.as_deref(), &context);
+        }
+        return 0;
+    }
+
+    #[cfg(feature = "render")]
+    if let Some(Command::LimitSet { depth, out, png }) = &args.command {
+        limit_set(&args, &gens, *depth, out.as_deref(), png, budget, &context);
+        return 0;
+    }
+    #[cfg(not(feature = "render"))]
+    if let Some(Command::LimitSet { depth, out }) = &args.command {
+        limit_set(&args, &gens, *depth, out.as_deref(), budget, &context);
+        return 0;
+    }
+
+    if let Some(Command::Orbit { basepoint, depth, random, length, out, #[cfg(feature = "render")] png }) = &args.command {
+        let basepoint = parse_basepoint(basepoint, precision).unwrap_or_else(|e| {
+            fail(Error::Input(format!("Invalid --basepoint: {}", e)))
+        });
+        let words = match (depth, random, length) {
+            (Some(depth), _, _) => OrbitWords::Depth(*depth, args.shard),
+            (None, Some(samples), Some(length)) => OrbitWords::Random { samples: *samples, length: *length, rng },
+            _ => unreachable!("clap requires --depth or --random with --length"),
+        };
+        orbit(&gens, &basepoint, words, out.as_deref(), #[cfg(feature = "render")] png, budget, &context);
+        return 0;
+    }
+
+    if let Some(Command::Farey { max_height, check_matrices, fixed_points }) = &args.command {
+        return farey(&gens, *max_height, *check_matrices, fixed_points.as_deref(), &context);
+    }
+
+    if let Some(Command::EnumeratePrimitives { max_q }) = args.command {
+        enumerate_primitives(&gens, max_q);
+        return 0;
+    }
+
+    if let Some(Command::McShane { max_height }) = args.command {
+        mcshane(&gens, max_height);
+        return 0;
+    }
+
+    if let Some(Command::TraceMap { steps, moves }) = &args.command {
+        trace_map(&args, &gens, *steps, moves);
+        return 0;
+    }
+
+    if let Some(Command::Pleating { max_height }) = args.command {
+        pleating(&gens, max_height);
+        return 0;
+    }
+
+    if let Some(Command::EndInvariant { max_height, near }) = args.command {
+        print_end_invariant(&gens, max_height, near);
+        return 0;
+    }
+
+    if let Some(Command::Spectrum { max_length }) = args.command {
+        let entries = length_spectrum(&gens, max_length, args.shard, budget);
+        for entry in &entries {
+            out!("{} {} {}", entry.translation_length, entry.word, entry.trace);
+        }
+        print_budget(budget);
+        let results = Results::new(&gens, max_length, args.shard, budget, Body::Spectrum(entries));
+        write_results(args.results.as_deref(), &results, &context);
+        write_snapshot(&args, &gens, Some(results), &context);
+        return 0;
+    }
+
+    if let Some(Command::FindWord { target, max_length, count }) = &args.command {
+        let target = parse_complex(target, precision).unwrap_or_else(|e| {
+            fail(Error::Input(format!("Invalid --target: {}", e)))
+        });
+        let search = find_word(&gens, &target, *max_length, *count, budget);
+        for candidate in &search.candidates {
+            out!("{} {} {}", candidate.distance, candidate.word, candidate.trace);
+        }
+        out!("pruned = {}", search.pruned);
+        print_budget(budget);
+        return 0;
+    }
+
+    if let Some(max_length) = args.verify_up_to {
+        let mut checks: Vec<Check> = [
+            (args.assert_no_elliptic, Check::NoElliptic),
+            (args.assert_bowditch, Check::Bowditch),
+            (args.assert_det, Check::Determinant),
+        ].iter().filter(|(on, _)| *on).map(|&(_, c)| c).collect();
+        if checks.is_empty() {
+            checks = vec![Check::NoElliptic, Check::Bowditch, Check::Determinant];
+        }
+        let checked = sweep(&gens, max_length, &checks, args.shard, budget);
+        print_budget(budget);
+        let status = print_verify(&checked, budget.exhausted().is_some());
+        let results = Results::new(&gens, max_length, args.shard, budget, Body::Verify(checked));
+        write_results(args.results.as_deref(), &results, &context);
+        write_snapshot(&args, &gens, Some(results), &context);
+        return status;
+    }
+
+    if let (Some(epsilon), Some(n)) = (args.small_trace, args.random_word) {
+        if args.particles == 0 || args.replicates < 2 {
+            fail(Error::Input("--small-trace needs at least one particle and two replicates.".to_string()))
+        }
+        let sampler = Sampler {
+            particles: args.particles,
+            replicates: args.replicates,
+            tilt: args.tilt,
+            reduced: args.reduce.is_some(),
+        };
+        let epsilon = Float::with_val(precision, epsilon);
+        let estimate = small_trace_probability(&gens, rng, n, &epsilon, &sampler);
+        out!("small_trace_probability = {}", estimate.probability);
+        out!("std_error = {}", estimate.std_error);
+        out!("hits = {}", estimate.hits);
+        return 0;
+    }
+
+    if let Some(samples) = args.trace_histogram {
+        if !args.clt_fit.is_empty() {
+            clt_fit(&gens, rng, &args.clt_fit, args.reduce, samples);
+        } else if let Some(n) = args.random_word {
+            trace_histogram(&args, &gens, rng, n, args.reduce, samples, args.bins);
+        }
+        return 0;
+    }
+
+    let slope = args.r.clone().map(|r| {
+        let p = r[0];
+        let q = r[1];
+        if q == 0 {
+            ExtendedRational::Infinity
+        } else {
+            ExtendedRational::R(Rational::from((p, q)))
+        }
+    });
+
+    if let Some(samples) = args.samples {
+        let fixed_word =
+            if let Some(w) = &args.word {
+                Some(w.clone())
+            } else if let Some(cf) = &args.cf {
+                Some(cf_letters(&args, &cf.expand(args.cf_periods)))
+            } else {
+                slope.clone().map(|q| slope_letters(&args, q))
+            };
+        if args.random_word.is_none() && fixed_word.is_none() {
+            fail(Error::Input("At least one of --word, --random-word, -r, --cf must be provided.".to_string()))
+        }
+        sample_statistics(&args, precision, rng, &z, fixed_word.as_deref(), samples);
+        return 0;
+    }
+
+    let word =
+        if let Some(n) = args.random_word {
+            Some(random_word(rng, n, args.reduce))
+        } else if let Some(cf) = &args.cf {
+            let terms = cf.expand(args.cf_periods);
+            out!("slope = {}", convergent(&terms));
+            Some(cf_letters(&args, &terms))
+        } else if let Some(w) = &args.word {
+            Some(match args.reduce {
+                None => w.clone(),
+                Some(Reduction::Free) => word::free_reduce(w),
+                Some(Reduction::Cyclic) => word::cyclic_reduce(w),
+            })
+        } else {
+            None
+        };
+
+
+    if args.renormalize == Some(0) {
+        fail(Error::Input("--renormalize must be positive.".to_string()))
+    }
+    let track_det = args.det_drift || args.renormalize.is_some();
+    let mut drift = None;
+    let res =
+        if let Some(w) = &word {
+            if args.reduce.is_some() {
+                out!("reduced_word = {}", if w.is_empty() { "1" } else { w });
+            }
+            if track_det {
+                let (m, d) = gens.eval_tracking(w, args.renormalize);
+                drift = Some(d);
+                m
+            } else if args.checkpoint.is_some() || args.resume.is_some() {
+                checkpointed_eval(&args, &gens, w, &context)
+            } else {
+                gens.eval(w)
+            }
+        } else if let Some(x) = &slope {
+            if track_det {
+                let (m, d) = gens.eval_tracking(&slope_letters(&args, x.clone()), args.renormalize);
+                drift = Some(d);
+                m
+            } else {
+                if !cache.as_ref().is_some_and(|c| c.is_for(&gens.a, &gens.b)) {
+                    *cache = Some(SternBrocotCache::new(gens.a.clone(), gens.b.clone()));
+                }
+                let cache = cache.as_mut().expect("just filled");
+                cache.word(x, M::mul, iteration_limit(&args)).unwrap_or_else(|e| limit_reached(e))
+            }
+        } else {
+            fail(Error::Input("At least one of --word, --random-word, -r, --cf must be provided.".to_string()));
+        };
+
+    let derivative = args.derivative.then(|| {
+        let letters = word.clone().or_else(|| slope.clone().map(|q| slope_letters(&args, q))).unwrap_or_default();
+        require_two_generators(&letters, "--derivative");
+        let (trace, derivative) = dual::trace_derivative(family(&args), precision, &z, &letters)
+            .unwrap_or_else(|e| fail(e));
+        let length_derivative = dual::complex_length_derivative(&trace, &derivative);
+        (derivative, length_derivative)
+    });
+
+    #[cfg(feature = "rigorous")]
+    let rigorous = args.rigorous.then(|| {
+        let letters = word.clone().or_else(|| slope.clone().map(|q| slope_letters(&args, q))).unwrap_or_default();
+        rigorous_word(&args, precision, &z, &letters)
+    });
+
+    if let Some(format) = args.format {
+        print_cas(&args, precision, &gens, &z, &res, format);
+    } else {
+        let [x, y, z, w] = &res.0;
+        out!("{} {}\n{} {}", x.clone(), y.clone(), z.clone(), w.clone());
+        out!("trace = {}", x.clone() + w.clone());
+    }
+    if let Some((trace_derivative, _)) = &derivative {
+        out!("trace_derivative = {}", trace_derivative);
+    }
+    if args.plot.is_some() {
+        let letters = word.clone().or_else(|| slope.clone().map(|q| slope_letters(&args, q))).unwrap_or_default();
+        print_plot(&args, "plot_log_norm", &growth(&gens, &letters, args.plot_width), letters.len());
+    }
+    if let Some(drift) = &drift {
+        out!("det_error = {}", drift.final_error);
+        out!("max_det_error = {}", drift.max_error);
+        if args.renormalize.is_some() {
+            out!("renormalizations = {}", drift.renormalizations);
+            out!("skipped_renormalizations = {}", drift.skipped);
+        }
+    }
+    #[cfg(feature = "rigorous")]
+    if let Some(rigorous) = &rigorous {
+        print_rigorous(rigorous.as_ref());
+    }
+    if args.classify {
+        let class = classify(&res, precision);
+        out!("classification = {}", class);
+        match class {
+            Classification::Elliptic { angle } => out!("rotation_angle = {}", angle),
+            Classification::Loxodromic { translation_length } =>
+                out!("translation_length = {}", translation_length),
+            Classification::Identity | Classification::Parabolic => {}
+        }
+    }
+    if args.complex_length {
+        let length = complex_length(&res);
+        out!("complex_length = {}", length);
+        if let Some((_, length_derivative)) = &derivative {
+            out!("complex_length_derivative = {}", length_derivative);
+        }
+        out!("translation_length = {}", length.real());
+        out!("rotation = {}", length.imag());
+    }
+    if args.fixed_points {
+        let [attracting, repelling] = fixed_points(&res, precision);
+        match classify(&res, precision) {
+            Classification::Identity => out!("fixed_points = all"),
+            Classification::Parabolic => out!("fixed_point = {}", attracting),
+            Classification::Elliptic { .. } => out!("fixed_points = {} {}", attracting, repelling),
+            Classification::Loxodromic { .. } => {
+                out!("attracting_fixed_point = {}", attracting);
+                out!("repelling_fixed_point = {}", repelling);
+            }
+        }
+    }
+    for values in args.apply.chunks(2) {
+        let point = Point::parse(values, precision).unwrap_or_else(|e| {
+            fail(Error::Input(format!("Invalid --apply: {}", e)))
+        });
+        out!("apply = {} {}", point, mobius::apply(&res, &point));
+    }
+    if let Some(tau) = &args.tau {
+        print_modular(&res, tau, precision);
+    }
+    if args.normal_form {
+        print_normal_form(&res, precision);
+    }
+    if let Some(n) = args.power_trace {
+        out!("power_trace = {}", trace_map::power_trace(&res.trace(), n));
+    }
+    if let Some(path) = &args.circle_map {
+        match circle_map(&res, precision, args.circle_samples) {
+            Some(map) => {
+                let mut file = BufWriter::new(File::create(path).or_fail("could not create circle map file"));
+                let is_svg = path.extension().is_some_and(|e| e == "svg");
+                if is_svg {
+                    circle::write_svg(&map, &mut file, &context)
+                } else {
+                    circle::write_csv(&map, &mut file, &context)
+                }.or_fail("could not write circle map file");
+                for (t, derivative) in &map.fixed_points {
+                    out!("circle_fixed_point = {} {}", t, derivative);
+                }
+            }
+            None => eprintln!("warning: the circle map is only defined for real matrices"),
+        }
+    }
+    if let Some(iterations) = args.rotation_number {
+        let letters = word.clone().or_else(|| slope.map(|q| slope_letters(&args, q))).unwrap_or_default();
+        match rotation_number(&gens, &letters, iterations as usize) {
+            Some(rho) => out!("rotation_number = {}", rho),
+            None => eprintln!("warning: the rotation number is only defined for real representations"),
+        }
+    }
+    if args.format.is_some() {
+        return 0;
+    }
+    let (lambda, [vx, vy]) = res.dominant_eigenvector(precision);
+    if !res.is_eigenvector([vx.clone(), vy.clone()]) {
+        eprintln!("warning: output is not very close to an eigenvector, increase precision")
+    }
+    out!("dominant_eigenvalue = {}", lambda);
+    out!("dominant_eigenvector = {} {}", vx, vy);
+    match res.eigen_decomposition(precision) {
+        EigenDecomposition::Diagonalizable([(lambda1, [x1, y1]), (lambda2, [x2, y2])]) => {
+            out!("eigenvalue_1 = {}", lambda1);
+            out!("eigenvector_1 = {} {}", x1, y1);
+            out!("eigenvalue_2 = {}", lambda2);
+            out!("eigenvector_2 = {} {}", x2, y2);
+        }
+        EigenDecomposition::Jordan { eigenvalue, eigenvector: [x, y], generalized: [gx, gy] } => {
+            out!("eigenvalue_1 = {}", eigenvalue);
+            out!("eigenvector_1 = {} {}", x, y);
+            out!("generalized_eigenvector = {} {}", gx, gy);
+        }
+        EigenDecomposition::Scalar(lambda) => {
+            out!("eigenvalue_1 = {}", lambda);
+            out!("eigenvectors = all");
+        }
+    }
+    0
+}
@@ -0,0 +1,141 @@
+//! The command line tool on the pure Rust `dashu` backend, for machines where GMP/MPFR
+//! cannot be built. It evaluates a single word in the generators of `--param z`, `maskit`
+//! or `riley` through `repcalc::scalar`, with the options of the full tool that take part
+//! in that, and prints the matrix and its trace in the same format. Every other option of
+//! the full tool is rejected with an error that names it.
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::{ArgGroup, Parser, ValueEnum};
+
+use repcalc::budget::IterationLimit;
+use repcalc::error::{Error, STATUS_INPUT};
+use repcalc::scalar::{self, DashuComplex, Scalar};
+use repcalc::word;
+
+fn parse_word(input: &str) -> Result<String, String> {
+    if input.chars().all(|c| matches!(c, 'a' | 'b' | 'A' | 'B')) {
+        Ok(input.to_string())
+    } else {
+        Err("Value must contain only the letters 'a', 'b', 'A', 'B'.".to_string())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Parameterization {
+    Z,
+    Maskit,
+    Riley,
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "repcalc", version, long_about = None,
+          about = "repcalc's word evaluator on the pure Rust dashu backend, built without GMP/MPFR (slower)")]
+#[command(group(ArgGroup::new("word_source").args(["word", "r"]).required(true)))]
+struct Args {
+    /// The parameter of --param, x + i y, read at the working precision
+    #[arg(short, value_names = ["x", "y"], num_args = 2, allow_negative_numbers = true, required = true)]
+    z: Vec<String>,
+
+    /// Number of bits of precision for floating point arithmetic
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..))]
+    precision: u32,
+
+    /// The family of the generators
+    #[arg(long, value_enum, default_value = "z")]
+    param: Parameterization,
+
+    /// Word in a, b, A, B to evaluate
+    #[arg(long, value_parser = parse_word)]
+    word: Option<String>,
+
+    /// Evaluate the Stern-Brocot word of the slope p/q
+    #[arg(short, num_args = 2, value_names = ["p", "q"])]
+    r: Option<Vec<u64>>,
+
+    /// Freely reduce the word before evaluating it
+    #[arg(long)]
+    reduce: bool,
+
+    /// Stop the Stern-Brocot descent of -r with an error after this many steps; 0 means
+    /// no limit
+    #[arg(long, default_value_t = 10_000_000)]
+    iteration_limit: u64,
+}
+
+/// Prints the error and exits with its status, see `Error::exit_code`.
+fn fail(e: Error) -> ! {
+    match &e {
+        Error::Limit(_) => eprintln!("{}. Raise --iteration-limit, or pass --iteration-limit 0 to continue without a limit.", e),
+        _ => eprintln!("{}", e),
+    }
+    std::process::exit(e.exit_code())
+}
+
+/// The options of this build, for the warning and the error on an option of the full tool.
+const SUPPORTED: &str = "-z, -p, --param z|maskit|riley, --word, -r, --reduce and --iteration-limit";
+
+pub fn main() {
+    let args = Args::try_parse().unwrap_or_else(|e| {
+        // --help and --version are not errors
+        if !e.use_stderr() {
+            e.exit()
+        }
+        // an option, subcommand or family of the full tool: say that this build lacks it,
+        // rather than that it does not exist
+        let context = |kind| match e.get(kind) {
+            Some(ContextValue::String(s)) => Some(s.as_str()),
+            _ => None,
+        };
+        let missing = match e.kind() {
+            ErrorKind::UnknownArgument => context(ContextKind::InvalidArg).map(str::to_string),
+            ErrorKind::InvalidSubcommand => context(ContextKind::InvalidSubcommand).map(str::to_string),
+            ErrorKind::InvalidValue if context(ContextKind::InvalidArg).is_some_and(|a| a.starts_with("--param")) => {
+                context(ContextKind::InvalidValue).filter(|v| !v.is_empty()).map(|v| format!("--param {}", v))
+            }
+            _ => None,
+        };
+        match missing {
+            Some(arg) => eprintln!("error: {} is not supported by this repcalc, which was built on the dashu backend \
+                                    without GMP/MPFR and supports only {}; the full repcalc needs the default rug \
+                                    feature", arg, SUPPORTED),
+            None => {
+                let _ = e.print();
+            }
+        }
+        std::process::exit(STATUS_INPUT)
+    });
+    eprintln!("warning: this repcalc was built without GMP/MPFR and runs on the pure Rust dashu backend, \
+               which is much slower; it supports only {}", SUPPORTED);
+    let precision = args.precision;
+
+    let Some(z) = DashuComplex::parse(precision, &args.z[0], &args.z[1]) else {
+        fail(Error::Input(format!("Invalid -z: {} {}", args.z[0], args.z[1])))
+    };
+    let letters = match (&args.word, &args.r) {
+        (Some(w), _) => w.clone(),
+        (None, Some(r)) => {
+            let limit = IterationLimit((args.iteration_limit > 0).then_some(args.iteration_limit));
+            word::slope_letters(r[0], r[1], limit).unwrap_or_else(|e| fail(e.into()))
+        }
+        (None, None) => unreachable!("clap requires --word or -r"),
+    };
+    let letters = if args.reduce {
+        let w = word::free_reduce(&letters);
+        println!("reduced_word = {}", if w.is_empty() { "1" } else { &w });
+        w
+    } else {
+        letters
+    };
+
+    let (a, b) = match args.param {
+        Parameterization::Z => {
+            scalar::check_z(precision, &z).unwrap_or_else(|e| fail(e));
+            scalar::rho(precision, &z)
+        }
+        Parameterization::Maskit => scalar::maskit(precision, &z),
+        Parameterization::Riley => scalar::riley(precision, &z),
+    };
+    let res = scalar::eval_word(precision, &a, &b, &letters).unwrap_or_else(|e| fail(e));
+    let [x, y, z, w] = &res.0;
+    println!("{} {}\n{} {}", x, y, z, w);
+    println!("trace = {}", scalar::trace(&res));
+}
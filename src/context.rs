@@ -0,0 +1,52 @@
+use std::io::{self, Write};
+
+/// How words and slopes are read, recorded in every output file. A word is the product
+/// of its letters from left to right, A and B are the inverses of a and b, and the word
+/// of a slope p/q is its Stern-Brocot word (that of 1 is a).
+pub const CONVENTION: &str = "left-to-right products, A = a^-1, B = b^-1, Stern-Brocot slope words";
+
+/// What a run computed with, written at the top of every file it writes, so that a file
+/// can still be interpreted long after the command line that made it is forgotten.
+#[derive(Clone, Debug)]
+pub struct RunContext {
+    /// The family of the generators (z, maskit, riley or trace), matrices for
+    /// --matrix-a and --matrix-b, or snapshot for --restore.
+    pub family: String,
+    /// The parameter of the family or the entries of the matrices, as computed with.
+    pub parameter: String,
+    /// The working precision in bits, if the run has one.
+    pub precision: Option<u32>,
+}
+
+impl RunContext {
+    /// The fields as (key, value), including the version and commit of repcalc.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("repcalc", env!("CARGO_PKG_VERSION").to_string()),
+            ("git", option_env!("REPCALC_GIT_HASH").unwrap_or("unknown").to_string()),
+            ("family", self.family.clone()),
+            ("parameter", self.parameter.clone()),
+            ("precision", self.precision.map_or("-".to_string(), |p| p.to_string())),
+            ("convention", CONVENTION.to_string()),
+        ]
+    }
+
+    /// Writes the fields as comment lines `# key = value`, which the readers of the
+    /// snapshot and certificate files, CSV readers with a comment character, and
+    /// Graphviz all skip.
+    pub fn write_comments<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        for (key, value) in self.fields() {
+            writeln!(out, "# {} = {}", key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the fields as an XML comment, for GraphML and SVG.
+    pub fn write_xml_comment<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "<!--")?;
+        for (key, value) in self.fields() {
+            writeln!(out, "  {} = {}", key, value.replace("--", "- -"))?;
+        }
+        writeln!(out, "-->")
+    }
+}
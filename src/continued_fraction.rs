@@ -0,0 +1,34 @@
+use rug::{Complex, Float};
+
+use crate::matrix::{C, M};
+
+/// The n-th convergent of a continued fraction and the one before it.
+pub struct Convergents {
+    pub value: C,
+    pub previous: C,
+    /// |value - previous|, the usual estimate of the distance from the value to the limit.
+    pub truncation_error: Float,
+}
+
+/// The convergents P_n / Q_n and P_{n-1} / Q_{n-1} of b_0 + a_1 / (b_1 + a_2 / (b_2 + ...))
+/// for the n terms a_1, ..., a_n and b_1, ..., b_n (of the same length), from the
+/// matrix product
+///
+/// [[P_n, P_{n-1}], [Q_n, Q_{n-1}]] = [[b_0, 1], [1, 0]] [[b_1, 1], [a_1, 0]] ... [[b_n, 1], [a_n, 0]],
+///
+/// multiplied as a balanced tree by `M::product` so that rounding errors build up over
+/// log n levels rather than n. `None` if Q_n or Q_{n-1} vanishes.
+pub fn convergents(b0: &C, a: &[C], b: &[C]) -> Option<Convergents> {
+    let precision = b0.prec().0;
+    let (zero, one) = (Complex::with_val(precision, 0), Complex::with_val(precision, 1));
+    let first = M([b0.clone(), one.clone(), one.clone(), zero.clone()]);
+    let terms = a.iter().zip(b).map(|(a, b)| M([b.clone(), one.clone(), a.clone(), zero.clone()]));
+    let [p, p_previous, q, q_previous] = M::product(std::iter::once(first).chain(terms).collect()).0;
+    if q.is_zero() || q_previous.is_zero() {
+        return None;
+    }
+    let value = p / q;
+    let previous = p_previous / q_previous;
+    let truncation_error = Float::with_val(precision, (value.clone() - &previous).abs_ref());
+    Some(Convergents { value, previous, truncation_error })
+}
@@ -0,0 +1,94 @@
+use std::time::{Duration, Instant};
+use rug::{Complex, Float};
+use rug::float::Constant;
+
+/// Complex multiplications in one product of 2x2 matrices.
+pub const MATRIX_MULTIPLICATION: u128 = 8;
+
+/// Above this height the Farey nodes are counted by the asymptotic 12 H^2 / pi^2 rather
+/// than by a sieve, which would take a word of memory per denominator.
+const SIEVE_LIMIT: u64 = 10_000_000;
+
+/// The number of nonempty freely reduced words of length at most `max_length` in `rank`
+/// generators and their inverses, the nodes of `for_each_reduced_word` and `find_word`
+/// when nothing is pruned.
+pub fn reduced_words(rank: u128, max_length: usize) -> u128 {
+    let mut total: u128 = 0;
+    let mut level = 2 * rank;
+    for _ in 0..max_length {
+        total = total.saturating_add(level);
+        level = level.saturating_mul(2 * rank - 1);
+    }
+    total
+}
+
+fn divisors(n: usize) -> impl Iterator<Item = usize> {
+    (1..=n).filter(move |d| n % d == 0)
+}
+
+fn totient(n: usize) -> usize {
+    (1..=n).filter(|&k| gcd(k, n) == 1).count()
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// The number of nontrivial conjugacy classes of cyclically reduced length exactly `n` in
+/// F_2, among the words of `conjugacy_classes`. There are c_n = 3^n + 1 + (1 + (-1)^n)
+/// cyclically reduced words of length n, and by Burnside's lemma
+/// (1/n) sum over d | n of phi(n/d) c_d classes of them under rotation.
+pub fn conjugacy_classes(n: usize) -> u128 {
+    let cyclically_reduced = |n: usize| {
+        let even = if n % 2 == 0 { 2 } else { 0 };
+        3u128.saturating_pow(n as u32).saturating_add(1 + even)
+    };
+    let fixed = divisors(n)
+        .map(|d| (totient(n / d) as u128).saturating_mul(cyclically_reduced(d)))
+        .fold(0u128, u128::saturating_add);
+    fixed / n as u128
+}
+
+/// The number of slopes p/q with 1 <= p, q <= `max_height` in lowest terms, the nodes
+/// of `for_each_farey`: 2 (phi(1) + ... + phi(H)) - 1.
+pub fn farey_nodes(max_height: u64) -> u128 {
+    if max_height == 0 {
+        return 0;
+    }
+    if max_height > SIEVE_LIMIT {
+        let h = max_height as f64;
+        return (12.0 * h * h / (std::f64::consts::PI * std::f64::consts::PI)) as u128;
+    }
+    let n = max_height as usize;
+    let mut phi: Vec<usize> = (0..=n).collect();
+    for p in 2..=n {
+        if phi[p] == p {
+            for k in (p..=n).step_by(p) {
+                phi[k] -= phi[k] / p;
+            }
+        }
+    }
+    2 * phi[1..].iter().map(|&x| x as u128).sum::<u128>() - 1
+}
+
+/// The time of one complex multiplication at `precision`, from timing a run of them for
+/// at least `duration`.
+pub fn calibrate(precision: u32, duration: Duration) -> Duration {
+    let pi = Float::with_val(precision, Constant::Pi);
+    let x = Complex::with_val(precision, (pi.clone().sqrt(), pi.clone().recip()));
+    let y = Complex::with_val(precision, (pi.clone().ln(), -pi));
+    // multiplying by y and then by 1/y keeps z bounded, so that its exponent does not drift
+    let y_inv = y.clone().recip();
+    let mut z = x;
+    let mut count = 0u32;
+    let start = Instant::now();
+    while start.elapsed() < duration || count < 1000 {
+        for _ in 0..100 {
+            z *= &y;
+            z *= &y_inv;
+        }
+        count += 200;
+    }
+    std::hint::black_box(&z);
+    start.elapsed() / count
+}
@@ -0,0 +1,322 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{self, Write};
+use rug::{Complex, Float, Rational};
+
+use crate::budget::{IterationLimit, LimitReached};
+use crate::classify::tolerance;
+use crate::context::RunContext;
+use crate::dual;
+use crate::matrix::C;
+use crate::scalar;
+use crate::stern_brocot::ExtendedRational;
+use crate::trace_polynomial::{Family, Polynomial, trace_polynomial};
+
+/// The cusps of the Maskit slice known in closed form, as (p, q, re, im^2) for the cusp
+/// mu = re + i sqrt(im^2) of slope p/q: the word a of 1 is parabolic at 2i, abb of 2 at
+/// -4 + 2i and aab of 1/2 at -1 + sqrt(3) i.
+const CATALOGUE: [(u64, u64, i32, u32); 3] = [(1, 1, 0, 4), (2, 1, -4, 4), (1, 2, -1, 3)];
+
+/// Where a cusp came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// The closed form in the built-in catalogue.
+    Catalogue,
+    /// A cusp cache file, at least as precise as asked for.
+    Cache,
+    /// Solved for now.
+    Solved,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Catalogue => write!(f, "catalogue"),
+            Source::Cache => write!(f, "cache"),
+            Source::Solved => write!(f, "solved"),
+        }
+    }
+}
+
+/// The cusp of the Maskit slice (`rho::maskit`) of a slope p/q: the parameter mu where
+/// the Stern-Brocot word of p/q becomes parabolic on the boundary of the slice.
+#[derive(Clone)]
+pub struct Cusp {
+    pub p: u64,
+    pub q: u64,
+    pub mu: C,
+    pub source: Source,
+}
+
+fn abs(x: &C) -> Float {
+    Float::with_val(x.prec().0, x.abs_ref())
+}
+
+/// The value and derivative at z of a polynomial with its constant term first.
+fn eval(coefficients: &[C], z: &C) -> (C, C) {
+    let precision = z.prec().0;
+    let mut value = Complex::with_val(precision, 0);
+    let mut derivative = Complex::with_val(precision, 0);
+    for c in coefficients.iter().rev() {
+        derivative = derivative * z + &value;
+        value = value * z + c;
+    }
+    (value, derivative)
+}
+
+/// All roots of a polynomial of positive degree, with its constant term first, by the
+/// Aberth-Ehrlich iteration from points on a circle enclosing them. `None` if the
+/// iteration has not settled after `max_iterations` rounds.
+pub fn roots(coefficients: &[C], max_iterations: usize) -> Option<Vec<C>> {
+    let precision = coefficients[0].prec().0;
+    let tol = tolerance(precision);
+    let n = coefficients.len() - 1;
+    let leading = &coefficients[n];
+    // every root is within 1 + max |c_k / c_n| of 0 (Cauchy's bound)
+    let radius = coefficients[..n].iter()
+        .map(|c| abs(&(c.clone() / leading)))
+        .fold(Float::with_val(precision, 0), |m, x| m.max(&x)) + 1u32;
+    let pi = Float::with_val(precision, rug::float::Constant::Pi);
+    let mut z: Vec<C> = (0..n)
+        .map(|k| {
+            // the offset keeps the starting points off any symmetry of the roots
+            let angle = (pi.clone() * 2u32 * k as u32 + 0.4f64) / n as u32;
+            Complex::with_val(precision, (angle.clone().cos(), angle.sin())) * &radius
+        })
+        .collect();
+    for _ in 0..max_iterations {
+        let mut settled = true;
+        for k in 0..n {
+            let (value, derivative) = eval(coefficients, &z[k]);
+            if value.is_zero() {
+                continue;
+            }
+            let ratio = value / derivative;
+            let mut repulsion = Complex::with_val(precision, 0);
+            for (j, w) in z.iter().enumerate() {
+                if j != k {
+                    repulsion += (z[k].clone() - w).recip();
+                }
+            }
+            let step = ratio.clone() / (1u32 - ratio * repulsion);
+            let size = abs(&z[k]).max(&Float::with_val(precision, 1));
+            if abs(&step) > tol.clone() * size {
+                settled = false;
+            }
+            z[k] -= step;
+        }
+        if settled {
+            return Some(z);
+        }
+    }
+    None
+}
+
+/// The cusp of slope p/q (p, q >= 1, in lowest terms), from the catalogue if it
+/// is there and otherwise solved: it is the root of tr W_{p/q}(mu) = 2 or -2 with the
+/// largest imaginary part, the rule of Indra's Pearls (chapter 9), where W_{p/q} is the
+/// Stern-Brocot word. `Ok(None)` if the root finder did not settle.
+pub fn maskit_cusp(p: u64, q: u64, precision: u32, limit: IterationLimit) -> Result<Option<Cusp>, LimitReached> {
+    if let Some(&(_, _, re, im_squared)) = CATALOGUE.iter().find(|&&(cp, cq, _, _)| (cp, cq) == (p, q)) {
+        let im = Float::with_val(precision, im_squared).sqrt();
+        let mu = Complex::with_val(precision, (re, im));
+        return Ok(Some(Cusp { p, q, mu, source: Source::Catalogue }));
+    }
+    let slope = ExtendedRational::R(Rational::from((p, q)));
+    let Polynomial(coefficients) = trace_polynomial(&slope, Family::Maskit, limit)?;
+    let coefficients: Vec<C> = coefficients.iter()
+        .map(|c| Complex::with_val(precision, (&c.re, &c.im)))
+        .collect();
+    let mut candidates = Vec::new();
+    for target in [2i32, -2] {
+        let mut shifted = coefficients.clone();
+        shifted[0] -= target;
+        match roots(&shifted, 1000) {
+            Some(r) => candidates.extend(r),
+            None => return Ok(None),
+        }
+    }
+    let mu = candidates.into_iter()
+        .max_by(|x, y| x.imag().partial_cmp(y.imag()).unwrap_or(Ordering::Equal))
+        .expect("the trace has positive degree");
+    Ok(Some(Cusp { p, q, mu, source: Source::Solved }))
+}
+
+/// A slope p/q of the Stern-Brocot tree, from 0/1 (word a) to 1/0 (word b), with its
+/// word and its cusp; 1/0 has none, as b is parabolic everywhere.
+#[derive(Clone)]
+pub struct Node {
+    pub p: u64,
+    pub q: u64,
+    pub word: String,
+    pub mu: Option<C>,
+}
+
+impl Node {
+    pub fn zero(precision: u32) -> Node {
+        Node { p: 0, q: 1, word: "a".to_string(), mu: Some(Complex::with_val(precision, (0, 2))) }
+    }
+
+    pub fn infinity() -> Node {
+        Node { p: 1, q: 0, word: "b".to_string(), mu: None }
+    }
+
+    /// The mediant of `self` and its Farey neighbour `high` above it, with its cusp. Below
+    /// 1/0 the cusp of p/q + 1 is that of p/q less 2; elsewhere it is solved by Newton's
+    /// method for tr = 2 or -2, whichever is nearer, from the mean of the cusps of the two
+    /// neighbours weighted by their denominators, since re mu stays close to -2 p/q. `None`
+    /// if Newton's method does not converge.
+    pub fn mediant(&self, high: &Node, precision: u32, max_iterations: usize) -> Option<Node> {
+        let low_mu = self.mu.as_ref().expect("the lower neighbour is not 1/0");
+        let (p, q) = (self.p + high.p, self.q + high.q);
+        let word = self.word.clone() + &high.word;
+        let mu = match &high.mu {
+            None => low_mu.clone() - 2u32,
+            Some(high_mu) => {
+                let seed = (low_mu.clone() * self.q + high_mu.clone() * high.q) / q;
+                // Stern-Brocot words are in a and b, so the evaluation cannot fail
+                let (trace, _) = dual::trace_derivative(scalar::maskit, precision, &seed, &word).ok()?;
+                let target = Complex::with_val(precision, if trace.real().is_sign_negative() { -2 } else { 2 });
+                let solution = dual::solve_trace(scalar::maskit, precision, &word, &target, seed, max_iterations).ok()?;
+                if !solution.converged {
+                    return None;
+                }
+                solution.z
+            }
+        };
+        Some(Node { p, q, word, mu: Some(mu) })
+    }
+}
+
+/// The cusp of a Stern-Brocot word, followed down the Stern-Brocot tree from the cusp 2i
+/// of a by `Node::mediant` rather than found among all the roots of its trace polynomial.
+/// `None` if `word` is b, is not a Stern-Brocot word, or Newton's method fails at some node.
+pub fn continue_cusp(word: &str, precision: u32, max_iterations: usize) -> Option<C> {
+    let (p, q) = (word.matches('b').count() as u128, word.matches('a').count() as u128);
+    if q == 0 {
+        return None;
+    }
+    let (mut low, mut high) = (Node::zero(precision), Node::infinity());
+    if word == low.word {
+        return low.mu;
+    }
+    loop {
+        let node = low.mediant(&high, precision, max_iterations)?;
+        match (p * node.q as u128).cmp(&(node.p as u128 * q)) {
+            Ordering::Less => high = node,
+            Ordering::Greater => low = node,
+            Ordering::Equal => return if node.word == word { node.mu } else { None },
+        }
+    }
+}
+
+/// The cusps of the slopes from `from` to `to` (p/q with p, q >= 0 and `from` < `to`) of
+/// height max(p, q) at most `max_height`, in order along the boundary of the Maskit slice,
+/// each solved by `Node::mediant` from its two neighbours above it in the Stern-Brocot tree.
+/// Going down the tree tracks the parabolic condition from cusp to cusp, so the points
+/// trace out the boundary arc between the two cusps, more finely for a larger height.
+/// `Err` with the slope whose cusp Newton's method failed to find.
+pub fn boundary_arc(from: (u64, u64), to: (u64, u64), max_height: u64, precision: u32,
+                    max_iterations: usize) -> Result<Vec<Node>, (u64, u64)> {
+    // p/q < r/s for q, s >= 0, with 1/0 the largest
+    let less = |(p, q): (u64, u64), (r, s): (u64, u64)| (p as u128) * (s as u128) < (r as u128) * (q as u128);
+    let within = |node: &Node| !less((node.p, node.q), from) && !less(to, (node.p, node.q));
+    let mut arc = Vec::new();
+    let low = Node::zero(precision);
+    if within(&low) {
+        arc.push(low.clone());
+    }
+    // an in-order walk of the tree: split the interval between two neighbours, or emit a node
+    enum Step {
+        Split(Node, Node),
+        Emit(Node),
+    }
+    let mut stack = vec![Step::Split(low, Node::infinity())];
+    while let Some(step) = stack.pop() {
+        let (low, high) = match step {
+            Step::Split(low, high) => (low, high),
+            Step::Emit(node) => {
+                arc.push(node);
+                continue;
+            }
+        };
+        let (p, q) = (low.p + high.p, low.q + high.q);
+        // every slope below this interval in the tree is strictly inside it
+        if p.max(q) > max_height || !less((low.p, low.q), to) || !less(from, (high.p, high.q)) {
+            continue;
+        }
+        let node = low.mediant(&high, precision, max_iterations).ok_or((p, q))?;
+        stack.push(Step::Split(node.clone(), high));
+        if within(&node) {
+            stack.push(Step::Emit(node.clone()));
+        }
+        stack.push(Step::Split(low, node));
+    }
+    Ok(arc)
+}
+
+/// A file of solved cusps, so that each is solved once per precision.
+pub struct CuspCache {
+    pub cusps: Vec<(u32, Cusp)>,
+}
+
+fn hex(x: &Float) -> String {
+    x.to_string_radix(16, None)
+}
+
+fn parse_hex(s: &str, precision: u32) -> Result<Float, String> {
+    Float::parse_radix(s, 16)
+        .map(|x| Float::with_val(precision, x))
+        .map_err(|_| format!("invalid hexadecimal number {}", s))
+}
+
+impl CuspCache {
+    /// Reads the lines `cusp p q precision re im` of a cache file, with mu written
+    /// exactly in hexadecimal; lines starting with `#` are comments.
+    pub fn read(input: &str) -> Result<CuspCache, String> {
+        let mut cusps = Vec::new();
+        for (n, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |e: &str| format!("line {}: {}", n + 1, e);
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [kind, p, q, bits, re, im] = fields[..] else {
+                return Err(error("expected cusp p q precision re im"));
+            };
+            if kind != "cusp" {
+                return Err(error(&format!("unknown record {}", kind)));
+            }
+            let p = p.parse().map_err(|_| error("invalid p"))?;
+            let q = q.parse().map_err(|_| error("invalid q"))?;
+            let bits = bits.parse().map_err(|_| error("invalid precision"))?;
+            let re = parse_hex(re, bits).map_err(|e| error(&e))?;
+            let im = parse_hex(im, bits).map_err(|e| error(&e))?;
+            let mu = Complex::with_val(bits, (re, im));
+            cusps.push((bits, Cusp { p, q, mu, source: Source::Cache }));
+        }
+        Ok(CuspCache { cusps })
+    }
+
+    /// The cached cusp of p/q with the most precision, if that is at least `precision`.
+    pub fn get(&self, p: u64, q: u64, precision: u32) -> Option<Cusp> {
+        self.cusps.iter()
+            .filter(|(bits, c)| (c.p, c.q) == (p, q) && *bits >= precision)
+            .max_by_key(|(bits, _)| *bits)
+            .map(|(_, c)| Cusp { mu: Complex::with_val(precision, &c.mu), ..c.clone() })
+    }
+
+    pub fn insert(&mut self, precision: u32, cusp: Cusp) {
+        self.cusps.push((precision, cusp));
+    }
+
+    /// Writes the cache after the comments of `context`.
+    pub fn write<W: Write>(&self, out: &mut W, context: &RunContext) -> io::Result<()> {
+        context.write_comments(out)?;
+        for (bits, c) in &self.cusps {
+            writeln!(out, "cusp {} {} {} {} {}", c.p, c.q, bits, hex(c.mu.real()), hex(c.mu.imag()))?;
+        }
+        Ok(())
+    }
+}
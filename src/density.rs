@@ -0,0 +1,92 @@
+use std::io::{self, Write};
+
+/// A rectangle [x0, x1] x [y0, y1] of the plane.
+#[derive(Clone, Copy, Debug)]
+pub struct Window {
+    pub x0: f64,
+    pub y0: f64,
+    pub x1: f64,
+    pub y1: f64,
+}
+
+impl Window {
+    /// A window around the central 98% of the points in each coordinate, so that a
+    /// few points near infinity do not shrink everything else to a dot.
+    pub fn around(points: &[(f64, f64)]) -> Option<Self> {
+        if points.is_empty() {
+            return None;
+        }
+        let range = |mut xs: Vec<f64>| {
+            xs.sort_by(f64::total_cmp);
+            let k = xs.len() / 100;
+            (xs[k], xs[xs.len() - 1 - k])
+        };
+        let (x0, x1) = range(points.iter().map(|p| p.0).collect());
+        let (y0, y1) = range(points.iter().map(|p| p.1).collect());
+        // a margin of 5% on each side, and no side shorter than a tenth of the other
+        let size = (x1 - x0).max(y1 - y0).max(1e-9);
+        let pad = |lo: f64, hi: f64| {
+            let half = 0.55 * (hi - lo).max(size / 10.0);
+            ((lo + hi) / 2.0 - half, (lo + hi) / 2.0 + half)
+        };
+        let ((x0, x1), (y0, y1)) = (pad(x0, x1), pad(y0, y1));
+        Some(Window { x0, y0, x1, y1 })
+    }
+}
+
+/// A histogram of points over a grid of pixels.
+pub struct Density {
+    pub window: Window,
+    pub width: u32,
+    pub height: u32,
+    counts: Vec<u32>,
+}
+
+impl Density {
+    /// The height is chosen so that pixels are square.
+    pub fn new(window: Window, width: u32) -> Self {
+        let aspect = (window.y1 - window.y0) / (window.x1 - window.x0);
+        let height = ((width as f64 * aspect).round() as u32).max(1);
+        Density { window, width, height, counts: vec![0; width as usize * height as usize] }
+    }
+
+    /// Counts a point; points outside the window are dropped.
+    pub fn add(&mut self, x: f64, y: f64) {
+        let w = &self.window;
+        let i = ((x - w.x0) / (w.x1 - w.x0) * self.width as f64).floor();
+        // rows go down from y1
+        let j = ((w.y1 - y) / (w.y1 - w.y0) * self.height as f64).floor();
+        if i >= 0.0 && j >= 0.0 && i < self.width as f64 && j < self.height as f64 {
+            let k = j as usize * self.width as usize + i as usize;
+            self.counts[k] = self.counts[k].saturating_add(1);
+        }
+    }
+
+    /// Writes an RGB PNG with empty pixels white and the others shaded from blue
+    /// to dark red by log(1 + count), relative to the densest pixel.
+    pub fn write_png<W: Write>(&self, out: W) -> io::Result<()> {
+        let max = self.counts.iter().copied().max().unwrap_or(0);
+        let scale = (1.0 + max as f64).ln();
+        let mut data = Vec::with_capacity(self.counts.len() * 3);
+        for &count in &self.counts {
+            if count == 0 {
+                data.extend_from_slice(&[255, 255, 255]);
+                continue;
+            }
+            let t = (1.0 + count as f64).ln() / scale;
+            let mix = |low: f64, high: f64| (low + (high - low) * t).round() as u8;
+            data.extend_from_slice(&[mix(70.0, 160.0), mix(130.0, 0.0), mix(200.0, 30.0)]);
+        }
+        write_rgb_png(out, self.width, self.height, &data)
+    }
+}
+
+/// Writes 8-bit RGB pixel data, row by row from the top, as a PNG.
+pub fn write_rgb_png<W: Write>(out: W, width: u32, height: u32, data: &[u8]) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(out, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(data)?;
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use std::str::FromStr;
+use rug::{Complex, Float};
+
+/// How --digits writes the numbers it rounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Notation {
+    /// Positional for decimal exponents from -4 up to the number of digits, scientific
+    /// otherwise.
+    Auto,
+    /// Always d.ddde<exponent>.
+    Scientific,
+    /// Always positional, however many zeros that takes.
+    Fixed,
+}
+
+impl FromStr for Notation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "auto" => Ok(Notation::Auto),
+            "scientific" => Ok(Notation::Scientific),
+            "fixed" => Ok(Notation::Fixed),
+            _ => Err(format!("unknown notation {} (expected auto, scientific or fixed)", s)),
+        }
+    }
+}
+
+/// How printed numbers are written. The numbers are computed and printed at the working
+/// precision, and `line` rewrites the printed text, so every output line goes through
+/// the same rules without the code that prints it knowing about them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Style {
+    /// Significant decimal digits to round to, or all of them.
+    pub digits: Option<usize>,
+    pub notation: Notation,
+    /// Complex numbers as (modulus ∠argument), with the argument in radians.
+    pub polar: bool,
+}
+
+impl Style {
+    /// Whether the style leaves every line as it is.
+    pub fn is_plain(&self) -> bool {
+        self.digits.is_none() && self.notation == Notation::Auto && !self.polar
+    }
+
+    /// The line with each decimal number rewritten, and each complex number, printed as
+    /// (re im), rewritten as a pair or in polar form. Tokens that do not read as decimal
+    /// numbers with a point or an exponent, such as counts, words, slopes and paths, are
+    /// left as they are.
+    pub fn line(&self, line: &str) -> String {
+        if self.is_plain() {
+            return line.to_string();
+        }
+        let mut out = String::with_capacity(line.len());
+        let mut rest = line;
+        while !rest.is_empty() {
+            if let Some((z, len)) = self.complex_at(rest) {
+                out.push_str(&z);
+                rest = &rest[len..];
+                continue;
+            }
+            let first = rest.chars().next().map_or(1, char::len_utf8);
+            let end = rest.find(|c: char| c.is_whitespace() || c == '(' || c == ')').unwrap_or(rest.len()).max(first);
+            let token = &rest[..end];
+            match parse(token) {
+                Some((x, digits)) => out.push_str(&self.real(&x, digits)),
+                None => out.push_str(token),
+            }
+            rest = &rest[end..];
+        }
+        out
+    }
+
+    /// x written in this style, rounded to --digits or else to the decimal digits its
+    /// precision holds.
+    pub fn number(&self, x: &Float) -> String {
+        let digits = (x.prec() as f64 * std::f64::consts::LOG10_2).ceil() as usize;
+        self.real(x, digits)
+    }
+
+    /// A complex number (re im) at the start of `s`, rewritten, and its length in `s`.
+    fn complex_at(&self, s: &str) -> Option<(String, usize)> {
+        let inner = s.strip_prefix('(')?;
+        let len = inner.find(')')?;
+        let mut parts = inner[..len].split(' ');
+        let (Some(re), Some(im), None) = (parts.next(), parts.next(), parts.next()) else {
+            return None;
+        };
+        let ((re, re_digits), (im, im_digits)) = (parse(re)?, parse(im)?);
+        let digits = re_digits.max(im_digits);
+        let text = if self.polar {
+            let precision = re.prec().max(im.prec());
+            let z = Complex::with_val(precision, (re, im));
+            let modulus = Float::with_val(precision, z.abs_ref());
+            let argument = Float::with_val(precision, z.arg_ref());
+            format!("({} ∠{})", self.real(&modulus, digits), self.real(&argument, digits))
+        } else {
+            format!("({} {})", self.real(&re, digits), self.real(&im, digits))
+        };
+        Some((text, len + 2))
+    }
+
+    /// x rounded to `digits` significant digits, or to those of --digits if fewer.
+    fn real(&self, x: &Float, digits: usize) -> String {
+        let digits = self.digits.map_or(digits, |d| d.min(digits)).max(1);
+        let (negative, mantissa, exponent) = x.to_sign_string_exp(10, Some(digits));
+        let Some(exponent) = exponent else {
+            // zero: the infinities and NaN are not read as numbers
+            return "0".to_string();
+        };
+        let sign = if negative { "-" } else { "" };
+        let mantissa = mantissa.trim_end_matches('0');
+        let mantissa = if mantissa.is_empty() { "0" } else { mantissa };
+        // x = 0.mantissa * 10^exponent, with the decimal exponent e of d.ddd * 10^e
+        let e = exponent - 1;
+        let scientific = match self.notation {
+            Notation::Scientific => true,
+            Notation::Fixed => false,
+            Notation::Auto => e < -4 || e >= digits as i32,
+        };
+        if scientific {
+            let (first, rest) = mantissa.split_at(1);
+            let point = if rest.is_empty() { String::new() } else { format!(".{}", rest) };
+            return format!("{}{}{}e{}", sign, first, point, e);
+        }
+        if exponent <= 0 {
+            format!("{}0.{}{}", sign, "0".repeat(-exponent as usize), mantissa)
+        } else if (exponent as usize) < mantissa.len() {
+            let (whole, fraction) = mantissa.split_at(exponent as usize);
+            format!("{}{}.{}", sign, whole, fraction)
+        } else {
+            format!("{}{}{}", sign, mantissa, "0".repeat(exponent as usize - mantissa.len()))
+        }
+    }
+}
+
+/// A printed decimal number with its significant digits, read at enough precision to keep
+/// them all. Only tokens with a point or an exponent count, so that integers stay as they
+/// are.
+fn parse(token: &str) -> Option<(Float, usize)> {
+    let (mantissa, _) = token.split_once(['e', 'E']).unwrap_or((token, ""));
+    if !token.contains(['.', 'e', 'E']) || !mantissa.bytes().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    if !token.bytes().all(|c| c.is_ascii_digit() || matches!(c, b'.' | b'e' | b'E' | b'-' | b'+')) {
+        return None;
+    }
+    let digits = mantissa.trim_start_matches(['-', '+', '0', '.']).bytes().filter(u8::is_ascii_digit).count().max(1);
+    // log2(10) < 3.33 bits per digit, and a few more
+    let precision = (digits as u32 * 10).div_ceil(3) + 8;
+    let x = Float::parse(token).ok()?;
+    Some((Float::with_val(precision, x), digits))
+}
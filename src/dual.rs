@@ -0,0 +1,183 @@
+use std::fmt;
+use rug::{Complex, Float};
+
+use crate::classify::tolerance;
+use crate::error::Error;
+use crate::matrix::{C, M};
+use crate::scalar::{self, Scalar};
+
+/// A dual number value + derivative ε with ε^2 = 0. Evaluating a holomorphic
+/// function of z on z + ε gives f(z) + f'(z) ε, so running the word evaluator of
+/// `scalar` on duals differentiates with respect to the parameter.
+#[derive(Clone, Debug)]
+pub struct Dual {
+    pub value: C,
+    pub derivative: C,
+}
+
+impl Dual {
+    /// The parameter itself, z + ε.
+    pub fn variable(z: C) -> Self {
+        let derivative = Complex::with_val(z.prec(), 1);
+        Dual { value: z, derivative }
+    }
+}
+
+impl fmt::Display for Dual {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl Scalar for Dual {
+    fn from_f64(precision: u32, re: f64, im: f64) -> Self {
+        Dual { value: Complex::with_val(precision, (re, im)), derivative: Complex::with_val(precision, 0) }
+    }
+    fn parse(precision: u32, re: &str, im: &str) -> Option<Self> {
+        let value = <C as Scalar>::parse(precision, re, im)?;
+        Some(Dual { value, derivative: Complex::with_val(precision, 0) })
+    }
+    fn add(&self, other: &Self) -> Self {
+        Dual { value: self.value.clone() + &other.value, derivative: self.derivative.clone() + &other.derivative }
+    }
+    fn sub(&self, other: &Self) -> Self {
+        Dual { value: self.value.clone() - &other.value, derivative: self.derivative.clone() - &other.derivative }
+    }
+    fn mul(&self, other: &Self) -> Self {
+        let derivative = self.derivative.clone() * &other.value + self.value.clone() * &other.derivative;
+        Dual { value: self.value.clone() * &other.value, derivative }
+    }
+    fn div(&self, other: &Self) -> Self {
+        // (u / v)' = (u' v - u v') / v^2
+        let numerator = self.derivative.clone() * &other.value - self.value.clone() * &other.derivative;
+        let derivative = numerator / other.value.clone().square();
+        Dual { value: self.value.clone() / &other.value, derivative }
+    }
+    fn neg(&self) -> Self {
+        Dual { value: -self.value.clone(), derivative: -self.derivative.clone() }
+    }
+    fn sqrt(&self) -> Self {
+        let value = self.value.clone().sqrt();
+        let derivative = self.derivative.clone() / (value.clone() * 2u32);
+        Dual { value, derivative }
+    }
+    fn to_f64(&self) -> (f64, f64) {
+        self.value.to_f64()
+    }
+}
+
+/// The generators as functions of the parameter, for differentiating through them.
+pub type Family = fn(u32, &Dual) -> (M<Dual>, M<Dual>);
+
+/// tr W(z) and its derivative d(tr W)/dz.
+pub fn trace_derivative(family: Family, precision: u32, z: &C, word: &str) -> Result<(C, C), Error> {
+    let (a, b) = family(precision, &Dual::variable(z.clone()));
+    let trace = scalar::trace(&scalar::eval_word(precision, &a, &b, word)?);
+    Ok((trace.value, trace.derivative))
+}
+
+/// dL/dz for the complex length L = 2 arccosh(tr / 2) of `classify::complex_length`,
+/// given tr and d(tr)/dz. The derivative of arccosh(w) is 1 / (sqrt(w - 1) sqrt(w + 1))
+/// on the same branch as the principal arccosh.
+pub fn complex_length_derivative(trace: &C, trace_derivative: &C) -> C {
+    let w = trace.clone() / 2u32;
+    let root = (w.clone() - 1u32).sqrt() * (w + 1u32).sqrt();
+    trace_derivative.clone() / root
+}
+
+pub struct Solution {
+    pub z: C,
+    pub trace: C,
+    /// |tr W(z) - target| at the final z.
+    pub residual: Float,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+/// Newton's method for tr W(z) = target, starting from `start`. It stops when the
+/// step is below the tolerance of `classify::tolerance`, when the derivative
+/// vanishes, or after `max_iterations` steps.
+pub fn solve_trace(family: Family, precision: u32, word: &str, target: &C, start: C,
+                   max_iterations: usize) -> Result<Solution, Error> {
+    let tol = tolerance(precision);
+    let mut z = start;
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < max_iterations {
+        let (trace, derivative) = trace_derivative(family, precision, &z, word)?;
+        if derivative.is_zero() {
+            break;
+        }
+        let step = (trace - target) / derivative;
+        z -= &step;
+        iterations += 1;
+        if Float::with_val(precision, step.abs_ref()) <= tol {
+            converged = true;
+            break;
+        }
+    }
+    let (trace, _) = trace_derivative(family, precision, &z, word)?;
+    let residual = Float::with_val(precision, (trace.clone() - target).abs_ref());
+    Ok(Solution { z, trace, residual, iterations, converged })
+}
+
+/// Follows a solution of tr W(z) = t from `start` as t moves from tr W(start) to `target`
+/// in `steps` steps, correcting each with a few Newton steps, for a starting point for
+/// `solve_trace` when none near the target is known. The path bows off the segment between
+/// the two traces by an eighth of its length, so that it is unlikely to pass through a
+/// critical value, where two solutions meet and the one followed is ambiguous.
+pub fn continue_trace(family: Family, precision: u32, word: &str, target: &C, start: C, steps: u32)
+                      -> Result<C, Error> {
+    let (start_trace, _) = trace_derivative(family, precision, &start, word)?;
+    let delta = target.clone() - &start_trace;
+    let mut z = start;
+    for k in 1..=steps {
+        let s = Float::with_val(precision, k) / steps;
+        let bow = Complex::with_val(precision, (0, s.clone() * (1u32 - s.clone()) / 2u32));
+        let t = start_trace.clone() + delta.clone() * (bow + &s);
+        for _ in 0..4 {
+            let (trace, derivative) = trace_derivative(family, precision, &z, word)?;
+            if derivative.is_zero() {
+                break;
+            }
+            z -= (trace - &t) / derivative;
+        }
+    }
+    Ok(z)
+}
+
+/// A point of a pleating ray, with the real trace of the word there.
+pub struct RayPoint {
+    pub z: C,
+    pub trace: Float,
+}
+
+/// The rational pleating ray of W from near `start` to its cusp: the curve where tr W is
+/// real with |tr W| >= 2, along which tr W runs from +-infinity to +-2. `start` is first
+/// moved onto the ray by solving tr W = Re tr W(start), and then tr W is moved to 2 (or -2,
+/// on the same side) in `steps` equal steps, each solved by `solve_trace` from the last
+/// point. The last point is the cusp, where W is parabolic. Fails with `Error::Degenerate`
+/// if |Re tr W(start)| < 2, so that there is no ray through it, or if Newton's method does
+/// not converge at some step.
+pub fn trace_ray(family: Family, precision: u32, word: &str, start: C, steps: u32,
+                 max_iterations: usize) -> Result<Vec<RayPoint>, Error> {
+    let (start_trace, _) = trace_derivative(family, precision, &start, word)?;
+    let from = start_trace.real().clone();
+    if Float::with_val(precision, from.abs_ref()) < 2 {
+        return Err(Error::Degenerate(format!("|Re tr| = {} at the start is below 2", from.abs())));
+    }
+    let end = if from.is_sign_negative() { -2 } else { 2 };
+    let mut ray = Vec::with_capacity(steps as usize + 1);
+    let mut z = start;
+    for k in 0..=steps {
+        let s = Float::with_val(precision, k) / steps;
+        let t = from.clone() + (Float::with_val(precision, end) - &from) * s;
+        let solution = solve_trace(family, precision, word, &Complex::with_val(precision, &t), z, max_iterations)?;
+        if !solution.converged {
+            return Err(Error::Degenerate(format!("Newton's method did not converge at trace {}", t)));
+        }
+        z = solution.z;
+        ray.push(RayPoint { z: z.clone(), trace: t });
+    }
+    Ok(ray)
+}
@@ -0,0 +1,67 @@
+use rug::{Float, Rational};
+
+use crate::pleating::{SlopeCandidate, best_slopes};
+use crate::rho::Generators;
+use crate::stern_brocot::ExtendedRational;
+
+/// The slopes whose traces are nearest to +/-2, by min(|tr - 2|, |tr + 2|). At a cusp
+/// on the boundary of the Maskit or Riley slice the word of one slope p/q is parabolic,
+/// and p/q is the (rational) end invariant.
+pub fn cusp_candidates(gens: &Generators, max_height: u64) -> Vec<SlopeCandidate> {
+    let precision = gens.precision;
+    best_slopes(gens, max_height, |_, trace| {
+        let minus = Float::with_val(precision, (trace.clone() - 2u32).abs_ref());
+        let plus = Float::with_val(precision, (trace.clone() + 2u32).abs_ref());
+        Some(minus.min(&plus))
+    })
+}
+
+/// The slopes whose traces grow slowest, by log(1 + |tr|) / (p + q), the growth per
+/// letter of the Stern-Brocot word of p/q (which has p + q letters). Traces grow
+/// exponentially in the word length away from the end invariant and stay bounded along
+/// it, so at a point with an irrational end invariant these slopes approach it as the
+/// height grows.
+pub fn growth_candidates(gens: &Generators, max_height: u64) -> Vec<SlopeCandidate> {
+    let precision = gens.precision;
+    best_slopes(gens, max_height, |slope, trace| {
+        let length = if *slope == ExtendedRational::R(Rational::from(1)) {
+            // the word of 1 is just a
+            1
+        } else {
+            (slope.numer().clone() + slope.denom()).to_u64().expect("lengths fit in u64")
+        };
+        let size = Float::with_val(precision, trace.abs_ref()) + 1u32;
+        Some(size.ln() / length)
+    })
+}
+
+pub enum Confidence {
+    High,
+    Medium,
+    Low,
+}
+
+/// The end invariant guessed from the candidates: rational if some slope is parabolic.
+pub enum EndInvariant {
+    Rational { slope: ExtendedRational, confidence: Confidence },
+    Irrational { estimate: ExtendedRational, confidence: Confidence },
+}
+
+/// Combines the two heuristics. A slope whose trace is +/-2 up to the tolerance is
+/// taken as a rational end invariant with high confidence. A trace within `near` of
+/// +/-2 whose slope also grows slowest gives a rational one with medium confidence.
+/// Otherwise the slowest growing slope estimates an irrational end invariant, with
+/// medium confidence if it is the same at the two largest heights and low otherwise.
+pub fn end_invariant(cusps: &[SlopeCandidate], growth: &[SlopeCandidate], tolerance: &Float,
+                     near: f64) -> Option<EndInvariant> {
+    let (cusp, slowest) = (cusps.last()?, growth.last()?);
+    if cusp.value <= *tolerance {
+        return Some(EndInvariant::Rational { slope: cusp.slope.clone(), confidence: Confidence::High });
+    }
+    if cusp.value <= near && cusp.slope == slowest.slope {
+        return Some(EndInvariant::Rational { slope: cusp.slope.clone(), confidence: Confidence::Medium });
+    }
+    let stable = growth.len() >= 2 && growth[growth.len() - 2].slope == slowest.slope;
+    let confidence = if stable { Confidence::Medium } else { Confidence::Low };
+    Some(EndInvariant::Irrational { estimate: slowest.slope.clone(), confidence })
+}
@@ -0,0 +1,63 @@
+use std::fmt;
+
+use crate::budget::LimitReached;
+
+/// The exit status of a check that failed, such as `relator = failed` or a --verify-up-to
+/// that found a counterexample. It is a result rather than an error.
+pub const STATUS_FAILED: i32 = 1;
+/// The exit status of a search that stopped before it could settle a check, such as
+/// `verify = incomplete`.
+pub const STATUS_INCOMPLETE: i32 = 2;
+/// The exit status of `Error::Input`, and of the errors clap finds in the arguments.
+pub const STATUS_INPUT: i32 = 3;
+/// The exit status of `Error::Degenerate`.
+pub const STATUS_DEGENERATE: i32 = 4;
+/// The exit status of `Error::Limit`.
+pub const STATUS_LIMIT: i32 = 5;
+/// The exit status of `Error::Io`.
+pub const STATUS_IO: i32 = 6;
+
+/// Why a computation could not be done, with a message for the user. The command line
+/// tool exits with a different status for each kind, so that a script can tell a mistake
+/// in its input from a parameter where the mathematics breaks down.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Invalid or inconsistent options, values or input files.
+    Input(String),
+    /// A parameter where the representation or an algorithm degenerates, such as the
+    /// branch points z = ±1 of the square roots in the generators, or a method that did
+    /// not converge there.
+    Degenerate(String),
+    /// An iterative algorithm ran into its `IterationLimit`.
+    Limit(LimitReached),
+    /// A file could not be read or written.
+    Io(String),
+}
+
+impl Error {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Input(_) => STATUS_INPUT,
+            Error::Degenerate(_) => STATUS_DEGENERATE,
+            Error::Limit(_) => STATUS_LIMIT,
+            Error::Io(_) => STATUS_IO,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Input(message) | Error::Degenerate(message) | Error::Io(message) => write!(f, "{}", message),
+            Error::Limit(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<LimitReached> for Error {
+    fn from(e: LimitReached) -> Self {
+        Error::Limit(e)
+    }
+}
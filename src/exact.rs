@@ -0,0 +1,532 @@
+use std::fmt;
+use rug::{Complex, Float, Integer, Rational};
+
+use crate::error::Error;
+use crate::matrix::C;
+use crate::scalar::{self, M, Scalar};
+
+/// An element re + im i of Q(i).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GaussianRational {
+    pub re: Rational,
+    pub im: Rational,
+}
+
+impl GaussianRational {
+    pub fn new(re: Rational, im: Rational) -> Self {
+        GaussianRational { re, im }
+    }
+
+    fn zero() -> Self {
+        GaussianRational::new(Rational::new(), Rational::new())
+    }
+
+    /// Parses the real and imaginary parts, each an integer, a fraction p/q or a decimal
+    /// such as 1.25, exactly.
+    pub fn parse(re: &str, im: &str) -> Result<Self, String> {
+        Ok(GaussianRational::new(parse_rational(re)?, parse_rational(im)?))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.re.is_zero() && self.im.is_zero()
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        GaussianRational::new(Rational::from(&self.re + &other.re), Rational::from(&self.im + &other.im))
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        GaussianRational::new(Rational::from(&self.re - &other.re), Rational::from(&self.im - &other.im))
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        let re = Rational::from(&self.re * &other.re) - Rational::from(&self.im * &other.im);
+        let im = Rational::from(&self.re * &other.im) + Rational::from(&self.im * &other.re);
+        GaussianRational::new(re, im)
+    }
+
+    /// 1 / (a + b i) = (a - b i) / (a^2 + b^2), or `None` for zero.
+    pub fn recip(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        let norm = Rational::from(&self.re * &self.re) + Rational::from(&self.im * &self.im);
+        Some(GaussianRational::new(Rational::from(&self.re / &norm), -Rational::from(&self.im / &norm)))
+    }
+
+    /// The principal square root, the one with positive real part or on the positive
+    /// imaginary axis as for complex floats, if it lies in Q(i). For p + q i it is a + b i
+    /// with a^2 = (r + p)/2 and b^2 = (r - p)/2, r = sqrt(p^2 + q^2), and b of the sign of q.
+    pub fn sqrt_exact(&self) -> Option<Self> {
+        let norm = Rational::from(&self.re * &self.re) + Rational::from(&self.im * &self.im);
+        let r = rational_sqrt(&norm)?;
+        let a = rational_sqrt(&(Rational::from(&r + &self.re) / 2u32))?;
+        let b = rational_sqrt(&(Rational::from(&r - &self.re) / 2u32))?;
+        Some(GaussianRational::new(a, if self.im < 0 { -b } else { b }))
+    }
+
+    /// The nearest complex number at `precision`.
+    pub fn to_complex(&self, precision: u32) -> C {
+        Complex::with_val(precision, (Float::with_val(precision, &self.re), Float::with_val(precision, &self.im)))
+    }
+}
+
+/// An integer, a fraction p/q or a decimal such as -1.25, exactly.
+fn parse_rational(input: &str) -> Result<Rational, String> {
+    let s = input.trim();
+    let invalid = || format!("invalid rational number {}", input);
+    if let Some((whole, fraction)) = s.split_once('.') {
+        let unsigned = whole.strip_prefix(['-', '+']).unwrap_or(whole);
+        if !unsigned.bytes().chain(fraction.bytes()).all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let digits: Rational = format!("{}{}", whole, fraction).parse().map_err(|_| invalid())?;
+        return Ok((0..fraction.len()).fold(digits, |x, _| x / 10u32));
+    }
+    s.parse().map_err(|_| invalid())
+}
+
+/// The square root of a rational that is a square of one.
+fn rational_sqrt(x: &Rational) -> Option<Rational> {
+    let (numer, denom) = (x.numer(), x.denom());
+    if numer.is_negative() || !numer.is_perfect_square() || !denom.is_perfect_square() {
+        return None;
+    }
+    Some(Rational::from((numer.clone().sqrt(), denom.clone().sqrt())))
+}
+
+/// Exact arithmetic in Q(i), so that the word evaluator and everything else generic over
+/// `Scalar` can run without rounding, as the ground truth for the floating point backends.
+/// The precision is ignored. Division by zero and the square root of a number that is not
+/// a square in Q(i) panic, so check them with `recip` and `sqrt_exact` first, as
+/// `rho_parameter` does for `scalar::rho`.
+impl Scalar for GaussianRational {
+    fn from_f64(_precision: u32, re: f64, im: f64) -> Self {
+        let exact = |x: f64| Rational::from_f64(x).expect("finite");
+        GaussianRational::new(exact(re), exact(im))
+    }
+    fn parse(_precision: u32, re: &str, im: &str) -> Option<Self> {
+        GaussianRational::parse(re, im).ok()
+    }
+    fn add(&self, other: &Self) -> Self {
+        GaussianRational::add(self, other)
+    }
+    fn sub(&self, other: &Self) -> Self {
+        GaussianRational::sub(self, other)
+    }
+    fn mul(&self, other: &Self) -> Self {
+        GaussianRational::mul(self, other)
+    }
+    fn div(&self, other: &Self) -> Self {
+        self.mul(&other.recip().expect("division by zero in Q(i)"))
+    }
+    fn neg(&self) -> Self {
+        GaussianRational::new(-self.re.clone(), -self.im.clone())
+    }
+    fn sqrt(&self) -> Self {
+        self.sqrt_exact().unwrap_or_else(|| panic!("{} is not a square in Q(i)", self))
+    }
+    fn to_f64(&self) -> (f64, f64) {
+        (self.re.to_f64(), self.im.to_f64())
+    }
+}
+
+/// Checks that `scalar::rho` can run exactly at z: the generators have entries in Q(i)
+/// when z^2 - 1 = s^2 for some s in Q(i), and then the root it takes for b is 1/s or -1/s.
+/// These are the z = (t + 1/t)/2 for t = z + s in Q(i), such as 5/4 (t = 2) or
+/// 3/4 + i/4 (t = 1 + i).
+pub fn rho_parameter(z: &GaussianRational) -> Result<(), Error> {
+    let one = GaussianRational::new(Rational::from(1), Rational::new());
+    let square = z.mul(z).sub(&one);
+    match square.sqrt_exact() {
+        None => Err(Error::Input(format!(
+            "z^2 - 1 = {} is not a square in Q(i); take z = (t + 1/t)/2 for a Gaussian rational t", square))),
+        Some(root) if root.is_zero() =>
+            Err(Error::Degenerate(format!("z = {} is a branch point of the generators: z^2 - 1 = 0", z))),
+        Some(_) => Ok(()),
+    }
+}
+
+/// The entries of a matrix over Q(i) rounded to complex numbers at `precision`.
+pub fn to_complex(m: &M<GaussianRational>, precision: u32) -> M<C> {
+    M(m.0.clone().map(|x| x.to_complex(precision)))
+}
+
+/// det m, exactly.
+pub fn det(m: &M<GaussianRational>) -> GaussianRational {
+    let [a, b, c, d] = &m.0;
+    a.mul(d).sub(&b.mul(c))
+}
+
+/// The matrix of a word over Q(i), in any of the generators of `scalar` at an exact
+/// parameter.
+pub fn eval_word(a: &M<GaussianRational>, b: &M<GaussianRational>, word: &str) -> Result<M<GaussianRational>, Error> {
+    scalar::eval_word(0, a, b, word)
+}
+
+impl fmt::Display for GaussianRational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let im = match &self.im {
+            im if *im == 1 => String::new(),
+            im if *im == -1 => "-".to_string(),
+            im => im.to_string(),
+        };
+        match (self.re.is_zero(), self.im.is_zero()) {
+            (_, true) => write!(f, "{}", self.re),
+            (true, false) => write!(f, "{}i", im),
+            (false, false) if self.im < 0 => write!(f, "({}{}i)", self.re, im),
+            (false, false) => write!(f, "({}+{}i)", self.re, im),
+        }
+    }
+}
+
+/// The ring Q(i)[z]/(f) for a polynomial f with integer coefficients. When f is
+/// irreducible over Q(i) this is the number field Q(i, z) with z a root of f.
+pub struct Field {
+    /// f divided by its leading coefficient, without the leading 1, constant term first.
+    reduction: Vec<Rational>,
+}
+
+/// An element of a `Field` as a polynomial in z of degree less than deg f, constant
+/// term first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Element(pub Vec<GaussianRational>);
+
+impl Field {
+    /// The field for f = c_n z^n + ... + c_0, given as [c_n, ..., c_0] with c_n nonzero
+    /// and n >= 1.
+    pub fn new(coefficients: &[Integer]) -> Result<Self, String> {
+        let leading = coefficients.first().filter(|c| !c.is_zero())
+            .ok_or("the leading coefficient must be nonzero")?;
+        if coefficients.len() < 2 {
+            return Err("the polynomial must have degree at least 1".to_string());
+        }
+        let reduction = coefficients[1..].iter().rev()
+            .map(|c| Rational::from((c.clone(), leading.clone())))
+            .collect();
+        Ok(Field { reduction })
+    }
+
+    pub fn degree(&self) -> usize {
+        self.reduction.len()
+    }
+
+    pub fn constant(&self, c: GaussianRational) -> Element {
+        let mut e = vec![GaussianRational::zero(); self.degree()];
+        e[0] = c;
+        Element(e)
+    }
+
+    pub fn integer(&self, re: i32, im: i32) -> Element {
+        self.constant(GaussianRational::new(Rational::from(re), Rational::from(im)))
+    }
+
+    /// The generator z.
+    pub fn z(&self) -> Element {
+        let mut e = vec![GaussianRational::zero(); self.degree()];
+        if self.degree() == 1 {
+            // z = -c_0 / c_1
+            e[0] = GaussianRational::new(-self.reduction[0].clone(), Rational::new());
+        } else {
+            e[1] = GaussianRational::new(Rational::from(1), Rational::new());
+        }
+        Element(e)
+    }
+
+    pub fn add(&self, x: &Element, y: &Element) -> Element {
+        Element(x.0.iter().zip(&y.0).map(|(a, b)| a.add(b)).collect())
+    }
+
+    pub fn sub(&self, x: &Element, y: &Element) -> Element {
+        Element(x.0.iter().zip(&y.0).map(|(a, b)| a.sub(b)).collect())
+    }
+
+    pub fn neg(&self, x: &Element) -> Element {
+        self.sub(&self.integer(0, 0), x)
+    }
+
+    pub fn mul(&self, x: &Element, y: &Element) -> Element {
+        let n = self.degree();
+        let mut product = vec![GaussianRational::zero(); 2 * n - 1];
+        for (i, a) in x.0.iter().enumerate() {
+            for (j, b) in y.0.iter().enumerate() {
+                product[i + j] = product[i + j].add(&a.mul(b));
+            }
+        }
+        // z^n = -(r_{n-1} z^{n-1} + ... + r_0), applied from the top down
+        for k in (n..product.len()).rev() {
+            let top = std::mem::replace(&mut product[k], GaussianRational::zero());
+            for (j, r) in self.reduction.iter().enumerate() {
+                let term = top.mul(&GaussianRational::new(r.clone(), Rational::new()));
+                product[k - n + j] = product[k - n + j].sub(&term);
+            }
+        }
+        product.truncate(n);
+        Element(product)
+    }
+
+    /// 1 / x, by the extended Euclidean algorithm on x and f, or `None` when they have a
+    /// common root, as zero does.
+    pub fn inv(&self, x: &Element) -> Option<Element> {
+        let one = GaussianRational::new(Rational::from(1), Rational::new());
+        let mut f: Vec<GaussianRational> = self.reduction.iter().map(|r| GaussianRational::new(r.clone(), Rational::new())).collect();
+        f.push(one.clone());
+        // r = s x (mod f) for the pairs (r0, s0) and (r1, s1)
+        let (mut r0, mut s0) = (f, vec![]);
+        let (mut r1, mut s1) = (trimmed(x.0.clone()), vec![one]);
+        while !r1.is_empty() {
+            let (quotient, remainder) = poly_div_rem(&r0, &r1);
+            let s2 = poly_sub(&s0, &poly_mul(&quotient, &s1));
+            (r0, r1) = (r1, remainder);
+            (s0, s1) = (s1, s2);
+        }
+        // r0 is the gcd, a unit only if it is a constant
+        if r0.len() != 1 {
+            return None;
+        }
+        let scale = r0[0].recip()?;
+        let mut inverse: Vec<GaussianRational> = s0.iter().map(|c| c.mul(&scale)).collect();
+        inverse.resize(self.degree(), GaussianRational::zero());
+        Some(Element(inverse))
+    }
+
+    pub fn mul_matrix(&self, x: &M<Element>, y: &M<Element>) -> M<Element> {
+        let [a1, b1, c1, d1] = &x.0;
+        let [a2, b2, c2, d2] = &y.0;
+        M([
+            self.add(&self.mul(a1, a2), &self.mul(b1, c2)),
+            self.add(&self.mul(a1, b2), &self.mul(b1, d2)),
+            self.add(&self.mul(c1, a2), &self.mul(d1, c2)),
+            self.add(&self.mul(c1, b2), &self.mul(d1, d2)),
+        ])
+    }
+
+    /// The inverse of a matrix of determinant 1, which needs no division.
+    pub fn inv_sl2(&self, m: &M<Element>) -> M<Element> {
+        let [a, b, c, d] = &m.0;
+        M([d.clone(), self.neg(b), self.neg(c), a.clone()])
+    }
+
+    /// The matrix of a word in {a,b,A,B}; the empty word evaluates to the identity.
+    pub fn eval_word(&self, a: &M<Element>, b: &M<Element>, word: &str) -> Result<M<Element>, Error> {
+        let (a_inv, b_inv) = (self.inv_sl2(a), self.inv_sl2(b));
+        let (zero, one) = (self.integer(0, 0), self.integer(1, 0));
+        word.chars().try_fold(M([one.clone(), zero.clone(), zero, one]), |m, c| {
+            let letter = match c {
+                'a' => a,
+                'b' => b,
+                'A' => &a_inv,
+                'B' => &b_inv,
+                _ => return Err(scalar::not_a_letter(c)),
+            };
+            Ok(self.mul_matrix(&m, letter))
+        })
+    }
+
+    /// `rho::maskit` at z: a = [[-i z, -i], [-i, 0]], b = [[1, 2], [0, 1]].
+    pub fn maskit(&self) -> (M<Element>, M<Element>) {
+        let minus_i = self.integer(0, -1);
+        let a = M([self.mul(&minus_i, &self.z()), minus_i.clone(), minus_i, self.integer(0, 0)]);
+        let b = M([self.integer(1, 0), self.integer(2, 0), self.integer(0, 0), self.integer(1, 0)]);
+        (a, b)
+    }
+
+    /// `rho::riley` at z: a = [[1, 1], [0, 1]], b = [[1, 0], [z, 1]].
+    pub fn riley(&self) -> (M<Element>, M<Element>) {
+        let (zero, one) = (self.integer(0, 0), self.integer(1, 0));
+        let a = M([one.clone(), one.clone(), zero.clone(), one.clone()]);
+        let b = M([one.clone(), zero, self.z(), one]);
+        (a, b)
+    }
+}
+
+/// p without its zero coefficients of highest degree, so that zero is empty.
+fn trimmed(mut p: Vec<GaussianRational>) -> Vec<GaussianRational> {
+    while p.last().is_some_and(GaussianRational::is_zero) {
+        p.pop();
+    }
+    p
+}
+
+fn poly_sub(p: &[GaussianRational], q: &[GaussianRational]) -> Vec<GaussianRational> {
+    let zero = GaussianRational::zero();
+    trimmed((0..p.len().max(q.len()))
+        .map(|k| p.get(k).unwrap_or(&zero).sub(q.get(k).unwrap_or(&zero)))
+        .collect())
+}
+
+fn poly_mul(p: &[GaussianRational], q: &[GaussianRational]) -> Vec<GaussianRational> {
+    if p.is_empty() || q.is_empty() {
+        return vec![];
+    }
+    let mut product = vec![GaussianRational::zero(); p.len() + q.len() - 1];
+    for (i, a) in p.iter().enumerate() {
+        for (j, b) in q.iter().enumerate() {
+            product[i + j] = product[i + j].add(&a.mul(b));
+        }
+    }
+    trimmed(product)
+}
+
+/// The quotient and remainder of p by a nonzero trimmed q.
+fn poly_div_rem(p: &[GaussianRational], q: &[GaussianRational]) -> (Vec<GaussianRational>, Vec<GaussianRational>) {
+    let lead = q.last().and_then(GaussianRational::recip).expect("division by the zero polynomial");
+    let mut remainder = trimmed(p.to_vec());
+    let mut quotient = vec![GaussianRational::zero(); p.len().saturating_sub(q.len()) + 1];
+    while remainder.len() >= q.len() {
+        let shift = remainder.len() - q.len();
+        let c = remainder[remainder.len() - 1].mul(&lead);
+        for (k, b) in q.iter().enumerate() {
+            remainder[shift + k] = remainder[shift + k].sub(&c.mul(b));
+        }
+        // the top coefficient is now exactly zero
+        remainder.pop();
+        remainder = trimmed(remainder);
+        quotient[shift] = c;
+    }
+    (trimmed(quotient), remainder)
+}
+
+/// The ring K[s]/(s^2 - (z^2 - 1)) over a `Field` K: K with the square root s of
+/// z^2 - 1 adjoined, in which `rho::rho_a` and `rho::rho_b` have their entries.
+pub struct RootField<'a> {
+    pub field: &'a Field,
+    /// z^2 - 1.
+    square: Element,
+}
+
+/// An element x + y s of a `RootField`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RootElement(pub Element, pub Element);
+
+impl<'a> RootField<'a> {
+    pub fn new(field: &'a Field) -> Self {
+        let z = field.z();
+        let square = field.sub(&field.mul(&z, &z), &field.integer(1, 0));
+        RootField { field, square }
+    }
+
+    pub fn integer(&self, re: i32, im: i32) -> RootElement {
+        RootElement(self.field.integer(re, im), self.field.integer(0, 0))
+    }
+
+    pub fn add(&self, x: &RootElement, y: &RootElement) -> RootElement {
+        RootElement(self.field.add(&x.0, &y.0), self.field.add(&x.1, &y.1))
+    }
+
+    pub fn neg(&self, x: &RootElement) -> RootElement {
+        RootElement(self.field.neg(&x.0), self.field.neg(&x.1))
+    }
+
+    /// (x0 + x1 s)(y0 + y1 s) = x0 y0 + x1 y1 (z^2 - 1) + (x0 y1 + x1 y0) s.
+    pub fn mul(&self, x: &RootElement, y: &RootElement) -> RootElement {
+        let k = self.field;
+        let rational = k.add(&k.mul(&x.0, &y.0), &k.mul(&k.mul(&x.1, &y.1), &self.square));
+        RootElement(rational, k.add(&k.mul(&x.0, &y.1), &k.mul(&x.1, &y.0)))
+    }
+
+    pub fn mul_matrix(&self, x: &M<RootElement>, y: &M<RootElement>) -> M<RootElement> {
+        let [a1, b1, c1, d1] = &x.0;
+        let [a2, b2, c2, d2] = &y.0;
+        M([
+            self.add(&self.mul(a1, a2), &self.mul(b1, c2)),
+            self.add(&self.mul(a1, b2), &self.mul(b1, d2)),
+            self.add(&self.mul(c1, a2), &self.mul(d1, c2)),
+            self.add(&self.mul(c1, b2), &self.mul(d1, d2)),
+        ])
+    }
+
+    /// The inverse of a matrix of determinant 1, which needs no division.
+    pub fn inv_sl2(&self, m: &M<RootElement>) -> M<RootElement> {
+        let [a, b, c, d] = &m.0;
+        M([d.clone(), self.neg(b), self.neg(c), a.clone()])
+    }
+
+    /// The matrix of a word in {a,b,A,B}; the empty word evaluates to the identity.
+    pub fn eval_word(&self, a: &M<RootElement>, b: &M<RootElement>, word: &str) -> Result<M<RootElement>, Error> {
+        let (a_inv, b_inv) = (self.inv_sl2(a), self.inv_sl2(b));
+        let (zero, one) = (self.integer(0, 0), self.integer(1, 0));
+        word.chars().try_fold(M([one.clone(), zero.clone(), zero, one]), |m, c| {
+            let letter = match c {
+                'a' => a,
+                'b' => b,
+                'A' => &a_inv,
+                'B' => &b_inv,
+                _ => return Err(scalar::not_a_letter(c)),
+            };
+            Ok(self.mul_matrix(&m, letter))
+        })
+    }
+
+    /// `rho::rho_a` and `rho::rho_b` at z: with t = 1 / (z^2 - 1), so that 1/s = t s,
+    /// a = [[z t s, t s], [t s, z t s]] and b = [[-z, i s], [-i s, -z]]. `rho_b` takes
+    /// the same b where z^2 - 1 is not a negative real number, and -b where it is, which
+    /// changes the sign of the traces of words with an odd number of b's. An error at the
+    /// branch points, where z^2 - 1 has no inverse.
+    pub fn rho(&self) -> Result<(M<RootElement>, M<RootElement>), Error> {
+        let k = self.field;
+        let t = k.inv(&self.square).ok_or_else(|| Error::Degenerate(format!(
+            "z^2 - 1 = {} is not invertible in Q(i)[z]/({}): z is a branch point of the generators", self.square, k)))?;
+        let zero = k.integer(0, 0);
+        let (z, zt) = (k.z(), k.mul(&k.z(), &t));
+        let diagonal = RootElement(zero.clone(), zt);
+        let off = RootElement(zero.clone(), t);
+        let a = M([diagonal.clone(), off.clone(), off, diagonal]);
+        let minus_z = RootElement(k.neg(&z), zero.clone());
+        let b = M([minus_z.clone(), RootElement(zero.clone(), k.integer(0, 1)),
+                   RootElement(zero, k.integer(0, -1)), minus_z]);
+        Ok((a, b))
+    }
+}
+
+impl fmt::Display for RootElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let root = self.1.0.iter().any(|c| !c.is_zero());
+        match (self.0.0.iter().any(|c| !c.is_zero()), root) {
+            (_, false) => write!(f, "{}", self.0),
+            (false, true) => write!(f, "({}) s", self.1),
+            (true, true) => write!(f, "{} + ({}) s", self.0, self.1),
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    /// The monic polynomial whose roots z is taken to be.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.degree() {
+            1 => write!(f, "z")?,
+            n => write!(f, "z^{}", n)?,
+        }
+        for (k, c) in self.reduction.iter().enumerate().rev().filter(|(_, c)| !c.is_zero()) {
+            let sign = if *c < 0 { "-" } else { "+" };
+            let c = c.clone().abs();
+            match k {
+                0 => write!(f, " {} {}", sign, c)?,
+                1 if c == 1 => write!(f, " {} z", sign)?,
+                1 => write!(f, " {} {} z", sign, c)?,
+                _ if c == 1 => write!(f, " {} z^{}", sign, k)?,
+                _ => write!(f, " {} {} z^{}", sign, c, k)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms: Vec<String> = self.0.iter().enumerate().rev()
+            .filter(|(_, c)| !c.is_zero())
+            .map(|(k, c)| match k {
+                0 => c.to_string(),
+                _ if c.re == 1 && c.im.is_zero() => if k == 1 { "z".to_string() } else { format!("z^{}", k) },
+                1 => format!("{} z", c),
+                _ => format!("{} z^{}", c, k),
+            })
+            .collect();
+        if terms.is_empty() {
+            write!(f, "0")
+        } else {
+            write!(f, "{}", terms.join(" + "))
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use rug::Float;
+use rug::ops::Pow;
+
+use crate::budget::Budget;
+use crate::classify::tolerance;
+use crate::matrix::{C, M};
+use crate::rho::Generators;
+use crate::word::{canonical_rotation, inverse_letter, is_cyclically_reduced};
+
+/// A conjugacy class with the distance of its trace from the target.
+pub struct Candidate {
+    pub word: String,
+    pub trace: C,
+    pub distance: Float,
+}
+
+/// The best classes found so far, nearest first, with everything tied with the last.
+pub struct Search {
+    pub candidates: Vec<Candidate>,
+    /// The number of subtrees cut off by the trace bound.
+    pub pruned: u64,
+}
+
+fn frobenius_norm(m: &M<C>) -> Float {
+    let precision = m.0[0].prec().0;
+    m.0.iter()
+        .map(|x| Float::with_val(precision, x.abs_ref()).square())
+        .fold(Float::with_val(precision, 0), |s, x| s + x)
+        .sqrt()
+}
+
+/// The `count` conjugacy classes of cyclically reduced length at most `max_length` whose
+/// traces are nearest to `target`, each as its canonical rotation as in `length_spectrum`,
+/// together with every class tied with the last of them up to the tolerance. A class and
+/// its inverse have the same trace, so both appear.
+///
+/// The words are walked as a tree, multiplying out each prefix once. A prefix P with r
+/// letters left to add is cut off when no extension can come near enough: for a suffix S,
+/// |tr PS| <= |P| |S| <= |P| g^r in the Frobenius norm, where g is the largest norm of a
+/// generator, so every extension is further than |target| - |P| g^r from the target.
+/// Each prefix is one node of the budget.
+pub fn find_word(gens: &Generators, target: &C, max_length: usize, count: usize, budget: &mut Budget) -> Search {
+    let precision = gens.precision;
+    let tol = tolerance(precision);
+    let target_size = Float::with_val(precision, target.abs_ref());
+    let letters = gens.letters();
+    let growth = letters.iter()
+        .map(|&c| frobenius_norm(gens.letter(c)))
+        .fold(Float::with_val(precision, 0), |g, x| g.max(&x));
+    let mut search = Search { candidates: Vec::new(), pruned: 0 };
+
+    // the distance that a class must beat or tie to be kept, once there are `count`
+    let cutoff = |candidates: &[Candidate]| {
+        candidates.get(count.checked_sub(1)?).map(|c| c.distance.clone() + &tol)
+    };
+
+    let mut stack = vec![(String::new(), M::identity(precision))];
+    while let Some((word, m)) = stack.pop() {
+        if !word.is_empty() && is_cyclically_reduced(&word) && canonical_rotation(&word) == word {
+            let trace = m.trace();
+            let distance = Float::with_val(precision, (trace.clone() - target).abs_ref());
+            if cutoff(&search.candidates).map_or(true, |c| distance <= c) {
+                let position = search.candidates.partition_point(|c| c.distance <= distance);
+                search.candidates.insert(position, Candidate { word: word.clone(), trace, distance });
+                if let Some(c) = cutoff(&search.candidates) {
+                    search.candidates.retain(|x| x.distance <= c);
+                }
+            }
+        }
+        let left = max_length - word.len();
+        if left == 0 {
+            continue;
+        }
+        if let Some(c) = cutoff(&search.candidates) {
+            let bound = frobenius_norm(&m) * growth.clone().pow(left as u32);
+            if target_size.clone() - bound > c {
+                search.pruned += 1;
+                continue;
+            }
+        }
+        for c in letters.iter().rev() {
+            if word.chars().last().map(inverse_letter) == Some(*c) {
+                continue;
+            }
+            if !budget.spend() {
+                return search;
+            }
+            let mut next = word.clone();
+            next.push(*c);
+            stack.push((next, m.clone().mul(gens.letter(*c).clone())));
+        }
+    }
+    search
+}
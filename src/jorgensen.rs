@@ -0,0 +1,106 @@
+use rug::Float;
+
+use crate::budget::Budget;
+use crate::classify::tolerance;
+use crate::matrix::{C, M};
+use crate::rho::Generators;
+use crate::shard::Shard;
+use crate::sweep::for_each_reduced_word;
+use crate::word::{free_reduce, inverse_letter};
+
+/// |tr^2 A - 4| + |tr[A, B] - 2|. Jørgensen's inequality says that this is at
+/// least 1 whenever A and B generate a discrete non-elementary subgroup of SL(2,C).
+pub fn jorgensen_sum(a: &M<C>, b: &M<C>, precision: u32) -> Float {
+    let ta = a.trace();
+    let commutator = M::product(vec![a.clone(), b.clone(), a.clone().inv(), b.clone().inv()]);
+    let first = ta.square() - 4u32;
+    let second = commutator.trace() - 2u32;
+    Float::with_val(precision, first.abs_ref()) + Float::with_val(precision, second.abs_ref())
+}
+
+/// A pair of words (A, B) with the value of `jorgensen_sum` on their matrices.
+#[derive(Clone)]
+pub struct JorgensenPair {
+    pub first: String,
+    pub second: String,
+    pub sum: Float,
+}
+
+pub struct JorgensenReport {
+    /// The pairs that were tested; pairs with tr[A, B] = 2 up to the tolerance share
+    /// a fixed point, so the inequality says nothing about them and they are skipped.
+    pub checked: usize,
+    pub skipped: usize,
+    pub violations: usize,
+    /// The pair with the smallest sum.
+    pub min: Option<JorgensenPair>,
+    /// The first pair with sum below 1 - tolerance.
+    pub first_violation: Option<JorgensenPair>,
+}
+
+impl JorgensenReport {
+    pub fn passed(&self) -> bool {
+        self.violations == 0
+    }
+}
+
+fn inverse(word: &str) -> String {
+    word.chars().rev().map(inverse_letter).collect()
+}
+
+/// Tests the inequality on (a, b) and (b, a) and, when `max_length > 0`, on
+/// every pair (a, w b w^-1) and (b, w a w^-1) for a reduced word w of length at
+/// most `max_length`. A violation means that the representation is not discrete
+/// (or that its image is elementary). The conjugating words are enumerated until
+/// the budget runs out, and only the pairs in `shard` are tested; the generator
+/// pairs come first.
+pub fn jorgensen(gens: &Generators, max_length: usize, shard: Shard, budget: &mut Budget) -> JorgensenReport {
+    let precision = gens.precision;
+    let tol = tolerance(precision);
+    let one = Float::with_val(precision, 1) - &tol;
+    let mut report = JorgensenReport { checked: 0, skipped: 0, violations: 0, min: None, first_violation: None };
+
+    let mut test = |first: &str, second: &str, a: &M<C>, b: &M<C>| {
+        let commutator = M::product(vec![a.clone(), b.clone(), a.clone().inv(), b.clone().inv()]);
+        let error = commutator.trace() - 2u32;
+        if Float::with_val(precision, error.abs_ref()) <= tol {
+            report.skipped += 1;
+            return;
+        }
+        let pair = JorgensenPair { first: first.to_string(), second: second.to_string(), sum: jorgensen_sum(a, b, precision) };
+        report.checked += 1;
+        if pair.sum < one {
+            report.violations += 1;
+            if report.first_violation.is_none() {
+                report.first_violation = Some(pair.clone());
+            }
+        }
+        if report.min.as_ref().map_or(true, |min| pair.sum < min.sum) {
+            report.min = Some(pair);
+        }
+    };
+
+    let mut position = 0;
+    let mut in_shard = || {
+        position += 1;
+        shard.contains(position - 1)
+    };
+    if in_shard() {
+        test("a", "b", &gens.a, &gens.b);
+    }
+    if in_shard() {
+        test("b", "a", &gens.b, &gens.a);
+    }
+    for_each_reduced_word(gens, max_length, budget, |w, m| {
+        let m_inv = m.clone().inv();
+        for (c, other) in [('a', 'b'), ('b', 'a')] {
+            if !in_shard() {
+                continue;
+            }
+            let conjugate = free_reduce(&format!("{}{}{}", w, other, inverse(w)));
+            let matrix = M::product(vec![m.clone(), gens.letter(other).clone(), m_inv.clone()]);
+            test(&c.to_string(), &conjugate, gens.letter(c), &matrix);
+        }
+    });
+    report
+}
@@ -0,0 +1,88 @@
+#[cfg(feature = "rigorous")]
+pub mod ball;
+pub mod batch;
+pub mod bench;
+pub mod budget;
+#[cfg(feature = "rug")]
+pub mod cas;
+#[cfg(feature = "rug")]
+pub mod cayley;
+#[cfg(feature = "rigorous")]
+pub mod certificate;
+#[cfg(feature = "rug")]
+pub mod checkpoint;
+#[cfg(feature = "rug")]
+pub mod circle;
+#[cfg(feature = "rug")]
+pub mod classify;
+pub mod context;
+#[cfg(feature = "rug")]
+pub mod continued_fraction;
+#[cfg(feature = "rug")]
+pub mod cost;
+#[cfg(feature = "rug")]
+pub mod cusp;
+#[cfg(feature = "render")]
+pub mod density;
+#[cfg(feature = "rug")]
+pub mod display;
+#[cfg(feature = "rug")]
+pub mod dual;
+#[cfg(feature = "rug")]
+pub mod end_invariant;
+pub mod error;
+#[cfg(feature = "rug")]
+pub mod exact;
+#[cfg(feature = "rug")]
+pub mod find_word;
+#[cfg(feature = "rug")]
+pub mod jorgensen;
+#[cfg(feature = "rug")]
+pub mod limit_set;
+pub mod markov;
+#[cfg(feature = "rug")]
+pub mod matrix;
+#[cfg(feature = "rug")]
+pub mod mcshane;
+#[cfg(feature = "rug")]
+pub mod mobius;
+#[cfg(feature = "rug")]
+pub mod modular;
+#[cfg(feature = "notebook")]
+pub mod notebook;
+#[cfg(feature = "rug")]
+pub mod orbit;
+#[cfg(feature = "rug")]
+pub mod pingpong;
+#[cfg(feature = "rug")]
+pub mod pleating;
+pub mod plot;
+#[cfg(feature = "rug")]
+pub mod rho;
+#[cfg(feature = "rug")]
+pub mod real_form;
+#[cfg(feature = "rug")]
+pub mod results;
+#[cfg(feature = "rug")]
+pub mod rotation;
+#[cfg(feature = "rug")]
+pub mod sampling;
+pub mod scalar;
+#[cfg(feature = "rug")]
+pub mod scan;
+pub mod shard;
+#[cfg(feature = "rug")]
+pub mod snapshot;
+#[cfg(feature = "rug")]
+pub mod spectrum;
+#[cfg(feature = "rug")]
+pub mod stats;
+#[cfg(feature = "rug")]
+pub mod stern_brocot;
+#[cfg(feature = "rug")]
+pub mod sweep;
+#[cfg(feature = "rug")]
+pub mod trace_map;
+#[cfg(feature = "rug")]
+pub mod trace_polynomial;
+pub mod word;
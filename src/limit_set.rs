@@ -0,0 +1,63 @@
+use std::io::{self, Write};
+
+use crate::budget::Budget;
+use crate::classify::{Classification, classify};
+use crate::context::RunContext;
+use crate::matrix::{C, M};
+use crate::mobius::{Point, fixed_points};
+use crate::rho::Generators;
+use crate::shard::Shard;
+use crate::sweep::{Frontier, for_each_reduced_word_from};
+
+/// Calls `f` with each loxodromic or parabolic reduced word of length at most `depth`
+/// and its attracting fixed point. These points accumulate on the limit set as the
+/// depth grows. Points are given in the affine chart C of CP^1 (the stereographic
+/// projection of the sphere), and the fixed points at infinity are left out. Only
+/// the words in `shard` are used, and the enumeration stops when the budget runs out.
+pub fn for_each_limit_point(gens: &Generators, depth: usize, shard: Shard, budget: &mut Budget,
+                            mut f: impl FnMut(&str, &C)) {
+    for_each_limit_point_from(gens, depth, shard, None, budget, |progress, point| {
+        if let Some(point) = point {
+            f(progress.word, point);
+        }
+    });
+}
+
+/// How far an enumeration of `for_each_limit_point_from` got: the reduced words
+/// enumerated, counting those outside the shard, and where the enumeration stands.
+pub struct Progress<'a> {
+    pub words: u64,
+    pub word: &'a str,
+    pub prefixes: &'a [M<C>],
+}
+
+/// Like `for_each_limit_point`, but carries on from `start`, the number of words
+/// enumerated and the frontier saved from the `Progress` of an earlier run, and calls `f`
+/// after every word with the progress and the limit point, if the word has one, so that
+/// the caller can save its progress.
+pub fn for_each_limit_point_from(gens: &Generators, depth: usize, shard: Shard, start: Option<(u64, &Frontier)>,
+                                 budget: &mut Budget, mut f: impl FnMut(&Progress, Option<&C>)) {
+    let precision = gens.precision;
+    let mut position = start.map_or(0, |(words, _)| words);
+    for_each_reduced_word_from(gens, depth, start.map(|(_, frontier)| frontier), budget, |word, prefixes| {
+        position += 1;
+        let m = prefixes.last().expect("the word is nonempty");
+        let mut point = None;
+        if shard.contains((position - 1) as usize)
+            && !matches!(classify(m, precision), Classification::Identity | Classification::Elliptic { .. }) {
+            if let [Point::Finite(p), _] = fixed_points(m, precision) {
+                point = Some(p);
+            }
+        }
+        f(&Progress { words: position, word, prefixes }, point.as_ref());
+    });
+}
+
+pub fn write_csv_header<W: Write>(out: &mut W, context: &RunContext) -> io::Result<()> {
+    context.write_comments(out)?;
+    writeln!(out, "x,y,word")
+}
+
+pub fn write_csv_row<W: Write>(out: &mut W, word: &str, point: &C) -> io::Result<()> {
+    writeln!(out, "{},{},{}", point.real(), point.imag(), word)
+}
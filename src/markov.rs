@@ -0,0 +1,119 @@
+use crate::word::{ball_edges, ball_words};
+
+/// The eigenvalues, in decreasing order, of the simple random walk operator of the
+/// 4-regular tree restricted to the ball of the given radius, i.e. the adjacency
+/// matrix of the ball divided by 4. On the whole tree the spectrum is
+/// [-sqrt(3)/2, sqrt(3)/2] (Kesten), and the largest eigenvalue of the ball
+/// increases to sqrt(3)/2 with the radius.
+pub fn markov_spectrum(radius: usize) -> Vec<f64> {
+    let words = ball_words(radius);
+    let n = words.len();
+    let mut m = vec![vec![0.0; n]; n];
+    for (i, j) in ball_edges(&words) {
+        m[i][j] = 0.25;
+        m[j][i] = 0.25;
+    }
+    let mut eigenvalues = symmetric_eigenvalues(m);
+    eigenvalues.sort_by(|x, y| y.total_cmp(x));
+    eigenvalues
+}
+
+/// The eigenvalues of a real symmetric matrix, by Householder reduction to
+/// tridiagonal form followed by the implicit QL algorithm.
+#[allow(clippy::needless_range_loop)]
+fn symmetric_eigenvalues(mut a: Vec<Vec<f64>>) -> Vec<f64> {
+    let n = a.len();
+    let mut d = vec![0.0; n];
+    let mut e = vec![0.0; n];
+
+    // Householder: row i is reduced against the leading i x i block
+    for i in (1..n).rev() {
+        let l = i - 1;
+        let scale: f64 = a[i][..=l].iter().map(|x| x.abs()).sum();
+        if l == 0 || scale == 0.0 {
+            e[i] = a[i][l];
+            continue;
+        }
+        let mut h = 0.0;
+        for k in 0..=l {
+            a[i][k] /= scale;
+            h += a[i][k] * a[i][k];
+        }
+        let f = a[i][l];
+        let g = if f >= 0.0 { -h.sqrt() } else { h.sqrt() };
+        e[i] = scale * g;
+        h -= f * g;
+        a[i][l] = f - g;
+        let mut f = 0.0;
+        for j in 0..=l {
+            let mut g = 0.0;
+            for k in 0..=j {
+                g += a[j][k] * a[i][k];
+            }
+            for k in j + 1..=l {
+                g += a[k][j] * a[i][k];
+            }
+            e[j] = g / h;
+            f += e[j] * a[i][j];
+        }
+        let hh = f / (h + h);
+        for j in 0..=l {
+            let f = a[i][j];
+            let g = e[j] - hh * f;
+            e[j] = g;
+            for k in 0..=j {
+                a[j][k] -= f * e[k] + g * a[i][k];
+            }
+        }
+    }
+    for i in 0..n {
+        d[i] = a[i][i];
+    }
+
+    // implicit QL on the tridiagonal matrix with diagonal d and off-diagonal e
+    e.rotate_left(1);
+    if let Some(last) = e.last_mut() {
+        *last = 0.0;
+    }
+    for l in 0..n {
+        for _ in 0..60 {
+            let mut m = l;
+            while m + 1 < n && e[m].abs() > f64::EPSILON * (d[m].abs() + d[m + 1].abs()) {
+                m += 1;
+            }
+            if m == l {
+                break;
+            }
+            let g = (d[l + 1] - d[l]) / (2.0 * e[l]);
+            let r = g.hypot(1.0);
+            let mut g = d[m] - d[l] + e[l] / (g + r.copysign(g));
+            let (mut s, mut c, mut p) = (1.0, 1.0, 0.0);
+            let mut underflow = false;
+            for i in (l..m).rev() {
+                let f = s * e[i];
+                let b = c * e[i];
+                let r = f.hypot(g);
+                e[i + 1] = r;
+                if r == 0.0 {
+                    d[i + 1] -= p;
+                    e[m] = 0.0;
+                    underflow = true;
+                    break;
+                }
+                s = f / r;
+                c = g / r;
+                g = d[i + 1] - p;
+                let r = (d[i] - g) * s + 2.0 * c * b;
+                p = s * r;
+                d[i + 1] = g + p;
+                g = c * r - b;
+            }
+            if !underflow {
+                d[l] -= p;
+                e[l] = g;
+                e[m] = 0.0;
+            }
+        }
+    }
+    d
+}
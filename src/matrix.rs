@@ -0,0 +1,288 @@
+use std::cmp::Ordering;
+use rug::{Complex, Float};
+
+use crate::classify::tolerance;
+
+pub enum EigenDecomposition {
+    /// Two distinct eigenvalues with their eigenvectors, larger modulus first.
+    Diagonalizable([(C, [C; 2]); 2]),
+    /// A repeated eigenvalue with a single eigenline: `(M - eigenvalue) generalized = eigenvector`.
+    Jordan { eigenvalue: C, eigenvector: [C; 2], generalized: [C; 2] },
+    /// The matrix is eigenvalue * I and every vector is an eigenvector.
+    Scalar(C),
+}
+
+pub use crate::scalar::M;
+
+pub type C = Complex;
+
+fn parse_float(input: &str, precision: u32) -> Result<Float, String> {
+    Float::parse(input.trim())
+        .map(|x| Float::with_val(precision, x))
+        .map_err(|_| format!("invalid number {}", input))
+}
+
+/// Parses a complex number directly at the given precision, without going through
+/// f64. It is written as a real number `x`, as `(x y)`, or as `x+yi`, `x-yi` or `yi`.
+pub fn parse_complex(input: &str, precision: u32) -> Result<C, String> {
+    let s = input.trim();
+    let Some(s) = s.strip_suffix('i') else {
+        return Complex::parse(s)
+            .map(|c| Complex::with_val(precision, c))
+            .map_err(|_| format!("invalid complex number {}", input));
+    };
+    // the imaginary part starts at the last sign that is not part of an exponent
+    let split = s.char_indices()
+        .rev()
+        .find(|&(k, c)| (c == '+' || c == '-') && k > 0 && !s[..k].ends_with(['e', 'E']))
+        .map(|(k, _)| k);
+    let (re, im) = match split {
+        Some(k) => (&s[..k], &s[k..]),
+        None => ("0", s),
+    };
+    let im = match im {
+        "" | "+" => "1",
+        "-" => "-1",
+        im => im.strip_prefix('+').unwrap_or(im),
+    };
+    let (re, im) = (parse_float(re, precision), parse_float(im, precision));
+    match (re, im) {
+        (Ok(re), Ok(im)) => Ok(Complex::with_val(precision, (re, im))),
+        _ => Err(format!("invalid complex number {}", input)),
+    }
+}
+
+/// A complex number given on the command line either as one value for
+/// `parse_complex` or as two values for the real and imaginary parts.
+pub fn parse_complex_args(values: &[String], precision: u32) -> Result<C, String> {
+    match values {
+        [z] => parse_complex(z, precision),
+        [re, im] => Ok(Complex::with_val(precision, (parse_float(re, precision)?, parse_float(im, precision)?))),
+        _ => Err("expected a complex number or its real and imaginary parts".to_string()),
+    }
+}
+
+/// Parses the entries [a, b, c, d] of a matrix in SL(2,C), rejecting it if the
+/// determinant is not 1 up to the tolerance.
+pub fn parse_sl2(entries: &[String], precision: u32) -> Result<M<C>, String> {
+    let entries: Vec<C> = entries.iter().map(|e| parse_complex(e, precision)).collect::<Result<_, _>>()?;
+    let m = M([entries[0].clone(), entries[1].clone(), entries[2].clone(), entries[3].clone()]);
+    let error = m.det() - 1u32;
+    if Float::with_val(precision, error.abs_ref()) > tolerance(precision) {
+        return Err(format!("the determinant is {}, not 1", m.det()));
+    }
+    Ok(m)
+}
+
+impl M<C> {
+    pub fn det(&self) -> C {
+        let [a, b, c, d] = &self.0;
+        a.clone() * d.clone() - b.clone() * c.clone()
+    }
+
+    pub fn trace(&self) -> C {
+        self.0[0].clone() + self.0[3].clone()
+    }
+
+    pub fn identity(precision: u32) -> Self {
+        let one: C = Complex::with_val(precision, 1);
+        let zero: C = Complex::with_val(precision, 0);
+        M([one.clone(), zero.clone(), zero, one])
+    }
+
+    /// The inverse, dividing by the determinant so that it is also right for matrices
+    /// whose determinant has drifted away from 1.
+    pub fn inv(self) -> Self {
+        let det = &self.det();
+        let [a, b, c, d] = self.0;
+        M([d/det, -b/det, -c/det, a/det])
+    }
+
+    /// The matrix divided by a square root of its determinant, which has determinant 1.
+    pub fn renormalize(self) -> Self {
+        let root = self.det().sqrt();
+        let [a, b, c, d] = self.0;
+        M([a/&root, b/&root, c/&root, d/&root])
+    }
+
+    /// The product of a nonempty list of matrices, multiplied as a balanced binary tree:
+    /// neighbors first, then neighboring pairs, and so on. This takes as many
+    /// multiplications as going left to right, but each entry goes through only about
+    /// log2(n) of them, so rounding errors build up over log n levels rather than n.
+    pub fn product(ms: Vec<Self>) -> Self {
+        let mut ms = ms;
+        while ms.len() > 1 {
+            let mut next = Vec::with_capacity(ms.len().div_ceil(2));
+            let mut ms_iter = ms.into_iter();
+            while let Some(x) = ms_iter.next() {
+                next.push(match ms_iter.next() {
+                    Some(y) => x.mul(y),
+                    None => x,
+                });
+            }
+            ms = next;
+        }
+        ms.pop().expect("product of no matrices")
+    }
+
+    /// The n-th power by repeated squaring, in about 2 log2(n) multiplications.
+    pub fn pow(self, n: usize) -> Self {
+        let precision = self.0[0].prec().0;
+        let mut result = M::identity(precision);
+        let mut square = self;
+        let mut n = n;
+        while n > 0 {
+            if n % 2 == 1 {
+                result = result.mul(square.clone());
+            }
+            n /= 2;
+            if n > 0 {
+                square = square.clone().mul(square);
+            }
+        }
+        result
+    }
+
+    /// Both eigenvalues with eigenvectors, or the Jordan data when the eigenvalue is
+    /// repeated (up to the tolerance of `classify::tolerance`).
+    pub fn eigen_decomposition(&self, precision: u32) -> EigenDecomposition {
+        let tol = tolerance(precision);
+        let small = |x: &C| Float::with_val(precision, x.abs_ref()) <= tol;
+        let [a, b, c, d] = &self.0;
+        let two = Complex::with_val(precision, 2);
+        let four = Complex::with_val(precision, 4);
+        let trace = a.clone() + d.clone();
+        let disc = trace.clone().square() - four * self.det();
+
+        if small(&disc) {
+            let lambda = trace / two;
+            if small(b) && small(c) && small(&(a.clone() - d.clone())) {
+                return EigenDecomposition::Scalar(lambda);
+            }
+            // N = M - lambda has N^2 = 0, so N w is an eigenvector for any w outside ker N
+            let zero = Complex::with_val(precision, 0);
+            let one = Complex::with_val(precision, 1);
+            let (generalized, eigenvector) =
+                if small(b) {
+                    ([one, zero], [a.clone() - lambda.clone(), c.clone()])
+                } else {
+                    ([zero, one], [b.clone(), d.clone() - lambda.clone()])
+                };
+            return EigenDecomposition::Jordan { eigenvalue: lambda, eigenvector, generalized };
+        }
+
+        let x = disc.sqrt();
+        let eigenvector = |lambda: &C| -> [C; 2] {
+            if !small(b) {
+                [b.clone(), lambda.clone() - a.clone()]
+            } else if !small(c) {
+                [lambda.clone() - d.clone(), c.clone()]
+            } else {
+                // diagonal
+                let zero = Complex::with_val(precision, 0);
+                let one = Complex::with_val(precision, 1);
+                if small(&(lambda.clone() - a.clone())) { [one, zero] } else { [zero, one] }
+            }
+        };
+        let lambda1 = (trace.clone() + x.clone()) / two.clone();
+        let lambda2 = (trace - x) / two;
+        let (lambda1, lambda2) = match lambda1.cmp_abs(&lambda2) {
+            Some(Ordering::Less) => (lambda2, lambda1),
+            _ => (lambda1, lambda2),
+        };
+        let v1 = eigenvector(&lambda1);
+        let v2 = eigenvector(&lambda2);
+        EigenDecomposition::Diagonalizable([(lambda1, v1), (lambda2, v2)])
+    }
+
+    /// A normal form N of the conjugacy class with a conjugator P of determinant 1 such
+    /// that P M P^-1 = N: diag(lambda1, lambda2), larger modulus first, when there are
+    /// two eigenvalues; [[lambda, 1], [0, lambda]] for a repeated eigenvalue with one
+    /// eigenline, such as a parabolic; and M itself, with P = I, when M is scalar.
+    pub fn normal_form(&self, precision: u32) -> (Self, Self) {
+        let zero = Complex::with_val(precision, 0);
+        let one = Complex::with_val(precision, 1);
+        // Q has the new basis as its columns, so that Q^-1 M Q = N
+        let (normal, q) = match self.eigen_decomposition(precision) {
+            EigenDecomposition::Diagonalizable([(l1, [x1, y1]), (l2, [x2, y2])]) =>
+                (M([l1, zero.clone(), zero, l2]), M([x1, x2, y1, y2])),
+            EigenDecomposition::Jordan { eigenvalue, eigenvector: [x1, y1], generalized: [x2, y2] } =>
+                (M([eigenvalue.clone(), one, zero, eigenvalue]), M([x1, x2, y1, y2])),
+            EigenDecomposition::Scalar(_) => return (self.clone(), M::identity(precision)),
+        };
+        // rescaling both columns of Q by the same factor keeps N
+        (normal, q.renormalize().inv())
+    }
+
+    pub fn dominant_eigenvector(&self, precision: u32) -> (C, [C; 2]) {
+        let two = Complex::with_val(precision, 2);
+        let four = Complex::with_val(precision, 4);
+        let [a, b, c, d] = &self.0;
+        // sqrt(a^2 + 4*b*c - 2*a*d + d^2)
+        // using the assumption that det = 1
+        // let x = (a.clone().square() + four*b.clone()*c.clone() - two.clone()*a.clone()*d.clone() + d.clone().square()).sqrt();
+        let x = ((a.clone() + d.clone()).square() - four.clone()).sqrt();
+        // lambda^2 - (a + d) lambda + (ad - bc) = 0
+        // lambda = ( (a+d) +/- sqrt((a + d)^2 - 4 (ad - bc)) ) / 2
+        let lambda1 = (a.clone() + d.clone() - x.clone()) / two.clone();
+        let lambda2 = (a.clone() + d.clone() + x.clone()) / two.clone();
+        // the moduli do not compare if one is NaN, which the NaN of the result shows
+        match lambda1.clone().cmp_abs(&lambda2) {
+            Some(Ordering::Less) =>
+                (lambda2.clone(), [b.clone(), lambda2 - a.clone()]),
+            _ => (lambda1.clone(), [lambda1 - d, c.clone()]),
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(self, other: Self) -> Self {
+        let [a1, b1, c1, d1] = self.0;
+        let [a2, b2, c2, d2] = other.0;
+        M([
+            a1.clone()*a2.clone() + b1.clone()*c2.clone(),
+            a1*b2.clone() + b1*d2.clone(),
+            c1.clone()*a2 + d1.clone()*c2,
+            c1*b2 + d1*d2,
+        ])
+    }
+
+    /// |det - 1|, the drift of the determinant away from SL(2,C) through rounding.
+    pub fn det_error(&self, precision: u32) -> Float {
+        Float::with_val(precision, (self.det() - 1u32).abs_ref())
+    }
+
+    /// The relative residual |M v - lambda v| / (|lambda| |v|) of the dominant eigenvalue
+    /// lambda, for the better of the eigenvectors (lambda - d, c) and (b, lambda - a).
+    /// Both vanish only for M = lambda I, which has residual 0.
+    pub fn eigenvector_residual(&self, precision: u32) -> Float {
+        let (lambda, _) = self.dominant_eigenvector(precision);
+        let [a, b, c, d] = &self.0;
+        let candidates = [[lambda.clone() - d, c.clone()], [b.clone(), lambda.clone() - a]];
+        let scale = Float::with_val(precision, lambda.abs_ref());
+        candidates.into_iter()
+            .filter_map(|[x, y]| {
+                let norm = Float::with_val(precision, x.abs_ref()).hypot(&Float::with_val(precision, y.abs_ref()));
+                if norm.is_zero() {
+                    return None;
+                }
+                let ux = a.clone() * &x + b.clone() * &y - lambda.clone() * &x;
+                let uy = c.clone() * &x + d.clone() * &y - lambda.clone() * &y;
+                let error = Float::with_val(precision, ux.abs_ref()).hypot(&Float::with_val(precision, uy.abs_ref()));
+                Some(error / (norm * &scale))
+            })
+            .min_by(|r, s| r.partial_cmp(s).unwrap_or(Ordering::Equal))
+            .unwrap_or_else(|| Float::with_val(precision, 0))
+    }
+
+    pub fn is_eigenvector(&self, v: [C; 2]) -> bool {
+        let [x, y] = v;
+        let epsilon = Complex::with_val(x.prec(), 0.000001);
+        let [a, b, c, d] = &self.0;
+        let ux = a.clone() * x.clone() + b.clone() * y.clone();
+        let uy = c.clone() * x.clone() + d.clone() * y.clone();
+
+        let c = ux / x;
+        // c * y should be close to uy
+        (c * y - uy).cmp_abs(&epsilon) == Some(Ordering::Less)
+    }
+}
@@ -0,0 +1,44 @@
+use rug::Complex;
+
+use crate::matrix::C;
+use crate::rho::Generators;
+use crate::scan::primitive_traces;
+
+/// 1 / (1 + e^l) for the complex length l of a curve with trace x = 2 cosh(l/2), as
+/// (1 - sqrt(1 - 4/x^2)) / 2 with the principal square root, which picks Re l > 0.
+pub fn term(trace: &C) -> C {
+    let precision = trace.prec().0;
+    let one = Complex::with_val(precision, 1);
+    let root = (one.clone() - Complex::with_val(precision, 4) / trace.clone().square()).sqrt();
+    (one - root) / 2u32
+}
+
+/// The sum of `term` over the slopes of height max(|p|, q) at most `height`.
+pub struct PartialSum {
+    pub height: u64,
+    pub curves: usize,
+    pub sum: C,
+}
+
+/// The partial sums of the McShane series over the simple closed curves, one per slope
+/// p/q from `primitive_traces`, for heights 1, 2, 4, ... up to `max_height` (at least 1).
+/// When tr[a, b] = -2 and the representation is quasi-Fuchsian they converge to 1/2
+/// (Bowditch's form of McShane's identity).
+pub fn partial_sums(gens: &Generators, max_height: u64) -> Vec<PartialSum> {
+    let mut heights: Vec<u64> = std::iter::successors(Some(1u64), |h| h.checked_mul(2))
+        .take_while(|&h| h < max_height)
+        .collect();
+    heights.push(max_height);
+    let mut sums: Vec<PartialSum> = heights.iter()
+        .map(|&height| PartialSum { height, curves: 0, sum: Complex::with_val(gens.precision, 0) })
+        .collect();
+    primitive_traces(gens, max_height, |q, trace| {
+        let height = q.numer().clone().abs().max(q.denom().clone()).to_u64().expect("heights fit in u64");
+        let t = term(trace);
+        for s in sums.iter_mut().filter(|s| height <= s.height) {
+            s.curves += 1;
+            s.sum += &t;
+        }
+    });
+    sums
+}
@@ -0,0 +1,149 @@
+use std::cmp::Ordering;
+use std::fmt;
+use rug::{Complex, Float};
+
+use crate::classify::{Classification, classify, tolerance};
+use crate::matrix::{C, M, parse_complex_args};
+
+/// A point of the Riemann sphere CP^1.
+#[derive(Clone)]
+pub enum Point {
+    Finite(C),
+    Infinity,
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Point::Finite(z) => write!(f, "{}", z),
+            Point::Infinity => write!(f, "inf"),
+        }
+    }
+}
+
+impl Point {
+    /// Parses a point given like `matrix::parse_complex_args`, or as `inf` (or as the
+    /// parts `inf inf`) for infinity.
+    pub fn parse(values: &[String], precision: u32) -> Result<Point, String> {
+        let infinite = |v: &String| v.trim() == "inf";
+        match values {
+            [v] if infinite(v) => Ok(Point::Infinity),
+            [x, y] if infinite(x) && infinite(y) => Ok(Point::Infinity),
+            _ => parse_complex_args(values, precision).map(Point::Finite),
+        }
+    }
+}
+
+/// The image of p under z -> (az + b)/(cz + d), computed at the precision of m. The
+/// pole -d/c goes to infinity and infinity goes to a/c (or stays if c = 0).
+pub fn apply(m: &M<C>, p: &Point) -> Point {
+    let [a, b, c, d] = &m.0;
+    match p {
+        Point::Infinity if c.is_zero() => Point::Infinity,
+        Point::Infinity => Point::Finite(a.clone() / c),
+        Point::Finite(z) => {
+            let denominator = c.clone() * z + d;
+            if denominator.is_zero() {
+                Point::Infinity
+            } else {
+                Point::Finite((a.clone() * z + b) / denominator)
+            }
+        }
+    }
+}
+
+/// The image of the point (z, t) of upper half-space H^3, at height t > 0 above z, under
+/// the isometry extending z -> (az + b)/(cz + d). With D = |cz + d|^2 + |c|^2 t^2 it is
+/// ((az + b) conj(cz + d) + a conj(c) t^2) / D at height t / D, for determinant 1.
+pub fn apply_h3(m: &M<C>, z: &C, t: &Float) -> (C, Float) {
+    let [a, b, c, d] = &m.0;
+    let precision = t.prec();
+    let t2 = Float::with_val(precision, t.square_ref());
+    let denominator = c.clone() * z + d;
+    let scale = Float::with_val(precision, denominator.norm_ref()) + Float::with_val(precision, c.norm_ref()) * &t2;
+    let numerator = (a.clone() * z + b) * denominator.conj() + a.clone() * c.clone().conj() * &t2;
+    (numerator / &scale, Float::with_val(precision, t / &scale))
+}
+
+/// The fixed points of z -> (az + b)/(cz + d), i.e. the eigenlines [z : 1] of the
+/// matrix, ordered so that the attracting one (larger |eigenvalue|) comes first.
+/// For parabolic matrices the two points coincide.
+pub fn fixed_points(m: &M<C>, precision: u32) -> [Point; 2] {
+    let [a, b, c, d] = &m.0;
+    let four = Complex::with_val(precision, 4);
+
+    if c.is_zero() {
+        // infinity is an eigenline with eigenvalue a, the other one has eigenvalue d
+        let diff = d.clone() - a.clone();
+        let other = if diff.is_zero() { Point::Infinity } else { Point::Finite(b.clone() / diff) };
+        return match a.cmp_abs(d) {
+            Some(Ordering::Less) => [other, Point::Infinity],
+            _ => [Point::Infinity, other],
+        };
+    }
+
+    // z = (a - d +/- s) / 2c with eigenvalue cz + d = (a + d +/- s) / 2
+    let s = ((a.clone() + d.clone()).square() - four).sqrt();
+    let two_c = c.clone() * 2u32;
+    let plus = Point::Finite((a.clone() - d.clone() + s.clone()) / two_c.clone());
+    let minus = Point::Finite((a.clone() - d.clone() - s.clone()) / two_c);
+    let lambda_plus = a.clone() + d.clone() + s.clone();
+    let lambda_minus = a.clone() + d.clone() - s;
+    match lambda_plus.cmp_abs(&lambda_minus) {
+        Some(Ordering::Less) => [minus, plus],
+        _ => [plus, minus],
+    }
+}
+
+/// The chordal distance between two points of CP^1, which stays finite at infinity.
+pub fn chordal_distance(p: &Point, q: &Point, precision: u32) -> Float {
+    let scale = |z: &C| (Float::with_val(precision, z.norm_ref()) + 1u32).sqrt();
+    match (p, q) {
+        (Point::Infinity, Point::Infinity) => Float::with_val(precision, 0),
+        (Point::Finite(z), Point::Infinity) | (Point::Infinity, Point::Finite(z)) => 2u32 / scale(z),
+        (Point::Finite(z), Point::Finite(w)) => {
+            let diff = Float::with_val(precision, (z.clone() - w).abs_ref());
+            diff * 2u32 / (scale(z) * scale(w))
+        }
+    }
+}
+
+/// The attracting fixed point of m, the first of `fixed_points`, refined by Newton's
+/// method on c z^2 + (d - a) z - b = 0 to undo the cancellation in the closed form.
+/// When m is not loxodromic (up to the tolerance) the two fixed points are about
+/// equally attracting and their order means little, so the one nearer to `seeds`,
+/// such as the fixed points of neighboring words, is taken instead.
+pub fn attracting_fixed_point(m: &M<C>, precision: u32, seeds: &[&Point]) -> Point {
+    let [first, second] = fixed_points(m, precision);
+    let loxodromic = matches!(classify(m, precision), Classification::Loxodromic { .. });
+    let point = if loxodromic || seeds.is_empty() {
+        first
+    } else {
+        let distance = |p: &Point| seeds.iter()
+            .map(|seed| chordal_distance(p, seed, precision))
+            .fold(None, |min: Option<Float>, d| Some(min.map_or(d.clone(), |m| m.min(&d))));
+        if distance(&second) < distance(&first) { second } else { first }
+    };
+    let Point::Finite(mut z) = point else {
+        return point;
+    };
+    let [a, b, c, d] = &m.0;
+    if c.is_zero() {
+        return Point::Finite(z);
+    }
+    let tol = tolerance(precision);
+    for _ in 0..8 {
+        let slope = c.clone() * z.clone() * 2u32 + d - a;
+        if slope.is_zero() {
+            break;
+        }
+        let value = (c.clone() * z.clone() + d - a) * z.clone() - b;
+        let step = value / slope;
+        let size = Float::with_val(precision, z.abs_ref()).max(&Float::with_val(precision, 1));
+        z -= &step;
+        if Float::with_val(precision, step.abs_ref()) <= tol.clone() * size {
+            break;
+        }
+    }
+    Point::Finite(z)
+}
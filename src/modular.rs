@@ -0,0 +1,88 @@
+use rug::{Complex, Float};
+use rug::float::Constant;
+use rug::ops::Pow;
+
+use crate::classify::tolerance;
+use crate::matrix::{C, M};
+
+/// Whether every entry is an integer up to the tolerance, so that the matrix is in
+/// SL(2,Z) if its determinant is 1.
+pub fn is_integral(m: &M<C>) -> bool {
+    let precision = m.0[0].prec().0;
+    let tol = tolerance(precision);
+    m.0.iter().all(|x| {
+        let re = x.real();
+        let fraction = Float::with_val(precision, re - re.clone().round()).abs();
+        fraction <= tol.clone() * re.clone().abs().max(&Float::with_val(precision, 1))
+            && x.imag().clone().abs() <= tol
+    })
+}
+
+/// The point of the standard fundamental domain |Re tau| <= 1/2, |tau| >= 1 in the
+/// SL(2,Z)-orbit of tau (Im tau > 0), by alternately translating and inverting.
+pub fn reduce(tau: &C) -> C {
+    let mut tau = tau.clone();
+    loop {
+        let shift = tau.real().clone().round();
+        tau -= shift;
+        if Float::with_val(tau.prec().0, tau.norm_ref()) >= 1 {
+            return tau;
+        }
+        tau = -tau.recip();
+    }
+}
+
+/// 2^-precision, where the series are cut off: they converge geometrically, so they are
+/// summed to the last bit rather than to the tolerance.
+fn epsilon(precision: u32) -> Float {
+    Float::with_val(precision, Float::u_exp(1, -(precision as i32)))
+}
+
+/// The Lambert series sum n^k q^n / (1 - q^n) for n >= 1, that is sum sigma_k(n) q^n,
+/// for |q| < 1.
+fn lambert(q: &C, k: u32) -> C {
+    let precision = q.prec().0;
+    let tol = epsilon(precision);
+    let mut sum = Complex::with_val(precision, 0);
+    let mut q_n = q.clone();
+    for n in 1u32.. {
+        let term = q_n.clone() * Float::with_val(precision, n).pow(k) / (1u32 - q_n.clone());
+        sum += &term;
+        if Float::with_val(precision, term.abs_ref()) <= tol.clone() * Float::with_val(precision, sum.abs_ref()) {
+            break;
+        }
+        q_n *= q;
+    }
+    sum
+}
+
+/// The discriminant Delta = q prod (1 - q^n)^24 for n >= 1, for |q| < 1. The product keeps
+/// its relative precision as q -> 0, unlike (E4^3 - E6^2) / 1728.
+fn discriminant(q: &C) -> C {
+    let precision = q.prec().0;
+    let tol = epsilon(precision);
+    let mut product = Complex::with_val(precision, 1);
+    let mut q_n = q.clone();
+    while Float::with_val(precision, q_n.abs_ref()) > tol {
+        product *= 1u32 - q_n.clone();
+        q_n *= q;
+    }
+    product.pow(24u32) * q
+}
+
+/// Klein's j-invariant E4^3 / Delta, from the q-expansion E4 = 1 + 240 sum sigma_3(n) q^n
+/// of the Eisenstein series and the product for the discriminant, with q = e^(2 pi i tau),
+/// at the precision of tau. Both are evaluated at the reduction of tau to the fundamental
+/// domain, where |q| <= e^(-pi sqrt 3) < 0.005, so that each term adds about 7.6 bits.
+/// `None` unless Im tau > 0.
+pub fn j_invariant(tau: &C) -> Option<C> {
+    if *tau.imag() <= 0 {
+        return None;
+    }
+    let precision = tau.prec().0;
+    let tau = reduce(tau);
+    let two_pi_i = Complex::with_val(precision, (0, Float::with_val(precision, Constant::Pi) * 2u32));
+    let q = (two_pi_i * tau).exp();
+    let e4 = lambert(&q, 3) * 240u32 + 1u32;
+    Some(e4.clone().square() * &e4 / discriminant(&q))
+}
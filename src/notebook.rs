@@ -0,0 +1,128 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use rug::Float;
+
+use crate::classify::{classify, tolerance};
+use crate::display::{Notation, Style};
+use crate::matrix::{C, M};
+use crate::rho::Generators;
+
+/// Significant digits shown by the displays, see `set_digits`.
+static DIGITS: AtomicUsize = AtomicUsize::new(8);
+
+/// Sets the significant digits that matrices and traces are rounded to when a notebook
+/// shows them (8 to begin with). The values themselves keep their precision.
+pub fn set_digits(digits: usize) {
+    DIGITS.store(digits.max(1), Ordering::Relaxed);
+}
+
+fn style() -> Style {
+    Style { digits: Some(DIGITS.load(Ordering::Relaxed)), notation: Notation::Auto, polar: false }
+}
+
+/// A real number in LaTeX, rounded as set by `set_digits`.
+pub fn latex_real(x: &Float) -> String {
+    let s = style().number(x);
+    match s.split_once('e') {
+        Some((mantissa, exponent)) => format!("{} \\times 10^{{{}}}", mantissa, exponent),
+        None => s,
+    }
+}
+
+/// A complex number in LaTeX as a + b i, leaving out a part that is zero up to the
+/// tolerance of `classify::tolerance` relative to |z|, such as the rounding error in the
+/// imaginary part of a real trace.
+pub fn latex_complex(z: &C) -> String {
+    let precision = z.prec().0;
+    let scale = Float::with_val(precision, z.abs_ref()).max(&Float::with_val(precision, 1));
+    let negligible = tolerance(precision) * scale;
+    let part = |x: &Float| if x.clone().abs() <= negligible { "0".to_string() } else { latex_real(x) };
+    let (re, im) = (part(z.real()), part(z.imag()));
+    let (negative, magnitude) = match im.strip_prefix('-') {
+        Some(im) => (true, im.to_string()),
+        None => (false, im.clone()),
+    };
+    let im = if magnitude == "1" { String::new() } else { format!("{}\\,", magnitude) };
+    match (re.as_str(), magnitude.as_str(), negative) {
+        (_, "0", _) => re,
+        ("0", _, false) => format!("{}i", im),
+        ("0", _, true) => format!("-{}i", im),
+        (_, _, false) => format!("{} + {}i", re, im),
+        (_, _, true) => format!("{} - {}i", re, im),
+    }
+}
+
+/// A matrix in LaTeX, as a pmatrix.
+pub fn latex_matrix(m: &M<C>) -> String {
+    let [a, b, c, d] = &m.0;
+    format!("\\begin{{pmatrix}} {} & {} \\\\ {} & {} \\end{{pmatrix}}",
+            latex_complex(a), latex_complex(b), latex_complex(c), latex_complex(d))
+}
+
+/// Hands `html` to evcxr, the Rust kernel for Jupyter, which shows it as the output of
+/// the cell. Jupyter typesets the LaTeX between $ signs in it.
+fn show(html: &str) {
+    println!("EVCXR_BEGIN_CONTENT text/html\n{}\nEVCXR_END_CONTENT", html);
+}
+
+/// LaTeX that evcxr shows typeset, for a value without a display of its own:
+/// `Math::complex(&m.trace())` as the last expression of a cell.
+pub struct Math(pub String);
+
+impl Math {
+    pub fn complex(z: &C) -> Self {
+        Math(latex_complex(z))
+    }
+
+    pub fn evcxr_display(&self) {
+        show(&format!("$${}$$", self.0));
+    }
+}
+
+impl M<C> {
+    /// Shows the matrix and its trace in a notebook; evcxr calls this for a matrix that
+    /// ends a cell.
+    pub fn evcxr_display(&self) {
+        show(&format!("$${} \\qquad \\operatorname{{tr}} = {}$$", latex_matrix(self), latex_complex(&self.trace())));
+    }
+}
+
+impl Generators {
+    /// Shows a table of the generators with their traces in a notebook.
+    pub fn evcxr_display(&self) {
+        let mut html = String::from("<table>\n<tr><th>generator</th><th>matrix</th><th>trace</th></tr>\n");
+        for c in self.letters().into_iter().filter(|c| c.is_ascii_lowercase()) {
+            let m = self.letter(c);
+            writeln!(html, "<tr><td>\\({}\\)</td><td>\\({}\\)</td><td>\\({}\\)</td></tr>",
+                     c, latex_matrix(m), latex_complex(&m.trace())).unwrap();
+        }
+        html.push_str("</table>");
+        show(&html);
+    }
+}
+
+/// Words evaluated in a representation, shown in a notebook as a table of their traces and
+/// classifications: `Table::new(&gens, &["ab", "abAB"])` as the last expression of a cell.
+pub struct Table {
+    pub precision: u32,
+    pub rows: Vec<(String, M<C>)>,
+}
+
+impl Table {
+    pub fn new(gens: &Generators, words: &[&str]) -> Self {
+        let rows = words.iter().map(|w| (w.to_string(), gens.eval(w))).collect();
+        Table { precision: gens.precision, rows }
+    }
+
+    pub fn evcxr_display(&self) {
+        let mut html = String::from("<table>\n<tr><th>word</th><th>trace</th><th>type</th><th>matrix</th></tr>\n");
+        for (word, m) in &self.rows {
+            // the empty word is the identity
+            let word = if word.is_empty() { "1" } else { word };
+            writeln!(html, "<tr><td><code>{}</code></td><td>\\({}\\)</td><td>{}</td><td>\\({}\\)</td></tr>",
+                     word, latex_complex(&m.trace()), classify(m, self.precision), latex_matrix(m)).unwrap();
+        }
+        html.push_str("</table>");
+        show(&html);
+    }
+}
@@ -0,0 +1,89 @@
+use std::io::{self, Write};
+use rand::Rng;
+use rug::Float;
+
+use crate::budget::Budget;
+use crate::context::RunContext;
+use crate::matrix::{C, M};
+use crate::mobius::{self, Point};
+use crate::rho::Generators;
+use crate::shard::Shard;
+use crate::sweep::for_each_reduced_word;
+use crate::word::random_reduced_word;
+
+/// A point that the group acts on: a point of CP^1, or a point (z, t) of upper
+/// half-space H^3 at height t > 0 above z.
+#[derive(Clone)]
+pub enum Basepoint {
+    Sphere(Point),
+    Space(C, Float),
+}
+
+impl Basepoint {
+    /// The image of the point under the matrix.
+    pub fn image(&self, m: &M<C>) -> Basepoint {
+        match self {
+            Basepoint::Sphere(p) => Basepoint::Sphere(mobius::apply(m, p)),
+            Basepoint::Space(z, t) => {
+                let (z, t) = mobius::apply_h3(m, z, t);
+                Basepoint::Space(z, t)
+            }
+        }
+    }
+
+    /// The point over which it lies in the affine chart C, if any: the point itself on
+    /// CP^1, or the point below it for H^3.
+    pub fn shadow(&self) -> Option<&C> {
+        match self {
+            Basepoint::Sphere(Point::Finite(z)) | Basepoint::Space(z, _) => Some(z),
+            Basepoint::Sphere(Point::Infinity) => None,
+        }
+    }
+}
+
+/// Calls `f` with each reduced word of length at most `depth`, starting with the
+/// identity as `1`, and the image of `basepoint` under it. Only the words in `shard`
+/// are used, and the enumeration stops when the budget runs out.
+pub fn for_each_orbit_point(gens: &Generators, basepoint: &Basepoint, depth: usize, shard: Shard,
+                            budget: &mut Budget, mut f: impl FnMut(&str, &Basepoint)) {
+    if shard.contains(0) {
+        f("1", basepoint);
+    }
+    let mut position = 1;
+    for_each_reduced_word(gens, depth, budget, |word, m| {
+        position += 1;
+        if shard.contains(position - 1) {
+            f(word, &basepoint.image(m));
+        }
+    });
+}
+
+/// Calls `f` with `samples` random reduced words of length `length` and the image of
+/// `basepoint` under each.
+pub fn for_each_random_orbit_point<R: Rng>(gens: &Generators, basepoint: &Basepoint, length: usize, samples: usize,
+                                           rng: &mut R, mut f: impl FnMut(&str, &Basepoint)) {
+    for _ in 0..samples {
+        let word = random_reduced_word(rng, length);
+        let image = basepoint.image(&gens.eval(&word));
+        f(if word.is_empty() { "1" } else { &word }, &image);
+    }
+}
+
+/// The header of the CSV file of an orbit, with columns x,y,word on CP^1 and x,y,t,word
+/// in H^3.
+pub fn write_csv_header<W: Write>(out: &mut W, basepoint: &Basepoint, context: &RunContext) -> io::Result<()> {
+    context.write_comments(out)?;
+    match basepoint {
+        Basepoint::Sphere(_) => writeln!(out, "x,y,word"),
+        Basepoint::Space(..) => writeln!(out, "x,y,t,word"),
+    }
+}
+
+/// A row of the CSV file; the point at infinity of CP^1 is written as inf,inf.
+pub fn write_csv_row<W: Write>(out: &mut W, word: &str, point: &Basepoint) -> io::Result<()> {
+    match point {
+        Basepoint::Sphere(Point::Finite(z)) => writeln!(out, "{},{},{}", z.real(), z.imag(), word),
+        Basepoint::Sphere(Point::Infinity) => writeln!(out, "inf,inf,{}", word),
+        Basepoint::Space(z, t) => writeln!(out, "{},{},{},{}", z.real(), z.imag(), t, word),
+    }
+}
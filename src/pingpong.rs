@@ -0,0 +1,146 @@
+use std::fmt;
+use rug::Float;
+
+use crate::classify::tolerance;
+use crate::matrix::{C, M};
+use crate::rho::Generators;
+use crate::word::{LETTERS, inverse_letter};
+
+/// A closed disk in the complex plane.
+#[derive(Clone)]
+pub struct Disk {
+    pub center: C,
+    pub radius: Float,
+}
+
+impl fmt::Display for Disk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.center, self.radius)
+    }
+}
+
+/// The image of the boundary of D_{x^-1} under the generator x, and how far it
+/// stays inside D_x (positive means strictly inside).
+pub struct Inclusion {
+    pub letter: char,
+    pub image: Disk,
+    pub margin: Float,
+}
+
+pub enum PingPongFailure {
+    /// The disks of the two letters intersect.
+    Overlap(char, char),
+    /// The generator does not send any point of D_{x^-1} to infinity, so it cannot
+    /// map the outside of D_{x^-1} into a bounded disk.
+    PoleOutside(char),
+    /// The image of the outside of D_{x^-1} is not contained in D_x.
+    NotNested(Inclusion),
+    /// Ball arithmetic could not separate a quantity for the generator from zero or
+    /// from a branch cut; more precision may help.
+    Enclosure(char),
+}
+
+impl fmt::Display for PingPongFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PingPongFailure::Overlap(x, y) => write!(f, "the disks for {} and {} overlap", x, y),
+            PingPongFailure::PoleOutside(x) =>
+                write!(f, "the pole of {} is not inside the disk for {}", x, inverse_letter(*x)),
+            PingPongFailure::NotNested(inclusion) =>
+                write!(f, "{} does not map the outside of the disk for {} into the disk for {} (margin {})",
+                    inclusion.letter, inverse_letter(inclusion.letter), inclusion.letter, inclusion.margin),
+            PingPongFailure::Enclosure(x) =>
+                write!(f, "ball arithmetic could not bound the image under {}, increase precision", x),
+        }
+    }
+}
+
+fn abs(x: &C) -> Float {
+    Float::with_val(x.prec().0, x.abs_ref())
+}
+
+fn apply(g: &M<C>, z: C) -> C {
+    let [a, b, c, d] = &g.0;
+    (a.clone() * z.clone() + b.clone()) / (c.clone() * z + d.clone())
+}
+
+/// The isometric disks |cz + d| <= 1 of the generators, indexed like `LETTERS`:
+/// x maps the outside of the isometric disk of x onto the inside of that of x^-1,
+/// so D_x is taken to be the isometric disk of x^-1. Returns `None` if a generator
+/// fixes infinity.
+pub fn isometric_disks(gens: &Generators) -> Option<[Disk; 4]> {
+    let disk = |g: &M<C>| -> Option<Disk> {
+        let [_, _, c, d] = &g.0;
+        if c.is_zero() {
+            return None;
+        }
+        Some(Disk { center: -d.clone() / c.clone(), radius: abs(c).recip() })
+    };
+    Some([
+        disk(&gens.a_inv)?,
+        disk(&gens.b_inv)?,
+        disk(&gens.a)?,
+        disk(&gens.b)?,
+    ])
+}
+
+/// The image under g of the complement of `disk`, provided the pole of g lies
+/// inside the disk so that the image is again a bounded disk.
+fn image_of_outside(g: &M<C>, disk: &Disk) -> Option<Disk> {
+    let [a, _, c, d] = &g.0;
+    if c.is_zero() {
+        return None;
+    }
+    let pole = -d.clone() / c.clone();
+    let offset = pole - disk.center.clone();
+    if abs(&offset) >= disk.radius {
+        return None;
+    }
+    // g sends the pole to infinity, so it sends the reflection of the pole in the
+    // boundary circle to the center of the image circle
+    let center =
+        if offset.is_zero() {
+            a.clone() / c.clone()
+        } else {
+            let reflection = disk.center.clone() + disk.radius.clone().square() / offset.conj();
+            apply(g, reflection)
+        };
+    let boundary_point = disk.center.clone() + disk.radius.clone();
+    let radius = abs(&(apply(g, boundary_point) - center.clone()));
+    Some(Disk { center, radius })
+}
+
+/// Checks the hypotheses of the ping-pong lemma for disks D_a, D_b, D_A, D_B
+/// (indexed like `LETTERS`): the disks are pairwise disjoint, and each generator x
+/// maps the outside of D_{x^-1} into D_x. If they hold the group is a Schottky
+/// group, in particular free and discrete.
+///
+/// This is a floating point check: nesting is accepted up to `classify::tolerance`,
+/// since images of isometric disks touch the target disks from the inside.
+pub fn verify(gens: &Generators, disks: &[Disk; 4]) -> Result<Vec<Inclusion>, PingPongFailure> {
+    let tol = tolerance(gens.precision);
+    for i in 0..4 {
+        for j in (i + 1)..4 {
+            let distance = abs(&(disks[i].center.clone() - disks[j].center.clone()));
+            if distance <= disks[i].radius.clone() + &disks[j].radius {
+                return Err(PingPongFailure::Overlap(LETTERS[i], LETTERS[j]));
+            }
+        }
+    }
+
+    let index = |x: char| LETTERS.iter().position(|&l| l == x).unwrap();
+    let mut inclusions = vec![];
+    for (i, &x) in LETTERS.iter().enumerate() {
+        let source = &disks[index(inverse_letter(x))];
+        let target = &disks[i];
+        let image = image_of_outside(gens.letter(x), source).ok_or(PingPongFailure::PoleOutside(x))?;
+        let distance = abs(&(image.center.clone() - target.center.clone()));
+        let margin = target.radius.clone() - distance - &image.radius;
+        let inclusion = Inclusion { letter: x, image, margin };
+        if inclusion.margin < -tol.clone() {
+            return Err(PingPongFailure::NotNested(inclusion));
+        }
+        inclusions.push(inclusion);
+    }
+    Ok(inclusions)
+}
@@ -0,0 +1,58 @@
+use rug::Float;
+
+use crate::matrix::C;
+use crate::rho::Generators;
+use crate::stern_brocot::{ExtendedRational, for_each_farey};
+
+/// The slope that minimizes a measure of its trace among those of height at most `height`.
+pub struct SlopeCandidate {
+    pub height: u64,
+    pub slope: ExtendedRational,
+    pub trace: C,
+    pub value: Float,
+}
+
+/// For h = 1, 2, 4, ... up to `max_height`, the slope p/q of height max(p, q) at most h
+/// whose Stern-Brocot word minimizes `measure(slope, trace)`, found in one walk down the
+/// Farey tree. Slopes where the measure is `None` are left out, and so are heights at
+/// which no slope has a measure.
+pub fn best_slopes(gens: &Generators, max_height: u64,
+                   measure: impl Fn(&ExtendedRational, &C) -> Option<Float>) -> Vec<SlopeCandidate> {
+    let mut heights: Vec<u64> = std::iter::successors(Some(1u64), |h| h.checked_mul(2))
+        .take_while(|&h| h < max_height)
+        .collect();
+    heights.push(max_height);
+    let mut best: Vec<Option<SlopeCandidate>> = heights.iter().map(|_| None).collect();
+    let ab = gens.a.clone().mul(gens.b.clone());
+    for_each_farey(max_height, gens.a.trace(), gens.b.trace(), ab.trace(),
+                   |u, v, w| u.clone() * v - w,
+                   |slope, trace| {
+        let Some(value) = measure(slope, trace) else {
+            return;
+        };
+        let height = slope.numer().max(slope.denom()).to_u64().expect("heights fit in u64");
+        for (h, best) in heights.iter().zip(&mut best) {
+            if height <= *h && best.as_ref().map_or(true, |b| value < b.value) {
+                *best = Some(SlopeCandidate { height: *h, slope: slope.clone(), trace: trace.clone(), value: value.clone() });
+            }
+        }
+    });
+    best.into_iter().flatten().collect()
+}
+
+/// On the pleating ray of slope p/q in the Maskit or Riley slice, the trace of the
+/// Stern-Brocot word of p/q is real, and near a ray of irrational slope the traces of
+/// its convergents are close to real. So the candidates are the slopes minimizing
+/// |Im tr| / |tr|, the sine of the angle between the trace and the real axis. A
+/// candidate that stays the same as the height grows suggests a rational lamination,
+/// and one that keeps moving approximates an irrational one.
+pub fn pleating_candidates(gens: &Generators, max_height: u64) -> Vec<SlopeCandidate> {
+    let precision = gens.precision;
+    best_slopes(gens, max_height, |_, trace| {
+        let size = Float::with_val(precision, trace.abs_ref());
+        if size.is_zero() {
+            return None;
+        }
+        Some(Float::with_val(precision, trace.imag().abs_ref()) / size)
+    })
+}
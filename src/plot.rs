@@ -0,0 +1,100 @@
+use std::str::FromStr;
+
+/// How --plot draws a sequence in the terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Plot {
+    /// One line of block characters, one per column, from ▁ for the smallest value to █
+    /// for the largest.
+    Sparkline,
+    /// Rows of `*` over a labelled axis, in plain ASCII.
+    Ascii,
+}
+
+impl FromStr for Plot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "sparkline" => Ok(Plot::Sparkline),
+            "ascii" => Ok(Plot::Ascii),
+            _ => Err(format!("unknown plot {} (expected sparkline or ascii)", s)),
+        }
+    }
+}
+
+const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The values in at most `width` columns: each column is the mean of a run of about
+/// equally many consecutive values. Values that are not finite (an overflow, or the log of
+/// a zero) are left out of the means, and a column without any is `None`.
+pub fn columns(values: &[f64], width: usize) -> Vec<Option<f64>> {
+    let width = width.max(1).min(values.len());
+    (0..width)
+        .map(|i| {
+            let run = &values[i * values.len() / width..(i + 1) * values.len() / width];
+            let finite: Vec<f64> = run.iter().copied().filter(|x| x.is_finite()).collect();
+            (!finite.is_empty()).then(|| finite.iter().sum::<f64>() / finite.len() as f64)
+        })
+        .collect()
+}
+
+/// The smallest and largest of the columns, if any is finite.
+pub fn range(columns: &[Option<f64>]) -> Option<(f64, f64)> {
+    columns.iter().flatten().fold(None, |range, &x| match range {
+        None => Some((x, x)),
+        Some((lo, hi)) => Some((lo.min(x), hi.max(x))),
+    })
+}
+
+/// The level of x among `levels` equal parts of [lo, hi], the middle one if they are equal.
+fn level(x: f64, (lo, hi): (f64, f64), levels: usize) -> usize {
+    if hi > lo {
+        (((x - lo) / (hi - lo) * levels as f64) as usize).min(levels - 1)
+    } else {
+        levels / 2
+    }
+}
+
+/// The columns of the values as a sparkline, with a space for a column without a value.
+pub fn sparkline(columns: &[Option<f64>]) -> String {
+    let Some(range) = range(columns) else {
+        return " ".repeat(columns.len());
+    };
+    columns.iter().map(|x| x.map_or(' ', |x| LEVELS[level(x, range, LEVELS.len())])).collect()
+}
+
+/// A number short enough for an axis label.
+fn label(x: f64) -> String {
+    if x == 0.0 || (1e-3..1e5).contains(&x.abs()) {
+        format!("{:.4}", x)
+    } else {
+        format!("{:.3e}", x)
+    }
+}
+
+/// The columns as a plot `height` rows high, the largest value labelled on the top row and
+/// the smallest on the bottom one, over an axis numbered from 0 to `last`.
+pub fn ascii(columns: &[Option<f64>], last: usize, height: usize) -> Vec<String> {
+    let height = height.max(2);
+    let range = range(columns).unwrap_or((0.0, 0.0));
+    let labels = (label(range.1), label(range.0));
+    let margin = labels.0.len().max(labels.1.len());
+    let mut lines: Vec<String> = (0..height).rev()
+        .map(|row| {
+            let label = match row {
+                _ if row == height - 1 => &labels.0,
+                0 => &labels.1,
+                _ => "",
+            };
+            let marks: String = columns.iter()
+                .map(|x| if x.is_some_and(|x| level(x, range, height) == row) { '*' } else { ' ' })
+                .collect();
+            format!("{:>margin$} |{}", label, marks.trim_end())
+        })
+        .collect();
+    lines.push(format!("{:>margin$} +{}", "", "-".repeat(columns.len())));
+    let last = last.to_string();
+    let gap = (columns.len() + 1).saturating_sub(last.len()).max(1);
+    lines.push(format!("{:>margin$}  0{:>gap$}", "", last));
+    lines
+}
@@ -0,0 +1,156 @@
+use std::cmp::Ordering;
+use std::fmt;
+use rug::{Complex, Float};
+
+use crate::classify::tolerance;
+use crate::matrix::{C, M};
+use crate::rho::Generators;
+
+/// Generators conjugate to the representation with real entries.
+pub struct RealForm {
+    /// P in SL(2,C) with P g P^-1 real for both generators g.
+    pub conjugator: M<C>,
+    /// P a P^-1 and P b P^-1, with the imaginary parts left by rounding dropped.
+    pub a: M<Float>,
+    pub b: M<Float>,
+    /// The largest imaginary part dropped from an entry.
+    pub residual: Float,
+}
+
+pub enum NoRealForm {
+    /// Some trace of a, b or ab is not real; the residual is the largest |Im tr|.
+    NonRealTraces(Float),
+    /// The matrices S with conj(g) S = S g for both generators form more than a line,
+    /// which happens only for reducible pairs (tr[a, b] = 2); the traces do not
+    /// determine their conjugacy class.
+    Reducible,
+    /// The traces are real but the representation is conjugate into SU(2) rather than
+    /// SL(2,R), which needs tr a, tr b and tr ab in [-2, 2] and tr[a, b] < 2.
+    Unitary,
+}
+
+impl fmt::Display for NoRealForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NoRealForm::NonRealTraces(residual) => write!(f, "the traces are not real (residual {})", residual),
+            NoRealForm::Reducible => write!(f, "the representation is reducible"),
+            NoRealForm::Unitary => write!(f, "the representation is conjugate into SU(2), not SL(2,R)"),
+        }
+    }
+}
+
+fn abs(x: &C) -> Float {
+    Float::with_val(x.prec().0, x.abs_ref())
+}
+
+fn conj(m: &M<C>) -> M<C> {
+    M(m.0.clone().map(|x| x.conj()))
+}
+
+/// A nonzero S with conj(g) S = S g for both generators g, unique up to a scalar when
+/// the pair is irreducible, found by Gaussian elimination with full pivoting on the
+/// eight linear equations in the entries of S. `None` if the third pivot vanishes,
+/// when the solutions form more than a line.
+fn intertwiner(gens: &Generators) -> Option<M<C>> {
+    let precision = gens.precision;
+    let zero = Complex::with_val(precision, 0);
+    let mut rows: Vec<[C; 4]> = Vec::with_capacity(8);
+    for g in [&gens.a, &gens.b] {
+        let g_bar = conj(g);
+        for (i, j) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+            // entry (i, j) of conj(g) S - S g, with S_kl the unknown 2k + l
+            let mut row: [C; 4] = [zero.clone(), zero.clone(), zero.clone(), zero.clone()];
+            for k in 0..2 {
+                row[2 * k + j] += &g_bar.0[2 * i + k];
+                row[2 * i + k] -= &g.0[2 * k + j];
+            }
+            rows.push(row);
+        }
+    }
+    let scale = rows.iter().flatten().map(abs).fold(Float::with_val(precision, 0), |m, x| m.max(&x));
+    let mut columns = [0, 1, 2, 3];
+    for step in 0..3 {
+        let (r, c) = (step..rows.len())
+            .flat_map(|r| (step..4).map(move |c| (r, c)))
+            .max_by(|&(r1, c1), &(r2, c2)| {
+                abs(&rows[r1][c1]).partial_cmp(&abs(&rows[r2][c2])).unwrap_or(Ordering::Equal)
+            })
+            .expect("rows remain");
+        if abs(&rows[r][c]) <= tolerance(precision) * scale.clone() {
+            return None;
+        }
+        rows.swap(step, r);
+        columns.swap(step, c);
+        for row in rows.iter_mut() {
+            row.swap(step, c);
+        }
+        let (done, rest) = rows.split_at_mut(step + 1);
+        let pivot = &done[step];
+        for row in rest {
+            let factor = row[step].clone() / &pivot[step];
+            for (x, y) in row.iter_mut().zip(pivot).skip(step) {
+                *x -= factor.clone() * y;
+            }
+        }
+    }
+    // the last unknown is free; back substitute with it set to 1
+    let mut solution: [C; 4] = [zero.clone(), zero.clone(), zero, Complex::with_val(precision, 1)];
+    for step in (0..3).rev() {
+        let mut sum = Complex::with_val(precision, 0);
+        for (x, y) in rows[step].iter().zip(&solution).skip(step + 1) {
+            sum += x.clone() * y;
+        }
+        solution[step] = -sum / &rows[step][step];
+    }
+    let mut s: [C; 4] = solution.clone();
+    for (k, &column) in columns.iter().enumerate() {
+        s[column] = solution[k].clone();
+    }
+    Some(M(s))
+}
+
+/// Conjugates the representation into SL(2,R) when it is conjugate to a real one. Its
+/// traces are then real, and an irreducible one is conjugate to its complex conjugate
+/// by some S, conj(g) = S g S^-1, with conj(S) S = lambda I for a real lambda. When
+/// lambda > 0 the scaling with lambda = 1 gives P = X + conj(X) S with conj(P) S = P
+/// for every X, so that conj(P g P^-1) = P g P^-1 is real when P is invertible. When
+/// lambda < 0 the conjugate is in SU(2) instead.
+pub fn real_form(gens: &Generators) -> Result<RealForm, NoRealForm> {
+    let precision = gens.precision;
+    let tol = tolerance(precision);
+    let traces = gens.trace_coordinates();
+    let imaginary = traces.iter()
+        .map(|t| Float::with_val(precision, t.imag().abs_ref()))
+        .fold(Float::with_val(precision, 0), |m, x| m.max(&x));
+    let size = traces.iter().map(abs).fold(Float::with_val(precision, 1), |m, x| m.max(&x));
+    if imaginary > tol.clone() * &size {
+        return Err(NoRealForm::NonRealTraces(imaginary));
+    }
+    let s = intertwiner(gens).ok_or(NoRealForm::Reducible)?;
+    let lambda = conj(&s).mul(s.clone()).0[0].real().clone();
+    if lambda <= 0 {
+        return Err(NoRealForm::Unitary);
+    }
+    let root = lambda.sqrt();
+    let s = M(s.0.map(|x| x / &root));
+    // X + conj(X) S for a few X, of which at least one is far from singular
+    let (zero, one, i) = (Complex::with_val(precision, 0), Complex::with_val(precision, 1),
+                          Complex::with_val(precision, (0, 1)));
+    let candidates = [[&one, &one], [&i, &i], [&one, &i], [&i, &one]].map(|[x, y]| {
+        let x = M([x.clone(), zero.clone(), zero.clone(), y.clone()]);
+        let sum = conj(&x).mul(s.clone());
+        M([0, 1, 2, 3].map(|k| x.0[k].clone() + &sum.0[k]))
+    });
+    let p = candidates.into_iter()
+        .max_by(|p, q| abs(&p.det()).partial_cmp(&abs(&q.det())).unwrap_or(Ordering::Equal))
+        .expect("four candidates")
+        .renormalize();
+    let p_inv = p.clone().inv();
+    let a = p.clone().mul(gens.a.clone()).mul(p_inv.clone());
+    let b = p.clone().mul(gens.b.clone()).mul(p_inv);
+    let residual = a.0.iter().chain(&b.0)
+        .map(|x| Float::with_val(precision, x.imag().abs_ref()))
+        .fold(Float::with_val(precision, 0), |m, x| m.max(&x));
+    let real = |m: M<C>| M(m.0.map(|x| x.real().clone()));
+    Ok(RealForm { conjugator: p, a: real(a), b: real(b), residual })
+}
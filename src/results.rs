@@ -0,0 +1,541 @@
+use std::fmt;
+use std::io::{self, Write};
+use rug::{Complex, Float};
+use serde::{Deserialize, Serialize};
+
+use crate::budget::Budget;
+use crate::context::RunContext;
+use crate::jorgensen::{JorgensenPair, JorgensenReport};
+use crate::matrix::C;
+use crate::rho::Generators;
+use crate::shard::Shard;
+use crate::spectrum::{self, SpectrumEntry};
+use crate::sweep::{Check, CheckResult};
+
+/// Version of the schema of the results file written by `Results::write`, its `schema`
+/// field. Files of every version from `OLDEST_SCHEMA_VERSION` on can be read, and are
+/// converted to the current one on reading. A version is added whenever a field changes
+/// meaning or a new field is required, never otherwise.
+///
+/// 1. The first version, a text file of one record per line.
+/// 2. Adds `written_by`, the version of repcalc that wrote the file, after `format`.
+/// 3. A JSON document, with the version in `schema` in place of the `format` record and
+///    the header of the run in `context` in place of comments.
+///
+/// Versions 1 and 2 start with the record `format N`.
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// The oldest results schema that `read` accepts.
+pub const OLDEST_SCHEMA_VERSION: u32 = 1;
+
+/// The searches whose results can be saved and merged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Search {
+    Spectrum,
+    Verify,
+    Jorgensen,
+}
+
+impl fmt::Display for Search {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Search::Spectrum => write!(f, "spectrum"),
+            Search::Verify => write!(f, "verify"),
+            Search::Jorgensen => write!(f, "jorgensen"),
+        }
+    }
+}
+
+pub enum Body {
+    Spectrum(Vec<SpectrumEntry>),
+    Verify(Vec<CheckResult>),
+    Jorgensen(JorgensenReport),
+}
+
+/// The output of one run of a search, or of several merged ones: the parameters
+/// that must agree between runs, the shards covered, and what was found.
+pub struct Results {
+    pub precision: u32,
+    /// The entries of a and b in hexadecimal, which identify the representation.
+    pub generators: String,
+    pub max_length: usize,
+    pub shard_count: usize,
+    /// The indices of the shards covered, in increasing order.
+    pub shards: Vec<usize>,
+    pub nodes: u64,
+    /// Whether every covered shard ran to the end without exhausting its budget.
+    pub finished: bool,
+    pub body: Body,
+}
+
+fn hex(x: &Float) -> String {
+    x.to_string_radix(16, None)
+}
+
+fn hex_complex(z: &C) -> String {
+    format!("{} {}", hex(z.real()), hex(z.imag()))
+}
+
+fn parse_hex(s: &str, precision: u32) -> Result<Float, String> {
+    Float::parse_radix(s, 16)
+        .map(|p| Float::with_val(precision, p))
+        .map_err(|_| format!("invalid hexadecimal number {}", s))
+}
+
+impl Results {
+    pub fn new(gens: &Generators, max_length: usize, shard: Shard, budget: &Budget, body: Body) -> Self {
+        let generators = [&gens.a, &gens.b].iter()
+            .flat_map(|m| m.0.iter().map(hex_complex))
+            .collect::<Vec<_>>()
+            .join(" ");
+        Results {
+            precision: gens.precision,
+            generators,
+            max_length,
+            shard_count: shard.count,
+            shards: vec![shard.index],
+            nodes: budget.nodes(),
+            finished: budget.exhausted().is_none(),
+            body,
+        }
+    }
+
+    pub fn search(&self) -> Search {
+        match self.body {
+            Body::Spectrum(_) => Search::Spectrum,
+            Body::Verify(_) => Search::Verify,
+            Body::Jorgensen(_) => Search::Jorgensen,
+        }
+    }
+
+    /// Whether all the shards are present and finished.
+    pub fn complete(&self) -> bool {
+        self.finished && self.shards.len() == self.shard_count
+    }
+
+    /// Writes the results as a JSON document of the current schema, with every
+    /// number that is not a count written exactly in hexadecimal, as a string:
+    ///
+    /// ```text
+    /// {
+    ///   "schema": 3,
+    ///   "written_by": "repcalc <version>",
+    ///   "context": { "<key>": "<value>", ... },                   (the header of the run)
+    ///   "search": "spectrum" | "verify" | "jorgensen",
+    ///   "precision": <bits>,
+    ///   "generators": [<re im of a11 a12 a21 a22 b11 b12 b21 b22>],
+    ///   "max_length": <n>,
+    ///   "shard_count": <count>,
+    ///   "shards": [<index>...],
+    ///   "nodes": <n>,
+    ///   "finished": <bool>,
+    ///   "classes": [{"translation_length", "word", "trace": [re, im]}...]    (spectrum)
+    ///   "checks": [{"check", "checked", "failures", "first_failure"}...]     (verify)
+    ///   "jorgensen": {"checked", "skipped", "violations", "min",
+    ///                 "first_violation"}, each pair {"sum", "first", "second"} (jorgensen)
+    /// }
+    /// ```
+    pub fn write<W: Write>(&self, out: &mut W, context: &RunContext) -> io::Result<()> {
+        let pair = |p: &JorgensenPair| PairDocument { sum: hex(&p.sum), first: p.first.clone(), second: p.second.clone() };
+        let mut document = Document {
+            schema: SCHEMA_VERSION,
+            written_by: format!("repcalc {}", env!("CARGO_PKG_VERSION")),
+            context: context.fields().into_iter().map(|(key, value)| (key.to_string(), value.into())).collect(),
+            search: self.search(),
+            precision: self.precision,
+            generators: self.generators.split_whitespace().map(str::to_string).collect(),
+            max_length: self.max_length,
+            shard_count: self.shard_count,
+            shards: self.shards.clone(),
+            nodes: self.nodes,
+            finished: self.finished,
+            classes: None,
+            checks: None,
+            jorgensen: None,
+        };
+        match &self.body {
+            Body::Spectrum(entries) => {
+                document.classes = Some(entries.iter().map(|e| ClassDocument {
+                    translation_length: hex(&e.translation_length),
+                    word: e.word.clone(),
+                    trace: [hex(e.trace.real()), hex(e.trace.imag())],
+                }).collect());
+            }
+            Body::Verify(results) => {
+                document.checks = Some(results.iter().map(|r| CheckDocument {
+                    check: r.check.to_string(),
+                    checked: r.checked,
+                    failures: r.failures,
+                    first_failure: r.first_failure.clone(),
+                }).collect());
+            }
+            Body::Jorgensen(report) => {
+                document.jorgensen = Some(JorgensenDocument {
+                    checked: report.checked,
+                    skipped: report.skipped,
+                    violations: report.violations,
+                    min: report.min.as_ref().map(pair),
+                    first_violation: report.first_violation.as_ref().map(pair),
+                });
+            }
+        }
+        serde_json::to_writer_pretty(&mut *out, &document)?;
+        writeln!(out)
+    }
+}
+
+/// The results file of the current schema, as `serde` reads and writes it.
+#[derive(Serialize, Deserialize)]
+struct Document {
+    schema: u32,
+    written_by: String,
+    /// Only written: the header of the run, for the reader of the file.
+    #[serde(default, skip_deserializing)]
+    context: serde_json::Map<String, serde_json::Value>,
+    search: Search,
+    precision: u32,
+    generators: Vec<String>,
+    max_length: usize,
+    shard_count: usize,
+    shards: Vec<usize>,
+    nodes: u64,
+    finished: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    classes: Option<Vec<ClassDocument>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    checks: Option<Vec<CheckDocument>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    jorgensen: Option<JorgensenDocument>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ClassDocument {
+    translation_length: String,
+    word: String,
+    trace: [String; 2],
+}
+
+#[derive(Serialize, Deserialize)]
+struct CheckDocument {
+    check: String,
+    checked: usize,
+    failures: usize,
+    first_failure: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JorgensenDocument {
+    checked: usize,
+    skipped: usize,
+    violations: usize,
+    min: Option<PairDocument>,
+    first_violation: Option<PairDocument>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PairDocument {
+    sum: String,
+    first: String,
+    second: String,
+}
+
+/// Parses a file written by `Results::write` of any schema from `OLDEST_SCHEMA_VERSION`
+/// to `SCHEMA_VERSION`: a JSON document, or a text file of the versions before it.
+pub fn read(input: &str) -> Result<Results, String> {
+    if input.trim_start().starts_with('{') {
+        read_json(input)
+    } else {
+        read_text(input)
+    }
+}
+
+fn read_json(input: &str) -> Result<Results, String> {
+    let value: serde_json::Value = serde_json::from_str(input).map_err(|e| e.to_string())?;
+    let version = value.get("schema").and_then(serde_json::Value::as_u64).ok_or("missing schema")?;
+    if version > SCHEMA_VERSION as u64 {
+        return Err(format!("schema {} is newer than this repcalc reads ({}); upgrade repcalc",
+                           version, SCHEMA_VERSION));
+    }
+    if version < 3 {
+        return Err(format!("schema {} is a text file, not JSON", version));
+    }
+    let document: Document = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    let bits = document.precision;
+    if document.generators.len() != 16 {
+        return Err("the generators must have 16 entries".to_string());
+    }
+    for g in &document.generators {
+        parse_hex(g, bits)?;
+    }
+    if document.shards.is_empty() || document.shards.iter().any(|&i| i >= document.shard_count) {
+        return Err("shard index out of range".to_string());
+    }
+    let pair = |p: PairDocument| -> Result<JorgensenPair, String> {
+        Ok(JorgensenPair { sum: parse_hex(&p.sum, bits)?, first: p.first, second: p.second })
+    };
+    let missing = |field: &str| format!("missing {} for a {} search", field, document.search);
+    let body = match document.search {
+        Search::Spectrum => {
+            let classes = document.classes.ok_or_else(|| missing("classes"))?;
+            Body::Spectrum(classes.into_iter().map(|c| {
+                let [re, im] = c.trace;
+                Ok(SpectrumEntry {
+                    word: c.word,
+                    trace: Complex::with_val(bits, (parse_hex(&re, bits)?, parse_hex(&im, bits)?)),
+                    translation_length: parse_hex(&c.translation_length, bits)?,
+                })
+            }).collect::<Result<_, String>>()?)
+        }
+        Search::Verify => {
+            let checks = document.checks.ok_or_else(|| missing("checks"))?;
+            Body::Verify(checks.into_iter().map(|c| {
+                Ok(CheckResult {
+                    check: Check::parse(&c.check).ok_or_else(|| format!("unknown check {}", c.check))?,
+                    checked: c.checked,
+                    failures: c.failures,
+                    first_failure: c.first_failure,
+                })
+            }).collect::<Result<_, String>>()?)
+        }
+        Search::Jorgensen => {
+            let report = document.jorgensen.ok_or_else(|| missing("jorgensen"))?;
+            Body::Jorgensen(JorgensenReport {
+                checked: report.checked,
+                skipped: report.skipped,
+                violations: report.violations,
+                min: report.min.map(pair).transpose()?,
+                first_violation: report.first_violation.map(pair).transpose()?,
+            })
+        }
+    };
+    Ok(Results {
+        precision: bits,
+        generators: document.generators.join(" "),
+        max_length: document.max_length,
+        shard_count: document.shard_count,
+        shards: document.shards,
+        nodes: document.nodes,
+        finished: document.finished,
+        body,
+    })
+}
+
+/// Parses a text file of the schemas before JSON, in which every line is a record of
+/// space separated fields after the comments of the header:
+///
+/// ```text
+/// format <1 | 2>
+/// written_by repcalc <version>                                    (2)
+/// search <spectrum | verify | jorgensen>
+/// precision <bits>
+/// generators <re im of a11 a12 a21 a22 b11 b12 b21 b22>
+/// max_length <n>
+/// shards <count> <index>...
+/// nodes <n> <finished | stopped>
+/// class <translation length> <word> <trace re> <trace im>        (spectrum)
+/// check <name> <checked> <failures> <first failure or ->           (verify)
+/// pairs <checked> <skipped> <violations>                          (jorgensen)
+/// min <sum> <first> <second>                                      (jorgensen)
+/// violation <sum> <first> <second>                                (jorgensen)
+/// ```
+///
+/// They differ from the current schema only in records that are not kept, so converting
+/// them needs nothing filled in.
+fn read_text(input: &str) -> Result<Results, String> {
+    let mut format: Option<u32> = None;
+    let mut search: Option<Search> = None;
+    let mut precision: Option<u32> = None;
+    let mut generators: Option<String> = None;
+    let mut max_length: Option<usize> = None;
+    let mut shards: Option<(usize, Vec<usize>)> = None;
+    let mut nodes: Option<(u64, bool)> = None;
+    let mut classes = Vec::new();
+    let mut checks = Vec::new();
+    let mut report = JorgensenReport { checked: 0, skipped: 0, violations: 0, min: None, first_violation: None };
+
+    for (n, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let error = |msg: &str| format!("line {}: {}", n + 1, msg);
+        let number = |s: &str| s.parse::<u64>().map_err(|_| error(&format!("invalid number {}", s)));
+        let pair = |fields: &[&str], bits: u32| -> Result<JorgensenPair, String> {
+            let sum = parse_hex(fields[1], bits).map_err(|e| error(&e))?;
+            Ok(JorgensenPair { sum, first: fields[2].to_string(), second: fields[3].to_string() })
+        };
+        match (fields[0], format) {
+            ("format", None) if fields.len() == 2 => {
+                let version = number(fields[1])? as u32;
+                if version > 2 {
+                    return Err(error(&format!("format {} is written as JSON, with a schema field", version)));
+                }
+                if version < OLDEST_SCHEMA_VERSION {
+                    return Err(error(&format!("format {} is no longer supported", version)));
+                }
+                format = Some(version);
+                continue;
+            }
+            ("format", Some(_)) => return Err(error("the format is given twice")),
+            ("written_by", Some(version)) if version >= 2 => continue,
+            (_, None) => return Err(error("the format must come first")),
+            _ => {}
+        }
+        match (fields[0], precision) {
+            ("search", _) if fields.len() == 2 => {
+                search = Some(match fields[1] {
+                    "spectrum" => Search::Spectrum,
+                    "verify" => Search::Verify,
+                    "jorgensen" => Search::Jorgensen,
+                    _ => return Err(error("unknown search")),
+                });
+            }
+            ("precision", _) if fields.len() == 2 => precision = Some(number(fields[1])? as u32),
+            ("generators", _) if fields.len() == 17 => generators = Some(fields[1..].join(" ")),
+            ("max_length", _) if fields.len() == 2 => max_length = Some(number(fields[1])? as usize),
+            ("shards", _) if fields.len() >= 3 => {
+                let count = number(fields[1])? as usize;
+                let indices = fields[2..].iter().map(|i| number(i).map(|i| i as usize)).collect::<Result<Vec<_>, _>>()?;
+                if indices.iter().any(|&i| i >= count) {
+                    return Err(error("shard index out of range"));
+                }
+                shards = Some((count, indices));
+            }
+            ("nodes", _) if fields.len() == 3 => nodes = Some((number(fields[1])?, fields[2] == "finished")),
+            ("class", Some(bits)) if fields.len() == 5 => {
+                let translation_length = parse_hex(fields[1], bits).map_err(|e| error(&e))?;
+                let re = parse_hex(fields[3], bits).map_err(|e| error(&e))?;
+                let im = parse_hex(fields[4], bits).map_err(|e| error(&e))?;
+                let trace = Complex::with_val(bits, (re, im));
+                classes.push(SpectrumEntry { word: fields[2].to_string(), trace, translation_length });
+            }
+            ("check", _) if fields.len() == 5 => {
+                let check = Check::parse(fields[1]).ok_or_else(|| error("unknown check"))?;
+                let first_failure = if fields[4] == "-" { None } else { Some(fields[4].to_string()) };
+                checks.push(CheckResult {
+                    check,
+                    checked: number(fields[2])? as usize,
+                    failures: number(fields[3])? as usize,
+                    first_failure,
+                });
+            }
+            ("pairs", _) if fields.len() == 4 => {
+                report.checked = number(fields[1])? as usize;
+                report.skipped = number(fields[2])? as usize;
+                report.violations = number(fields[3])? as usize;
+            }
+            ("min", Some(bits)) if fields.len() == 4 => report.min = Some(pair(&fields, bits)?),
+            ("violation", Some(bits)) if fields.len() == 4 => report.first_violation = Some(pair(&fields, bits)?),
+            ("class" | "min" | "violation", None) => return Err(error("the precision must come first")),
+            _ => return Err(error("unrecognized line")),
+        }
+    }
+
+    format.ok_or("missing format")?;
+    let search = search.ok_or("missing search")?;
+    let (shard_count, shards) = shards.ok_or("missing shards")?;
+    let (nodes, finished) = nodes.ok_or("missing nodes")?;
+    let body = match search {
+        Search::Spectrum => Body::Spectrum(classes),
+        Search::Verify => Body::Verify(checks),
+        Search::Jorgensen => Body::Jorgensen(report),
+    };
+    Ok(Results {
+        precision: precision.ok_or("missing precision")?,
+        generators: generators.ok_or("missing generators")?,
+        max_length: max_length.ok_or("missing max_length")?,
+        shard_count,
+        shards,
+        nodes,
+        finished,
+        body,
+    })
+}
+
+/// Combines the results of runs of the same search on the same representation.
+/// Runs that cover the same set of shards did the same work, so only one of them
+/// is kept, preferring one that finished; runs whose shards overlap otherwise
+/// cannot be combined.
+pub fn merge(runs: Vec<Results>) -> Result<Results, String> {
+    let mut kept: Vec<Results> = Vec::new();
+    for run in runs {
+        if let Some(first) = kept.first() {
+            let mismatch = if run.search() != first.search() {
+                Some("search")
+            } else if run.precision != first.precision {
+                Some("precision")
+            } else if run.generators != first.generators {
+                Some("generators")
+            } else if run.max_length != first.max_length {
+                Some("max_length")
+            } else if run.shard_count != first.shard_count {
+                Some("shard count")
+            } else {
+                None
+            };
+            if let Some(field) = mismatch {
+                return Err(format!("the results have different {}", field));
+            }
+        }
+        match kept.iter().position(|k| k.shards == run.shards) {
+            Some(i) => {
+                if (run.finished, run.nodes) > (kept[i].finished, kept[i].nodes) {
+                    kept[i] = run;
+                }
+            }
+            None => {
+                if let Some(k) = kept.iter().find(|k| k.shards.iter().any(|i| run.shards.contains(i))) {
+                    return Err(format!("shards {:?} and {:?} overlap", k.shards, run.shards));
+                }
+                kept.push(run);
+            }
+        }
+    }
+    kept.sort_by_key(|k| k.shards[0]);
+
+    let mut runs = kept.into_iter();
+    let mut merged = runs.next().ok_or("nothing to merge")?;
+    for run in runs {
+        merged.shards.extend(run.shards);
+        merged.nodes += run.nodes;
+        merged.finished &= run.finished;
+        match (&mut merged.body, run.body) {
+            (Body::Spectrum(entries), Body::Spectrum(more)) => entries.extend(more),
+            (Body::Verify(results), Body::Verify(more)) => {
+                for r in more {
+                    match results.iter_mut().find(|x| x.check == r.check) {
+                        Some(x) => {
+                            x.checked += r.checked;
+                            x.failures += r.failures;
+                            if x.first_failure.is_none() {
+                                x.first_failure = r.first_failure;
+                            }
+                        }
+                        None => results.push(r),
+                    }
+                }
+            }
+            (Body::Jorgensen(report), Body::Jorgensen(more)) => {
+                report.checked += more.checked;
+                report.skipped += more.skipped;
+                report.violations += more.violations;
+                if report.first_violation.is_none() {
+                    report.first_violation = more.first_violation;
+                }
+                if let Some(min) = more.min {
+                    if report.min.as_ref().map_or(true, |m| min.sum < m.sum) {
+                        report.min = Some(min);
+                    }
+                }
+            }
+            _ => unreachable!("the searches were checked to agree"),
+        }
+    }
+    merged.shards.sort_unstable();
+    if let Body::Spectrum(entries) = &mut merged.body {
+        spectrum::sort(entries);
+    }
+    Ok(merged)
+}
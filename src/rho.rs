@@ -0,0 +1,280 @@
+use rug::{Complex, Float};
+
+use crate::classify::tolerance;
+use crate::error::Error;
+use crate::matrix::{C, M};
+use crate::word::LETTERS;
+
+pub fn rho_a(precision: u32, z: C) -> M<C> {
+    let one: C = Complex::with_val(precision, 1);
+    let c = (z.clone().square() - one.clone()).sqrt().recip();
+    let cz = c.clone() * z;
+    M([cz.clone(), c.clone(), c, cz])
+}
+
+/// Checks that z is not one of the branch points z = 1 and z = -1 of the square roots in
+/// `rho_a` and `rho_b`, where z^2 - 1 = 0 and the generators are not defined.
+pub fn check_z(z: &C) -> Result<(), Error> {
+    if (z.clone().square() - 1u32).is_zero() {
+        return Err(Error::Degenerate(format!(
+            "z = {} is a branch point of the generators: z^2 - 1 = 0, so their entries are infinite", z)));
+    }
+    Ok(())
+}
+
+pub fn rho_b(precision: u32, z: C) -> M<C> {
+    let i : C = Complex::with_val(precision, (0, 1)); 
+    let one: C = Complex::with_val(precision, 1);
+    let y = (-z.clone()) / (z.clone().square() - one.clone()).sqrt();
+    let c = (y.clone().square() - one).sqrt().recip();
+
+    let cy = c.clone() * y;
+    let ci = c.clone() * i;
+
+    M([cy.clone(), ci.clone(), -ci, cy])
+}
+
+/// The Maskit slice: a = [[-i mu, -i], [-i, 0]] and the parabolic b = [[1, 2], [0, 1]],
+/// with tr[a, b] = -2.
+pub fn maskit(precision: u32, mu: C) -> (M<C>, M<C>) {
+    let i: C = Complex::with_val(precision, (0, 1));
+    let zero: C = Complex::with_val(precision, 0);
+    let one: C = Complex::with_val(precision, 1);
+    let two: C = Complex::with_val(precision, 2);
+    let a = M([-(i.clone() * mu), -i.clone(), -i, zero.clone()]);
+    let b = M([one.clone(), two, zero, one]);
+    (a, b)
+}
+
+/// The Riley slice: the two parabolics a = [[1, 1], [0, 1]] and b = [[1, 0], [rho, 1]].
+pub fn riley(precision: u32, rho: C) -> (M<C>, M<C>) {
+    let zero: C = Complex::with_val(precision, 0);
+    let one: C = Complex::with_val(precision, 1);
+    let a = M([one.clone(), one.clone(), zero.clone(), one.clone()]);
+    let b = M([one.clone(), zero, rho, one]);
+    (a, b)
+}
+
+/// The root of the Markov identity x^2 + y^2 + z^2 = xyz in the third trace, given
+/// the other two, that is taken by the minus sign.
+fn markov_root(x: &C, y: &C) -> C {
+    let discriminant = (x.clone() * y).square() - (x.clone().square() + y.clone().square()) * 4u32;
+    (x.clone() * y - discriminant.sqrt()) / 2u32
+}
+
+/// Generators with tr a = ta, tr b = tb and tr[a, b] = -2, by Grandma's recipe
+/// from Indra's Pearls. tr ab is the root of the Markov identity
+/// x^2 + y^2 + z^2 = xyz given by the minus sign.
+pub fn from_traces(precision: u32, ta: C, tb: C) -> (M<C>, M<C>) {
+    let tab = markov_root(&ta, &tb);
+    grandma(precision, ta, tb, tab)
+}
+
+/// Generators with tr a = ta and tr ab = tab, by Grandma's recipe with tr b the root
+/// of the Markov identity given by the minus sign, as in `from_traces`.
+pub fn from_ta_tab(precision: u32, ta: C, tab: C) -> (M<C>, M<C>) {
+    let tb = markov_root(&ta, &tab);
+    grandma(precision, ta, tb, tab)
+}
+
+/// Grandma's special parabolic commutator recipe: generators with the given traces of
+/// a, b and ab, which have to satisfy the Markov identity, so that tr[a, b] = -2.
+/// Either root of the identity in tab works.
+pub fn grandma(precision: u32, ta: C, tb: C, tab: C) -> (M<C>, M<C>) {
+    let i: C = Complex::with_val(precision, (0, 1));
+    let z0 = (tab.clone() - 2u32) * &tb / (tb.clone() * &tab - ta.clone() * 2u32 + i.clone() * &tab * 2u32);
+
+    let half_ta = ta.clone() / 2u32;
+    let b_entry = (ta.clone() * &tab - tb.clone() * 2u32 + i.clone() * 4u32) / ((tab.clone() * 2u32 + 4u32) * &z0);
+    let c_entry = (ta * &tab - tb.clone() * 2u32 - i.clone() * 4u32) * z0 / (tab * 2u32 - 4u32);
+    let a = M([half_ta.clone(), b_entry, c_entry, half_ta]);
+
+    let half_tb = tb.clone() / 2u32;
+    let b = M([(tb.clone() - i.clone() * 2u32) / 2u32, half_tb.clone(), half_tb, (tb + i * 2u32) / 2u32]);
+    (a, b)
+}
+
+/// The runs of repeated letters of a word, as (letter, length).
+fn runs(word: &str) -> impl Iterator<Item = (char, usize)> + '_ {
+    let mut chars = word.chars().peekable();
+    std::iter::from_fn(move || {
+        let c = chars.next()?;
+        let mut n = 1;
+        while chars.next_if_eq(&c).is_some() {
+            n += 1;
+        }
+        Some((c, n))
+    })
+}
+
+/// How far the determinant of the partial products drifted from 1 while a word was
+/// multiplied out.
+pub struct DetDrift {
+    /// |det - 1| of the result.
+    pub final_error: Float,
+    /// The largest |det - 1| of any partial product, before renormalizing it.
+    pub max_error: Float,
+    pub renormalizations: usize,
+    /// Renormalizations left out because |det - 1| was beyond the tolerance, see
+    /// `eval_tracking`.
+    pub skipped: usize,
+}
+
+/// A generator beyond a and b, named by a lowercase letter; its inverse is named by the
+/// uppercase one.
+#[derive(Clone)]
+pub struct Extra {
+    pub name: char,
+    pub m: M<C>,
+    pub inv: M<C>,
+}
+
+/// The images of the generators and their inverses under the representation.
+#[derive(Clone)]
+pub struct Generators {
+    pub precision: u32,
+    pub a: M<C>,
+    pub b: M<C>,
+    pub a_inv: M<C>,
+    pub b_inv: M<C>,
+    /// Further generators, for groups such as those of the four-times-punctured sphere
+    /// that need more than two. Everything built on the two-generator families and the
+    /// trace coordinates ignores them.
+    pub extra: Vec<Extra>,
+}
+
+impl Generators {
+    pub fn new(precision: u32, z: C) -> Self {
+        Generators::from_matrices(precision, rho_a(precision, z.clone()), rho_b(precision, z))
+    }
+
+    pub fn from_matrices(precision: u32, a: M<C>, b: M<C>) -> Self {
+        let a_inv = a.clone().inv();
+        let b_inv = b.clone().inv();
+        Generators { precision, a, b, a_inv, b_inv, extra: Vec::new() }
+    }
+
+    /// Adds the generator `name`, a lowercase letter other than a and b that is not taken.
+    pub fn with_extra(mut self, name: char, m: M<C>) -> Self {
+        let inv = m.clone().inv();
+        self.extra.push(Extra { name, m, inv });
+        self
+    }
+
+    /// The conjugate representation g -> p g p^-1 of every generator, extras included.
+    /// Traces, and so everything that depends only on them, are unchanged.
+    pub fn conjugate(self, p: &M<C>) -> Self {
+        let p_inv = p.clone().inv();
+        let by = |m: M<C>| p.clone().mul(m).mul(p_inv.clone());
+        let conjugated = Generators::from_matrices(self.precision, by(self.a), by(self.b));
+        self.extra.into_iter().fold(conjugated, |gens, g| gens.with_extra(g.name, by(g.m)))
+    }
+
+    /// The letters of the generators and their inverses: a, b, A, B and then those of
+    /// `extra`.
+    pub fn letters(&self) -> Vec<char> {
+        let extra = self.extra.iter().flat_map(|g| [g.name, g.name.to_ascii_uppercase()]);
+        LETTERS.into_iter().chain(extra).collect()
+    }
+
+    pub fn has_letter(&self, c: char) -> bool {
+        matches!(c, 'a' | 'b' | 'A' | 'B') || self.extra.iter().any(|g| g.name == c.to_ascii_lowercase())
+    }
+
+    /// The matrix of a letter of `letters`; words are checked with `has_letter` when
+    /// they are read.
+    pub fn letter(&self, c: char) -> &M<C> {
+        match c {
+            'a' => &self.a,
+            'b' => &self.b,
+            'A' => &self.a_inv,
+            'B' => &self.b_inv,
+            _ => {
+                let g = self.extra.iter().find(|g| g.name == c.to_ascii_lowercase())
+                    .unwrap_or_else(|| panic!("{} is not a generator; check words with has_letter", c));
+                if c.is_ascii_lowercase() { &g.m } else { &g.inv }
+            }
+        }
+    }
+
+    /// Checks that every entry of every generator is finite, which fails where a recipe
+    /// divides by zero, such as `grandma` at tr ab = 2.
+    pub fn check_finite(&self) -> Result<(), Error> {
+        for c in self.letters() {
+            if !self.letter(c).0.iter().all(|x| x.real().is_finite() && x.imag().is_finite()) {
+                return Err(Error::Degenerate(format!(
+                    "the generator {} is not finite at this parameter, where the representation degenerates", c)));
+            }
+        }
+        Ok(())
+    }
+
+    /// tr[a, b] = tr(a b a^-1 b^-1), which is -2 exactly when the representation
+    /// sends the loop around the puncture to a parabolic.
+    pub fn commutator_trace(&self) -> C {
+        self.eval("abAB").trace()
+    }
+
+    /// |tr[a, b] + 2|, zero up to rounding for a type-preserving representation.
+    pub fn relator_error(&self) -> Float {
+        let error = self.commutator_trace() + 2u32;
+        Float::with_val(self.precision, error.abs_ref())
+    }
+
+    /// The trace coordinates (tr a, tr b, tr ab).
+    pub fn trace_coordinates(&self) -> [C; 3] {
+        [self.a.trace(), self.b.trace(), self.eval("ab").trace()]
+    }
+
+    /// x^2 + y^2 + z^2 - xyz in the trace coordinates. By the Fricke identity this
+    /// is tr[a, b] + 2, so it vanishes when the Markov identity x^2 + y^2 + z^2 = xyz holds.
+    pub fn markov_residual(&self) -> C {
+        let [x, y, z] = self.trace_coordinates();
+        let xyz = x.clone() * &y * &z;
+        x.square() + y.square() + z.square() - xyz
+    }
+
+    /// The matrix of a word in the letters of `letters`; the empty word evaluates to the
+    /// identity. Each run of a repeated letter is raised to its power by squaring, and
+    /// the runs are multiplied as a balanced tree (see `M::product`).
+    pub fn eval(&self, word: &str) -> M<C> {
+        if word.is_empty() {
+            return M::identity(self.precision);
+        }
+        M::product(runs(word).map(|(c, n)| self.letter(c).clone().pow(n)).collect())
+    }
+
+    /// Like `eval`, but also tracks |det - 1| of every partial product and, with
+    /// `renormalize_every = Some(k)`, divides the product by sqrt(det) after every k
+    /// multiplications (and once more at the end) so that rounding errors in the
+    /// determinant do not build up over long words. The determinant is computed from the
+    /// entries, so once they are large the cancellation in ad - bc swamps the drift, and
+    /// dividing by it would change the product by far more than its rounding error. So a
+    /// renormalization is only done while |det - 1| is within `classify::tolerance`, which
+    /// is where it helps: products that stay bounded, such as elliptic words.
+    pub fn eval_tracking(&self, word: &str, renormalize_every: Option<usize>) -> (M<C>, DetDrift) {
+        let precision = self.precision;
+        let mut max_error = Float::with_val(precision, 0);
+        let tol = tolerance(precision);
+        let (mut renormalizations, mut skipped) = (0, 0);
+        let mut m = M::identity(precision);
+        for (k, c) in word.chars().enumerate() {
+            m = m.mul(self.letter(c).clone());
+            let error = m.det_error(precision);
+            let small = error <= tol;
+            if error > max_error {
+                max_error = error;
+            }
+            if renormalize_every.is_some_and(|every| (k + 1) % every == 0 || k + 1 == word.len()) {
+                if small {
+                    m = m.renormalize();
+                    renormalizations += 1;
+                } else {
+                    skipped += 1;
+                }
+            }
+        }
+        let final_error = m.det_error(precision);
+        (m, DetDrift { final_error, max_error, renormalizations, skipped })
+    }
+}
@@ -0,0 +1,47 @@
+use rug::Float;
+use rug::float::Constant;
+
+use crate::classify::tolerance;
+use crate::matrix::{C, M};
+use crate::rho::Generators;
+
+/// Whether every entry has imaginary part within `tol` of zero.
+pub fn is_real(m: &M<C>, tol: &Float) -> bool {
+    m.0.iter().all(|x| x.imag().clone().abs() <= *tol)
+}
+
+/// The rotation number of the cocycle that applies the letters of `word` one at a
+/// time, averaged over `iterations` repetitions of the word, which must be positive.
+///
+/// The projective action is lifted by following a vector in R^2 and taking each
+/// letter's change of angle in (-pi, pi]. The result is in full turns per
+/// application of the word. Returns `None` if a generator is not real.
+pub fn rotation_number(gens: &Generators, word: &str, iterations: usize) -> Option<Float> {
+    let precision = gens.precision;
+    let tol = tolerance(precision);
+    if !is_real(&gens.a, &tol) || !is_real(&gens.b, &tol) {
+        return None;
+    }
+
+    let mut x = Float::with_val(precision, 1);
+    let mut y = Float::with_val(precision, 0);
+    let mut total = Float::with_val(precision, 0);
+    for _ in 0..iterations {
+        for l in word.chars() {
+            let [a, b, c, d] = &gens.letter(l).0;
+            let nx = a.real().clone() * &x + b.real().clone() * &y;
+            let ny = c.real().clone() * &x + d.real().clone() * &y;
+            // angle from (x, y) to (nx, ny)
+            let cross = x.clone() * &ny - y.clone() * &nx;
+            let dot = x * &nx + y * &ny;
+            total += cross.atan2(&dot);
+
+            let norm = (nx.clone().square() + ny.clone().square()).sqrt();
+            x = nx / &norm;
+            y = ny / norm;
+        }
+    }
+
+    let turn = Float::with_val(precision, Constant::Pi) * 2u32;
+    Some(total / turn / iterations as u64)
+}
@@ -0,0 +1,106 @@
+use rand::Rng;
+use rand::distributions::{Distribution, WeightedIndex};
+use rug::Float;
+
+use crate::matrix::{C, M};
+use crate::rho::Generators;
+use crate::stats::log_norm;
+use crate::word::{LETTERS, inverse_letter};
+
+/// An estimate of a probability from independent replicates, with its standard error.
+pub struct Estimate {
+    pub probability: Float,
+    pub std_error: Float,
+    /// The number of final particles, over all replicates, in which the event occurred.
+    pub hits: usize,
+}
+
+/// Parameters of the branching sampler in `small_trace_probability`.
+pub struct Sampler {
+    pub particles: usize,
+    pub replicates: usize,
+    /// How strongly the particles are pushed towards words of small norm; zero gives
+    /// naive Monte Carlo.
+    pub tilt: f64,
+    /// Sample freely reduced words instead of unreduced ones.
+    pub reduced: bool,
+}
+
+#[derive(Clone)]
+struct Particle {
+    last: Option<char>,
+    m: M<C>,
+    log_norm: Float,
+}
+
+/// Estimates the probability that a random word of the given length has
+/// |tr| < 2 + epsilon.
+///
+/// Words are grown a letter at a time by a population of particles which is
+/// resampled after each letter with weights exp(-tilt (log|M_k| - log|M_k-1|)), so
+/// that particles whose products stay small branch and the others die out. The
+/// weights telescope, so each final particle is reweighted by exp(tilt log|M_n|)
+/// times the product of the mean weights to give an unbiased estimate.
+pub fn small_trace_probability<R: Rng>(
+    gens: &Generators,
+    rng: &mut R,
+    length: usize,
+    epsilon: &Float,
+    sampler: &Sampler,
+) -> Estimate {
+    let precision = gens.precision;
+    let threshold = Float::with_val(precision, epsilon + 2u32);
+    let mut estimates = Vec::with_capacity(sampler.replicates);
+    let mut hits = 0;
+
+    for _ in 0..sampler.replicates {
+        let mut particles: Vec<Particle> = (0..sampler.particles)
+            .map(|_| Particle { last: None, m: M::identity(precision), log_norm: Float::with_val(precision, 0) })
+            .collect();
+        let mut normalization = Float::with_val(precision, 1);
+
+        for _ in 0..length {
+            let mut weights = Vec::with_capacity(particles.len());
+            for p in particles.iter_mut() {
+                let c = loop {
+                    let c = LETTERS[rng.gen_range(0usize..4)];
+                    if !sampler.reduced || p.last.map(inverse_letter) != Some(c) {
+                        break c;
+                    }
+                };
+                p.last = Some(c);
+                p.m = p.m.clone().mul(gens.letter(c).clone());
+                let log_norm = log_norm(&p.m);
+                let step = Float::with_val(precision, &log_norm - &p.log_norm) * -sampler.tilt;
+                weights.push(step.exp());
+                p.log_norm = log_norm;
+            }
+            let total = weights.iter().fold(Float::with_val(precision, 0), |acc, w| acc + w);
+            normalization *= Float::with_val(precision, &total / particles.len() as u32);
+            let relative: Vec<f64> = weights.iter()
+                .map(|w| Float::with_val(precision, w / &total).to_f64())
+                .collect();
+            let index = WeightedIndex::new(&relative).expect("weights are positive");
+            particles = (0..particles.len())
+                .map(|_| particles[index.sample(rng)].clone())
+                .collect();
+        }
+
+        let mut sum = Float::with_val(precision, 0);
+        for p in &particles {
+            if Float::with_val(precision, p.m.trace().abs_ref()) < threshold {
+                hits += 1;
+                sum += Float::with_val(precision, &p.log_norm * sampler.tilt).exp();
+            }
+        }
+        estimates.push(sum * normalization / particles.len() as u32);
+    }
+
+    let n = estimates.len() as u32;
+    let probability = estimates.iter().fold(Float::with_val(precision, 0), |acc, x| acc + x) / n;
+    let variance = estimates.iter().fold(Float::with_val(precision, 0), |acc, x| {
+        acc + Float::with_val(precision, x - &probability).square()
+    }) / (n.max(2) - 1);
+    let std_error = (variance / n).sqrt();
+    Estimate { probability, std_error, hits }
+}
@@ -0,0 +1,244 @@
+use std::fmt;
+
+use crate::error::Error;
+
+/// A 2x2 matrix [[a, b], [c, d]] stored as [a, b, c, d].
+#[derive(Clone, Copy, PartialEq)]
+pub struct M<A>(pub [A; 4]);
+
+/// The complex arithmetic that word evaluation needs, so that it can run on
+/// either bignum backend: `rug` (GMP/MPFR, the default) or the pure Rust `dashu`.
+pub trait Scalar: Clone + fmt::Display {
+    fn from_f64(precision: u32, re: f64, im: f64) -> Self;
+    /// Parses decimal real and imaginary parts directly at the given precision.
+    fn parse(precision: u32, re: &str, im: &str) -> Option<Self>;
+    fn add(&self, other: &Self) -> Self;
+    fn sub(&self, other: &Self) -> Self;
+    fn mul(&self, other: &Self) -> Self;
+    fn div(&self, other: &Self) -> Self;
+    fn neg(&self) -> Self;
+    /// The principal square root.
+    fn sqrt(&self) -> Self;
+    /// The real and imaginary parts, rounded to f64.
+    fn to_f64(&self) -> (f64, f64);
+}
+
+pub fn identity<S: Scalar>(precision: u32) -> M<S> {
+    let one = S::from_f64(precision, 1.0, 0.0);
+    let zero = S::from_f64(precision, 0.0, 0.0);
+    M([one.clone(), zero.clone(), zero, one])
+}
+
+pub fn trace<S: Scalar>(m: &M<S>) -> S {
+    m.0[0].add(&m.0[3])
+}
+
+pub fn mul<S: Scalar>(x: &M<S>, y: &M<S>) -> M<S> {
+    let [a1, b1, c1, d1] = &x.0;
+    let [a2, b2, c2, d2] = &y.0;
+    M([
+        a1.mul(a2).add(&b1.mul(c2)),
+        a1.mul(b2).add(&b1.mul(d2)),
+        c1.mul(a2).add(&d1.mul(c2)),
+        c1.mul(b2).add(&d1.mul(d2)),
+    ])
+}
+
+/// The inverse of a matrix of determinant 1.
+pub fn inv_sl2<S: Scalar>(m: &M<S>) -> M<S> {
+    let [a, b, c, d] = &m.0;
+    M([d.clone(), b.neg(), c.neg(), a.clone()])
+}
+
+/// `rho::check_z` in any backend: an `Error::Degenerate` at the branch points z = ±1,
+/// where z^2 - 1 vanishes and `rho` would divide by zero.
+pub fn check_z<S: Scalar>(precision: u32, z: &S) -> Result<(), Error> {
+    let one = S::from_f64(precision, 1.0, 0.0);
+    if z.mul(z).sub(&one).to_f64() == (0.0, 0.0) {
+        return Err(Error::Degenerate(format!(
+            "z = {} is a branch point of the generators: z^2 - 1 = 0, so their entries are infinite", z)));
+    }
+    Ok(())
+}
+
+/// `rho::rho_a(z)` and `rho::rho_b(z)` in any backend.
+pub fn rho<S: Scalar>(precision: u32, z: &S) -> (M<S>, M<S>) {
+    let one = S::from_f64(precision, 1.0, 0.0);
+    let i = S::from_f64(precision, 0.0, 1.0);
+
+    let root = z.mul(z).sub(&one).sqrt();
+    let c = one.div(&root);
+    let cz = c.mul(z);
+    let a = M([cz.clone(), c.clone(), c, cz]);
+
+    let y = z.neg().div(&root);
+    let c = one.div(&y.mul(&y).sub(&one).sqrt());
+    let cy = c.mul(&y);
+    let ci = c.mul(&i);
+    let b = M([cy.clone(), ci.clone(), ci.neg(), cy]);
+    (a, b)
+}
+
+/// `rho::maskit(mu)` in any backend.
+pub fn maskit<S: Scalar>(precision: u32, mu: &S) -> (M<S>, M<S>) {
+    let zero = S::from_f64(precision, 0.0, 0.0);
+    let one = S::from_f64(precision, 1.0, 0.0);
+    let two = S::from_f64(precision, 2.0, 0.0);
+    let minus_i = S::from_f64(precision, 0.0, -1.0);
+    let a = M([minus_i.mul(mu), minus_i.clone(), minus_i, zero.clone()]);
+    let b = M([one.clone(), two, zero, one]);
+    (a, b)
+}
+
+/// `rho::riley(rho)` in any backend.
+pub fn riley<S: Scalar>(precision: u32, rho: &S) -> (M<S>, M<S>) {
+    let zero = S::from_f64(precision, 0.0, 0.0);
+    let one = S::from_f64(precision, 1.0, 0.0);
+    let a = M([one.clone(), one.clone(), zero.clone(), one.clone()]);
+    let b = M([one.clone(), zero, rho.clone(), one]);
+    (a, b)
+}
+
+/// The matrix of a word in {a,b,A,B}; the empty word evaluates to the identity.
+pub fn eval_word<S: Scalar>(precision: u32, a: &M<S>, b: &M<S>, word: &str) -> Result<M<S>, Error> {
+    let (a_inv, b_inv) = (inv_sl2(a), inv_sl2(b));
+    word.chars().try_fold(identity(precision), |m, c| {
+        let letter = match c {
+            'a' => a,
+            'b' => b,
+            'A' => &a_inv,
+            'B' => &b_inv,
+            _ => return Err(not_a_letter(c)),
+        };
+        Ok(mul(&m, letter))
+    })
+}
+
+/// The error of a word with a letter other than a, b, A and B, for the evaluators of the
+/// two-generator families.
+pub fn not_a_letter(c: char) -> Error {
+    Error::Input(format!("{} is not one of the letters a, b, A, B of the generators", c))
+}
+
+#[cfg(feature = "rug")]
+mod rug_backend {
+    use rug::{Complex, Float};
+
+    use super::Scalar;
+
+    impl Scalar for Complex {
+        fn from_f64(precision: u32, re: f64, im: f64) -> Self {
+            Complex::with_val(precision, (re, im))
+        }
+        fn parse(precision: u32, re: &str, im: &str) -> Option<Self> {
+            let re = Float::with_val(precision, Float::parse(re).ok()?);
+            let im = Float::with_val(precision, Float::parse(im).ok()?);
+            Some(Complex::with_val(precision, (re, im)))
+        }
+        fn add(&self, other: &Self) -> Self {
+            self.clone() + other
+        }
+        fn sub(&self, other: &Self) -> Self {
+            self.clone() - other
+        }
+        fn mul(&self, other: &Self) -> Self {
+            self.clone() * other
+        }
+        fn div(&self, other: &Self) -> Self {
+            self.clone() / other
+        }
+        fn neg(&self) -> Self {
+            -self.clone()
+        }
+        fn sqrt(&self) -> Self {
+            self.clone().sqrt()
+        }
+        fn to_f64(&self) -> (f64, f64) {
+            (self.real().to_f64(), self.imag().to_f64())
+        }
+    }
+}
+
+#[cfg(feature = "dashu")]
+pub use dashu_backend::DashuComplex;
+
+#[cfg(feature = "dashu")]
+mod dashu_backend {
+    use std::fmt;
+    use std::str::FromStr;
+    use dashu_float::{DBig, FBig};
+    use dashu_float::ops::SquareRoot;
+    use dashu_float::round::mode::HalfEven;
+
+    use super::Scalar;
+
+    /// Binary floats rounded to nearest, like MPFR's default.
+    type Real = FBig<HalfEven, 2>;
+
+    /// A complex number as a pair of `dashu` binary floats. Every operation rounds
+    /// to the larger precision of its operands.
+    #[derive(Clone, Debug)]
+    pub struct DashuComplex {
+        pub re: Real,
+        pub im: Real,
+    }
+
+    fn real(precision: u32, x: f64) -> Real {
+        Real::try_from(x).expect("finite").with_precision(precision as usize).value()
+    }
+
+    impl fmt::Display for DashuComplex {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "({} {})", self.re.to_decimal().value(), self.im.to_decimal().value())
+        }
+    }
+
+    impl Scalar for DashuComplex {
+        fn from_f64(precision: u32, re: f64, im: f64) -> Self {
+            DashuComplex { re: real(precision, re), im: real(precision, im) }
+        }
+        fn parse(precision: u32, re: &str, im: &str) -> Option<Self> {
+            let parse = |x: &str| {
+                let decimal = DBig::from_str(x).ok()?;
+                Some(decimal.with_base_and_precision::<2>(precision as usize).value().with_rounding::<HalfEven>())
+            };
+            Some(DashuComplex { re: parse(re)?, im: parse(im)? })
+        }
+        fn add(&self, other: &Self) -> Self {
+            DashuComplex { re: &self.re + &other.re, im: &self.im + &other.im }
+        }
+        fn sub(&self, other: &Self) -> Self {
+            DashuComplex { re: &self.re - &other.re, im: &self.im - &other.im }
+        }
+        fn mul(&self, other: &Self) -> Self {
+            DashuComplex {
+                re: &self.re * &other.re - &self.im * &other.im,
+                im: &self.re * &other.im + &self.im * &other.re,
+            }
+        }
+        fn div(&self, other: &Self) -> Self {
+            let norm = &other.re * &other.re + &other.im * &other.im;
+            DashuComplex {
+                re: (&self.re * &other.re + &self.im * &other.im) / &norm,
+                im: (&self.im * &other.re - &self.re * &other.im) / &norm,
+            }
+        }
+        fn neg(&self) -> Self {
+            DashuComplex { re: -&self.re, im: -&self.im }
+        }
+        fn sqrt(&self) -> Self {
+            // sqrt(x + iy) = sqrt((r + x) / 2) + i sign(y) sqrt((r - x) / 2), r = |x + iy|
+            let r = (&self.re * &self.re + &self.im * &self.im).sqrt();
+            let two = Real::from(2);
+            // rounding can leave r slightly below |x|
+            let half = |x: Real| if x < Real::ZERO { Real::ZERO } else { x / &two };
+            let re = half(&r + &self.re).sqrt();
+            let im = half(&r - &self.re).sqrt();
+            let im = if self.im < Real::ZERO { -im } else { im };
+            DashuComplex { re, im }
+        }
+        fn to_f64(&self) -> (f64, f64) {
+            (self.re.to_f64().value(), self.im.to_f64().value())
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt;
+use std::io::{self, Write};
+use rug::{Float, Rational};
+
+use crate::classify::tolerance;
+use crate::context::RunContext;
+use crate::matrix::C;
+use crate::rho::Generators;
+use crate::stern_brocot::{ExtendedRational, for_each_farey};
+
+/// A grid of `columns` x `rows` samples over the rectangle [x0, x1] x [y0, y1] of the
+/// parameter plane, one at the center of each cell.
+#[derive(Clone, Copy, Debug)]
+pub struct Grid {
+    pub x0: f64,
+    pub y0: f64,
+    pub x1: f64,
+    pub y1: f64,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl Grid {
+    /// The sample in column i and row j; rows go down from y1, as in an image.
+    pub fn point(&self, i: u32, j: u32) -> (f64, f64) {
+        let x = self.x0 + (i as f64 + 0.5) * (self.x1 - self.x0) / self.columns as f64;
+        let y = self.y1 - (j as f64 + 0.5) * (self.y1 - self.y0) / self.rows as f64;
+        (x, y)
+    }
+}
+
+/// How the traces of the primitive words up to a height look at a point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Class {
+    /// Some trace is in [-2, 2] up to the tolerance, so the Bowditch condition fails.
+    Fails,
+    /// No trace is in [-2, 2], but the smallest |tr| is below the threshold.
+    Small,
+    /// Every |tr| is at least the threshold.
+    Bounded,
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Class::Fails => write!(f, "fails"),
+            Class::Small => write!(f, "small"),
+            Class::Bounded => write!(f, "bounded"),
+        }
+    }
+}
+
+/// The class of a point, with the slope of smallest |tr| and the number of slopes
+/// with |tr| at most 2.
+pub struct Verdict {
+    pub class: Class,
+    pub slope: ExtendedRational,
+    pub trace: C,
+    pub min_abs_trace: Float,
+    pub small_traces: usize,
+}
+
+/// Calls `f` with the trace of the primitive word of every slope p/q with |p|, q at most
+/// `max_height` (at least 1), one per conjugacy class up to inverses: a, b, ab and aB, then the
+/// Stern-Brocot words of the positive slopes in a and b and of the negative slopes in
+/// a and B, from the trace recursion.
+pub fn primitive_traces(gens: &Generators, max_height: u64, mut f: impl FnMut(&ExtendedRational, &C)) {
+    let [x, y, z] = gens.trace_coordinates();
+    // tr aB = tr a tr B - tr ab, and tr B = tr b
+    let w = x.clone() * &y - &z;
+    let slope = |p: i64| ExtendedRational::R(Rational::from(p));
+    f(&slope(0), &x);
+    f(&ExtendedRational::Infinity, &y);
+    f(&slope(1), &z);
+    f(&slope(-1), &w);
+    let one = slope(1);
+    let recursion = |u: &C, v: &C, w: &C| u.clone() * v - w;
+    // the word of 1 in `for_each_farey` is a, which is already done
+    for_each_farey(max_height, x.clone(), y.clone(), z, recursion, |q, trace| {
+        if *q != one {
+            f(q, trace);
+        }
+    });
+    for_each_farey(max_height, x, y, w, recursion, |q, trace| {
+        if let ExtendedRational::R(r) = q {
+            if *q != one {
+                f(&ExtendedRational::R(-r.clone()), trace);
+            }
+        }
+    });
+}
+
+/// Classifies a point by the traces of `primitive_traces`, with `threshold` the lower
+/// bound on |tr| asked for. The slopes in `skip` are left out: those whose words are
+/// parabolic throughout the family, such as b in the Maskit slice.
+pub fn bowditch(gens: &Generators, max_height: u64, threshold: &Float, skip: &[ExtendedRational]) -> Verdict {
+    let precision = gens.precision;
+    let tol = tolerance(precision);
+    let two = Float::with_val(precision, 2);
+    let mut fails = false;
+    let mut small_traces = 0;
+    let mut best: Option<(ExtendedRational, C, Float)> = None;
+    primitive_traces(gens, max_height, |q, trace| {
+        if skip.contains(q) {
+            return;
+        }
+        let size = Float::with_val(precision, trace.abs_ref());
+        let real = Float::with_val(precision, trace.imag().abs_ref()) <= tol;
+        if real && Float::with_val(precision, trace.real().abs_ref()) <= Float::with_val(precision, &two + &tol) {
+            fails = true;
+        }
+        if size <= two {
+            small_traces += 1;
+        }
+        if best.as_ref().map_or(true, |(_, _, b)| size < *b) {
+            best = Some((q.clone(), trace.clone(), size));
+        }
+    });
+    let (slope, trace, min_abs_trace) = best.expect("ab and aB are never skipped");
+    let class = if fails {
+        Class::Fails
+    } else if min_abs_trace < *threshold {
+        Class::Small
+    } else {
+        Class::Bounded
+    };
+    Verdict { class, slope, trace, min_abs_trace, small_traces }
+}
+
+pub fn write_csv_header<W: Write>(out: &mut W, context: &RunContext) -> io::Result<()> {
+    context.write_comments(out)?;
+    writeln!(out, "x,y,class,min_abs_trace,slope,small_traces")
+}
+
+pub fn write_csv_row<W: Write>(out: &mut W, x: f64, y: f64, verdict: &Verdict) -> io::Result<()> {
+    writeln!(out, "{},{},{},{},{},{}", x, y, verdict.class, verdict.min_abs_trace.to_f64(), verdict.slope,
+             verdict.small_traces)
+}
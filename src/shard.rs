@@ -0,0 +1,25 @@
+/// The part `index` of `count` of an enumeration: the items whose position in it is
+/// `index` mod `count`. The shards of a run are disjoint and together cover it, and
+/// each one is the same on every machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shard {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl Shard {
+    /// Parses `i/n` with 0 <= i < n.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let (i, n) = input.split_once('/').ok_or("expected i/n")?;
+        let index = i.trim().parse::<usize>().map_err(|_| format!("invalid shard index {}", i))?;
+        let count = n.trim().parse::<usize>().map_err(|_| format!("invalid shard count {}", n))?;
+        if index >= count {
+            return Err(format!("the shard index must be less than the count, got {}/{}", index, count));
+        }
+        Ok(Shard { index, count })
+    }
+
+    pub fn contains(&self, position: usize) -> bool {
+        position % self.count == self.index
+    }
+}
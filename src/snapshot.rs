@@ -0,0 +1,167 @@
+use std::io::{self, Write};
+use rug::{Complex, Float};
+
+use crate::checkpoint::{self, Progress};
+use crate::context::RunContext;
+use crate::cusp::CuspCache;
+use crate::matrix::{C, M};
+use crate::results::{self, Results};
+use crate::rho::Generators;
+
+/// Version of the snapshot format written by `Snapshot::write`, with the same rules as
+/// `results::SCHEMA_VERSION`.
+///
+/// 1. The first version.
+/// 2. Adds the records of a `Progress`, which --checkpoint writes. The checkpoint files
+///    of before had a format 1 of their own, with the same records after the generators,
+///    and read as snapshots of format 1.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// The state of a computation, enough to take it up again in another run or to hand it
+/// to a front end: the representation with every generator, the cusps solved so far, the
+/// outcome of a search, whether it finished or was stopped by its budget, and how far a
+/// long computation got, for --checkpoint and --resume.
+pub struct Snapshot {
+    pub generators: Generators,
+    pub cusps: CuspCache,
+    pub results: Option<Results>,
+    pub progress: Option<Progress>,
+}
+
+fn hex(x: &Float) -> String {
+    x.to_string_radix(16, None)
+}
+
+fn parse_hex(s: &str, precision: u32) -> Result<Float, String> {
+    Float::parse_radix(s, 16)
+        .map(|x| Float::with_val(precision, x))
+        .map_err(|_| format!("invalid hexadecimal number {}", s))
+}
+
+/// The entries of m exactly in hexadecimal, as the real and imaginary parts of m11, m12,
+/// m21 and m22 separated by spaces.
+pub(crate) fn matrix_hex(m: &M<C>) -> String {
+    let entries: Vec<String> = m.0.iter().map(|z| format!("{} {}", hex(z.real()), hex(z.imag()))).collect();
+    entries.join(" ")
+}
+
+/// Parses the eight fields written by `matrix_hex`.
+pub(crate) fn parse_matrix_hex(fields: &[&str], precision: u32) -> Result<M<C>, String> {
+    if fields.len() != 8 {
+        return Err(format!("a matrix takes 8 numbers, got {}", fields.len()));
+    }
+    let entries = fields.chunks(2)
+        .map(|f| Ok(Complex::with_val(precision, (parse_hex(f[0], precision)?, parse_hex(f[1], precision)?))))
+        .collect::<Result<Vec<C>, String>>()?;
+    Ok(M([entries[0].clone(), entries[1].clone(), entries[2].clone(), entries[3].clone()]))
+}
+
+impl Snapshot {
+    /// Whether the snapshot was taken with exactly these generators.
+    pub fn is_for(&self, gens: &Generators) -> bool {
+        let (saved, letters) = (&self.generators, gens.letters());
+        saved.precision == gens.precision
+            && saved.letters() == letters
+            && letters.iter().all(|&c| saved.letter(c) == gens.letter(c))
+    }
+
+    /// Writes the snapshot as lines of space separated fields, with every number written
+    /// exactly in hexadecimal, after the comments of `context`:
+    ///
+    /// ```text
+    /// format 2
+    /// precision <bits>
+    /// generator <name> <re im of m11 m12 m21 m22>   (a, b, then the others)
+    /// cusp <p> <q> <bits> <re> <im>                  (as in a cusp cache)
+    /// word | limit-set | prefix ...                  (as in `Progress::write`)
+    /// results                                        (the rest is a results file)
+    /// ```
+    pub fn write<W: Write>(&self, out: &mut W, context: &RunContext) -> io::Result<()> {
+        writeln!(out, "# repcalc snapshot")?;
+        context.write_comments(out)?;
+        writeln!(out, "format {}", FORMAT_VERSION)?;
+        writeln!(out, "precision {}", self.generators.precision)?;
+        for c in self.generators.letters().into_iter().filter(|c| c.is_ascii_lowercase()) {
+            writeln!(out, "generator {} {}", c, matrix_hex(self.generators.letter(c)))?;
+        }
+        for (bits, c) in &self.cusps.cusps {
+            writeln!(out, "cusp {} {} {} {} {}", c.p, c.q, bits, hex(c.mu.real()), hex(c.mu.imag()))?;
+        }
+        if let Some(progress) = &self.progress {
+            progress.write(out)?;
+        }
+        if let Some(results) = &self.results {
+            writeln!(out, "results")?;
+            results.write(out, context)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a file written by `Snapshot::write`, or by --checkpoint before checkpoints were
+/// snapshots. The generators are restored with their inverses, at the precision of the
+/// file.
+pub fn read(input: &str) -> Result<Snapshot, String> {
+    let mut format: Option<u32> = None;
+    let mut precision: Option<u32> = None;
+    let mut generators: Vec<(char, M<C>)> = Vec::new();
+    let mut cusps = String::new();
+    let mut results = None;
+    let mut progress = None;
+
+    let mut lines = input.lines().enumerate();
+    while let Some((n, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let error = |msg: &str| format!("line {}: {}", n + 1, msg);
+        let number = |s: &str| s.parse::<u32>().map_err(|_| error(&format!("invalid number {}", s)));
+        match (fields[0], format, precision) {
+            ("format", None, _) if fields.len() == 2 => {
+                let version = number(fields[1])?;
+                if version > FORMAT_VERSION {
+                    return Err(error(&format!("format {} is newer than this repcalc reads ({}); upgrade repcalc",
+                                              version, FORMAT_VERSION)));
+                }
+                format = Some(version);
+            }
+            ("format", Some(_), _) => return Err(error("the format is given twice")),
+            (_, None, _) => return Err(error("the format must come first")),
+            ("precision", _, None) if fields.len() == 2 => precision = Some(number(fields[1])?),
+            ("generator", _, Some(bits)) if fields.len() == 10 => {
+                let mut name = fields[1].chars();
+                let (Some(name), None) = (name.next(), name.next()) else {
+                    return Err(error("a generator is named by one letter"));
+                };
+                generators.push((name, parse_matrix_hex(&fields[2..], bits).map_err(|e| error(&e))?));
+            }
+            ("cusp", _, _) => {
+                cusps.push_str(line);
+                cusps.push('\n');
+            }
+            ("results", _, _) if fields.len() == 1 => {
+                let rest: Vec<&str> = lines.by_ref().map(|(_, line)| line).collect();
+                results = Some(results::read(&rest.join("\n")).map_err(|e| format!("results after line {}: {}", n + 1, e))?);
+            }
+            ("generator", _, None) => return Err(error("the precision must come first")),
+            (_, _, Some(bits)) if checkpoint::read_record(&fields, bits, &mut progress).map_err(|e| error(&e))? => {}
+            _ => return Err(error("unrecognized line")),
+        }
+    }
+
+    format.ok_or("missing format")?;
+    let precision = precision.ok_or("missing precision")?;
+    let mut generators = generators.into_iter();
+    let (Some(('a', a)), Some(('b', b))) = (generators.next(), generators.next()) else {
+        return Err("the generators must start with a and b".to_string());
+    };
+    let generators = generators.fold(Generators::from_matrices(precision, a, b), |gens, (name, m)| gens.with_extra(name, m));
+    // the lines counted are those of the cusp records alone
+    let cusps = CuspCache::read(&cusps).map_err(|e| format!("cusp records: {}", e))?;
+    if let Some(progress) = &progress {
+        checkpoint::check(progress)?;
+    }
+    Ok(Snapshot { generators, cusps, results, progress })
+}
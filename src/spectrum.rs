@@ -0,0 +1,47 @@
+use std::cmp::Ordering;
+use rug::Float;
+
+use crate::budget::Budget;
+use crate::classify::complex_length;
+use crate::matrix::C;
+use crate::rho::Generators;
+use crate::shard::Shard;
+use crate::word::conjugacy_classes;
+
+/// A conjugacy class together with the invariants of its image.
+pub struct SpectrumEntry {
+    pub word: String,
+    pub trace: C,
+    /// The real part of the complex length: zero unless the image is loxodromic.
+    pub translation_length: Float,
+}
+
+/// The translation lengths of all conjugacy classes of cyclically reduced length at
+/// most `max_length`, sorted by translation length and then by word length. If the
+/// budget runs out, only the classes evaluated so far (the shorter ones) are returned.
+/// Only the classes in `shard` are evaluated.
+pub fn length_spectrum(gens: &Generators, max_length: usize, shard: Shard, budget: &mut Budget) -> Vec<SpectrumEntry> {
+    let mut entries: Vec<SpectrumEntry> = conjugacy_classes(max_length)
+        .into_iter()
+        .enumerate()
+        .filter(|&(k, _)| shard.contains(k))
+        .take_while(|_| budget.spend())
+        .map(|(_, word)| {
+            let m = gens.eval(&word);
+            let translation_length = complex_length(&m).real().clone();
+            SpectrumEntry { trace: m.trace(), translation_length, word }
+        })
+        .collect();
+    sort(&mut entries);
+    entries
+}
+
+/// Sorts by translation length, then by word length and then alphabetically.
+pub fn sort(entries: &mut [SpectrumEntry]) {
+    entries.sort_by(|x, y| {
+        x.translation_length.partial_cmp(&y.translation_length)
+            .unwrap_or(Ordering::Equal)
+            .then(x.word.len().cmp(&y.word.len()))
+            .then(x.word.cmp(&y.word))
+    });
+}
@@ -0,0 +1,99 @@
+use rug::Float;
+use rug::ops::Pow;
+
+use crate::matrix::{C, M};
+
+/// log|tr m|, or `None` if the trace vanishes.
+pub fn log_abs_trace(m: &M<C>) -> Option<Float> {
+    let abs = Float::with_val(m.trace().prec().0, m.trace().abs_ref());
+    if abs.is_zero() { None } else { Some(abs.ln()) }
+}
+
+/// Sample moments of a list of values. Skewness and excess kurtosis are both
+/// zero for a normal distribution.
+pub struct Summary {
+    pub samples: usize,
+    pub mean: Float,
+    pub std_dev: Float,
+    pub skewness: Float,
+    pub excess_kurtosis: Float,
+    pub min: Float,
+    pub max: Float,
+}
+
+fn range(xs: &[Float], precision: u32) -> (Float, Float) {
+    let min = xs.iter().fold(Float::with_val(precision, f64::INFINITY), |acc, x| acc.min(x));
+    let max = xs.iter().fold(Float::with_val(precision, f64::NEG_INFINITY), |acc, x| acc.max(x));
+    (min, max)
+}
+
+pub fn summarize(xs: &[Float], precision: u32) -> Summary {
+    let n = xs.len() as u32;
+    let mean = xs.iter().fold(Float::with_val(precision, 0), |acc, x| acc + x) / n;
+    let moment = |k: i32| {
+        xs.iter().fold(Float::with_val(precision, 0), |acc, x| {
+            acc + Float::with_val(precision, x - &mean).pow(k)
+        }) / n
+    };
+    let variance = moment(2);
+    let std_dev = variance.clone().sqrt();
+    let skewness = moment(3) / Float::with_val(precision, &std_dev * &variance);
+    let excess_kurtosis = moment(4) / Float::with_val(precision, &variance * &variance) - 3u32;
+    let (min, max) = range(xs, precision);
+    Summary { samples: xs.len(), mean, std_dev, skewness, excess_kurtosis, min, max }
+}
+
+/// Counts of values in `bins` equal-width bins from the minimum to the maximum;
+/// bin i is [edges[i], edges[i + 1]), and the last bin also contains the maximum.
+pub struct Histogram {
+    pub edges: Vec<Float>,
+    pub counts: Vec<usize>,
+}
+
+pub fn histogram(xs: &[Float], bins: usize, precision: u32) -> Histogram {
+    let (min, max) = range(xs, precision);
+    let width = Float::with_val(precision, &max - &min) / bins as u32;
+    let edges = (0..=bins)
+        .map(|i| Float::with_val(precision, &width * i as u32) + &min)
+        .collect();
+    let mut counts = vec![0; bins];
+    for x in xs {
+        let i = if width.is_zero() {
+            0
+        } else {
+            let bin = (Float::with_val(precision, x - &min) / &width).floor();
+            bin.to_f64() as usize
+        };
+        counts[i.min(bins - 1)] += 1;
+    }
+    Histogram { edges, counts }
+}
+
+/// log of the operator norm of a matrix of determinant 1. Its singular values are
+/// s and 1/s, so with f = |m|_F^2 = s^2 + 1/s^2 the norm is s = sqrt((f + sqrt(f^2 - 4)) / 2).
+pub fn log_norm(m: &M<C>) -> Float {
+    let precision = m.0[0].prec().0;
+    let f = m.0.iter().fold(Float::with_val(precision, 0), |acc, x| acc + Float::with_val(precision, x.norm_ref()));
+    let discriminant = Float::with_val(precision, f.clone().square() - 4u32);
+    // rounding can push f slightly below 2 for matrices close to unitary
+    let root = if discriminant < 0 { Float::with_val(precision, 0) } else { discriminant.sqrt() };
+    let s2 = (f + root) / 2u32;
+    s2.ln() / 2u32
+}
+
+/// Least squares fit y = slope x + intercept, returned as (slope, intercept).
+pub fn linear_fit(xs: &[Float], ys: &[Float], precision: u32) -> (Float, Float) {
+    let n = xs.len() as u32;
+    let mean = |v: &[Float]| v.iter().fold(Float::with_val(precision, 0), |acc, x| acc + x) / n;
+    let (mx, my) = (mean(xs), mean(ys));
+    let mut sxy = Float::with_val(precision, 0);
+    let mut sxx = Float::with_val(precision, 0);
+    for (x, y) in xs.iter().zip(ys) {
+        let dx = Float::with_val(precision, x - &mx);
+        sxy += Float::with_val(precision, &dx * Float::with_val(precision, y - &my));
+        sxx += dx.square();
+    }
+    let slope = sxy / sxx;
+    let intercept = my - Float::with_val(precision, &slope * &mx);
+    (slope, intercept)
+}
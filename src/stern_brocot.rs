@@ -0,0 +1,412 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use rug::{Rational, Integer};
+
+use crate::budget::{IterationLimit, LimitReached};
+use crate::matrix::{C, M};
+
+#[derive(Clone)]
+pub enum ExtendedRational {
+    R(Rational),
+    Infinity,
+}
+
+impl PartialEq for ExtendedRational {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ExtendedRational::Infinity, ExtendedRational::Infinity) => true,
+            (ExtendedRational::R(lhs), ExtendedRational::R(rhs)) => lhs == rhs,
+            _ => false,
+        }
+    }
+}
+impl Eq for ExtendedRational {}
+impl PartialOrd for ExtendedRational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for ExtendedRational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (ExtendedRational::Infinity, ExtendedRational::Infinity) => Ordering::Equal,
+            (ExtendedRational::Infinity, ExtendedRational::R(_)) => Ordering::Greater,
+            (ExtendedRational::R(_), ExtendedRational::Infinity) => Ordering::Less,
+            (ExtendedRational::R(lhs), ExtendedRational::R(rhs)) => lhs.cmp(rhs),
+        }
+    }
+}
+
+impl Hash for ExtendedRational {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.numer().hash(state);
+        self.denom().hash(state);
+    }
+}
+
+impl fmt::Display for ExtendedRational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtendedRational::R(r) => write!(f, "{}", r),
+            ExtendedRational::Infinity => write!(f, "inf"),
+        }
+    }
+}
+
+const ZERO: &Integer = & Integer::ZERO;
+
+impl ExtendedRational {
+    pub fn numer(&self) -> &Integer {
+        match self {
+            ExtendedRational::R(r) => r.numer(),
+            ExtendedRational::Infinity => Integer::ONE,
+        }
+    }
+
+    pub fn denom(&self) -> &Integer {
+        match self {
+            ExtendedRational::R(r) => r.denom(),
+            ExtendedRational::Infinity => ZERO,
+        }
+    }
+
+    pub fn mediant(&self, other: &Self) -> Self {
+        let x = self.numer().clone() + other.numer().clone();
+        let y = self.denom().clone() + other.denom().clone();
+        if y.is_zero() {
+            ExtendedRational::Infinity
+        } else {
+            ExtendedRational::R(Rational::from((x, y)))
+        }
+    }
+}
+
+/// The Stern-Brocot word of q, which takes one step per letter, so about p + q steps
+/// for q = p/q; fails once `limit` steps have been taken. The words of 0 and 1 are both
+/// a, and that of infinity is b.
+pub fn stern_brocot_word(q: ExtendedRational, a: M<C>, b: M<C>, limit: IterationLimit) -> Result<M<C>, LimitReached> {
+    stern_brocot(q, a, b, M::mul, limit)
+}
+
+/// The word in {a,b} that `stern_brocot_word` multiplies out.
+pub fn stern_brocot_letters(q: ExtendedRational, limit: IterationLimit) -> Result<String, LimitReached> {
+    stern_brocot(q, "a".to_string(), "b".to_string(), |x, y| x + &y, limit)
+}
+
+fn stern_brocot<T: Clone>(q: ExtendedRational, a: T, b: T, mul: impl Fn(T, T) -> T,
+                          limit: IterationLimit) -> Result<T, LimitReached> {
+    match &q {
+        ExtendedRational::Infinity => { return Ok(b) },
+        ExtendedRational::R(x) => {
+            // 0 is where the descent starts, and the word of 1 is a, not ab
+            if x.eq(Rational::ONE) || x.is_zero() {
+                return Ok(a);
+            }
+        }
+    }
+
+    let mut low = ExtendedRational::R(Rational::ZERO.clone());
+    let mut high = ExtendedRational::Infinity;
+    let mut words = (a, b);
+    // the moves not yet applied to the words: how many, and whether to the right
+    let mut run = (0, true);
+
+    let mut iterations = 0;
+    loop {
+        iterations += 1;
+        limit.check("the Stern-Brocot descent", iterations)?;
+        let med = low.mediant(&high);
+        let right = match med.cmp(&q) {
+            // q is in (med, high)
+            Ordering::Less => true,
+            // q is in (low, med)
+            Ordering::Greater => false,
+            // finished
+            Ordering::Equal => {
+                let (low_m, high_m) = apply_run(words, run, &mul);
+                return Ok(mul(low_m, high_m));
+            }
+        };
+        if right != run.1 {
+            words = apply_run(words, run, &mul);
+            run = (0, right);
+        }
+        run.0 += 1;
+        if right {
+            low = med;
+        } else {
+            high = med;
+        }
+    }
+}
+
+/// The words (X, Y) of the ends of an interval of the Stern-Brocot tree after `steps`
+/// moves to the same side: (X Y^steps, Y) to the right and (X, X^steps Y) to the left.
+/// The powers are multiplied out on the right, which for letters appends to the string
+/// they started from, so a run costs as much as the letters it adds rather than a copy
+/// of the whole word per move.
+fn apply_run<T: Clone>((x, y): (T, T), (steps, right): (u64, bool), mul: &impl Fn(T, T) -> T) -> (T, T) {
+    if steps == 0 {
+        return (x, y);
+    }
+    if right {
+        let x = (0..steps).fold(x, |x, _| mul(x, y.clone()));
+        (x, y)
+    } else {
+        let power = (1..steps).fold(x.clone(), |power, _| mul(power, x.clone()));
+        let y = mul(power, y);
+        (x, y)
+    }
+}
+
+/// The values of the Stern-Brocot words of the nodes visited so far, for evaluating
+/// the words of many slopes with the same generators. The paths down the tree to two
+/// slopes share their nodes down to where they part, and the word of a node is the
+/// product of the words of its two neighbors above it, so each node is multiplied out
+/// only the first time a path passes through it. Evaluating every slope with p, q at
+/// most N this way takes one multiplication per node, like `for_each_farey`.
+pub struct SternBrocotCache<T> {
+    /// The word of each node as a parent, including 0/1 (a) and 1/0 (b); that of 1/1 is ab.
+    words: HashMap<ExtendedRational, T>,
+}
+
+impl<T: Clone + PartialEq> SternBrocotCache<T> {
+    pub fn new(a: T, b: T) -> Self {
+        let mut words = HashMap::new();
+        words.insert(ExtendedRational::R(Rational::ZERO.clone()), a);
+        words.insert(ExtendedRational::Infinity, b);
+        SternBrocotCache { words }
+    }
+
+    /// Whether the cache holds words in the generators a and b.
+    pub fn is_for(&self, a: &T, b: &T) -> bool {
+        self.words[&ExtendedRational::R(Rational::ZERO.clone())] == *a
+            && self.words[&ExtendedRational::Infinity] == *b
+    }
+
+    /// The number of nodes whose words are known, counting 0/1 and 1/0.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// The same value as `stern_brocot_word` (or `stern_brocot_letters`), multiplying only at nodes that no earlier call has passed through. It takes as
+    /// many steps, and fails after `limit` of them in the same way.
+    pub fn word(&mut self, q: &ExtendedRational, mul: impl Fn(T, T) -> T,
+                limit: IterationLimit) -> Result<T, LimitReached> {
+        let one = ExtendedRational::R(Rational::ONE.clone());
+        let a = ExtendedRational::R(Rational::ZERO.clone());
+        if *q == one || *q == a || *q == ExtendedRational::Infinity {
+            // the word of 1 is a, not ab
+            return Ok(self.words[if *q == one { &a } else { q }].clone());
+        }
+        let mut low = a;
+        let mut high = ExtendedRational::Infinity;
+        let mut iterations = 0;
+        loop {
+            iterations += 1;
+            limit.check("the Stern-Brocot descent", iterations)?;
+            let med = low.mediant(&high);
+            if !self.words.contains_key(&med) {
+                let value = mul(self.words[&low].clone(), self.words[&high].clone());
+                self.words.insert(med.clone(), value);
+            }
+            match med.cmp(q) {
+                Ordering::Less => low = med,
+                Ordering::Greater => high = med,
+                Ordering::Equal => return Ok(self.words[&med].clone()),
+            }
+        }
+    }
+}
+
+/// The trace of the Stern-Brocot word of q from tr a, tr b and tr ab alone. Along
+/// the path the pair of words (X, Y) becomes (X, XY) or (XY, Y), and the traces
+/// follow from tr(X XY) = tr X tr XY - tr Y and tr(XY Y) = tr XY tr Y - tr X, so each
+/// step costs one multiplication and one subtraction. Like `stern_brocot_word` it
+/// fails after `limit` steps, and the trace of 0 and of 1 is tr a.
+pub fn stern_brocot_trace<T: Clone>(q: &ExtendedRational, ta: T, tb: T, tab: T,
+                                    mul: impl Fn(&T, &T) -> T, sub: impl Fn(&T, &T) -> T,
+                                    limit: IterationLimit) -> Result<T, LimitReached> {
+    match q {
+        ExtendedRational::Infinity => return Ok(tb),
+        ExtendedRational::R(x) if x == Rational::ONE || x.is_zero() => return Ok(ta),
+        _ => {}
+    }
+    let mut low = ExtendedRational::R(Rational::ZERO.clone());
+    let mut high = ExtendedRational::Infinity;
+    // the traces of X, Y and XY, where X and Y are the words of low and high
+    let (mut tx, mut ty, mut txy) = (ta, tb, tab);
+    let mut iterations = 0;
+    loop {
+        iterations += 1;
+        limit.check("the Stern-Brocot trace recursion", iterations)?;
+        let med = low.mediant(&high);
+        if med < *q {
+            low = med;
+            let next = sub(&mul(&txy, &ty), &tx);
+            tx = std::mem::replace(&mut txy, next);
+        } else if *q < med {
+            high = med;
+            let next = sub(&mul(&tx, &txy), &ty);
+            ty = std::mem::replace(&mut txy, next);
+        } else {
+            return Ok(txy);
+        }
+    }
+}
+
+/// Calls `f` with every positive slope p/q of the Stern-Brocot tree with p and q at most
+/// `max_height`, in increasing order, and the value of its word. The values are built
+/// down the tree from those of a, b and ab: the word of a mediant is U V for the words
+/// U, V of its parents, and `product(u, v, w)` must give the value of U V from those of
+/// U, V and W = U^-1 V. For traces this is tr U tr V - tr W, and for matrices U V.
+/// As in `stern_brocot_word`, the word of 1 is a rather than ab.
+pub fn for_each_farey<T: Clone>(max_height: u64, a: T, b: T, ab: T, product: impl Fn(&T, &T, &T) -> T,
+                                mut f: impl FnMut(&ExtendedRational, &T)) {
+    enum Step<T> {
+        /// The subtree between two neighbors, with their values and that of their mediant
+        Visit(ExtendedRational, ExtendedRational, T, T, T),
+        Emit(ExtendedRational, T),
+    }
+    let within = |q: &ExtendedRational| *q.numer() <= max_height && *q.denom() <= max_height;
+    let low = ExtendedRational::R(Rational::ZERO.clone());
+    let high = ExtendedRational::Infinity;
+    if !within(&low.mediant(&high)) {
+        return;
+    }
+    let mut stack = vec![Step::Visit(low, high, a, b, ab)];
+    while let Some(step) = stack.pop() {
+        let (low, high, x, y, xy) = match step {
+            Step::Visit(low, high, x, y, xy) => (low, high, x, y, xy),
+            Step::Emit(q, value) => {
+                f(&q, &value);
+                continue;
+            }
+        };
+        let med = low.mediant(&high);
+        let left = within(&low.mediant(&med)).then(|| product(&x, &xy, &y));
+        // pushed in reverse: the left subtree, then the mediant, then the right subtree
+        if within(&med.mediant(&high)) {
+            let right = product(&xy, &y, &x);
+            stack.push(Step::Visit(med.clone(), high, xy.clone(), y, right));
+        }
+        let value = if med == ExtendedRational::R(Rational::ONE.clone()) { x.clone() } else { xy.clone() };
+        stack.push(Step::Emit(med.clone(), value));
+        if let Some(left) = left {
+            stack.push(Step::Visit(low, med, x, xy, left));
+        }
+    }
+}
+
+/// A continued fraction [a0; a1, ..., an, (p1, ..., pk)] whose terms p1, ..., pk
+/// repeat forever. Quadratic irrationals are exactly the ones with a period.
+#[derive(Clone, Debug)]
+pub struct ContinuedFraction {
+    pub terms: Vec<u64>,
+    pub period: Vec<u64>,
+}
+
+impl ContinuedFraction {
+    /// Parses `[a0; a1, a2, ...]`, where the last entry may be a parenthesized
+    /// period such as `(1, 2)`. The brackets are optional.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let s = input.trim();
+        let s = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')).unwrap_or(s);
+        let (s, period) = match s.find('(') {
+            Some(i) => {
+                let period = s[i + 1..].trim().strip_suffix(')')
+                    .ok_or("the period must close with ) at the end")?;
+                (&s[..i], parse_terms(period)?)
+            }
+            None => (s, vec![]),
+        };
+        let terms = parse_terms(&s.replace(';', ","))?;
+        if terms.is_empty() && period.is_empty() {
+            return Err("expected at least one term".to_string());
+        }
+        let cf = ContinuedFraction { terms, period };
+        if cf.expand(2).iter().skip(1).any(|&t| t == 0) {
+            return Err("every term after the first must be positive".to_string());
+        }
+        Ok(cf)
+    }
+
+    /// The terms with the period repeated `periods` times.
+    pub fn expand(&self, periods: usize) -> Vec<u64> {
+        let mut terms = self.terms.clone();
+        for _ in 0..periods {
+            terms.extend_from_slice(&self.period);
+        }
+        terms
+    }
+}
+
+fn parse_terms(s: &str) -> Result<Vec<u64>, String> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| t.parse::<u64>().map_err(|_| format!("invalid term {}", t)))
+        .collect()
+}
+
+/// The value of a finite continued fraction; the empty one is infinity.
+pub fn convergent(terms: &[u64]) -> ExtendedRational {
+    // p_n / q_n from p_n = a_n p_{n-1} + p_{n-2}, starting from 1/0 and 0/1
+    let (mut p, mut p_prev) = (Integer::from(1), Integer::from(0));
+    let (mut q, mut q_prev) = (Integer::from(0), Integer::from(1));
+    for &a in terms {
+        let p_next = Integer::from(&p * a) + &p_prev;
+        let q_next = Integer::from(&q * a) + &q_prev;
+        p_prev = std::mem::replace(&mut p, p_next);
+        q_prev = std::mem::replace(&mut q, q_next);
+    }
+    if q.is_zero() {
+        ExtendedRational::Infinity
+    } else {
+        ExtendedRational::R(Rational::from((p, q)))
+    }
+}
+
+/// The Stern-Brocot word of the finite continued fraction [a0; a1, ..., an],
+/// computed from its runs of partial quotients rather than one mediant at a time. It is
+/// the word `stern_brocot_word` gives for the value, whichever of its two expansions is
+/// given. It takes a0 + a1 + ... + an steps and fails once `limit` steps have been taken.
+pub fn continued_fraction_word(terms: &[u64], a: M<C>, b: M<C>, limit: IterationLimit) -> Result<M<C>, LimitReached> {
+    continued_fraction(terms, a, b, M::mul, limit)
+}
+
+/// The word in {a,b} that `continued_fraction_word` multiplies out.
+pub fn continued_fraction_letters(terms: &[u64], limit: IterationLimit) -> Result<String, LimitReached> {
+    continued_fraction(terms, "a".to_string(), "b".to_string(), |x, y| x + &y, limit)
+}
+
+fn continued_fraction<T: Clone>(terms: &[u64], a: T, b: T, mul: impl Fn(T, T) -> T,
+                                limit: IterationLimit) -> Result<T, LimitReached> {
+    match terms {
+        [] => return Ok(b),
+        // 0 = [0], and 1 = [1] = [0; 1], whose word is a rather than ab
+        [0] | [1] | [0, 1] => return Ok(a),
+        _ => {}
+    }
+    // the path to [a0; a1, ..., an] goes a0 steps right, a1 steps left, and so on,
+    // except that the last run is one step shorter
+    let mut words = (a, b);
+    let last = terms.len() - 1;
+    let mut iterations = 0u64;
+    for (i, &t) in terms.iter().enumerate() {
+        let steps = if i == last { t - 1 } else { t };
+        // the limit trips at the step it would in a walk one step at a time, before the
+        // words of the run are multiplied out
+        limit.check("the continued fraction descent", iterations.saturating_add(steps))?;
+        iterations += steps;
+        words = apply_run(words, (steps, i % 2 == 0), &mul);
+    }
+    let (low_m, high_m) = words;
+    Ok(mul(low_m, high_m))
+}
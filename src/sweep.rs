@@ -0,0 +1,218 @@
+use std::fmt;
+use rug::{Complex, Float};
+
+use crate::budget::Budget;
+use crate::classify::{Classification, classify, tolerance};
+use crate::matrix::{C, M};
+use crate::rho::Generators;
+use crate::shard::Shard;
+use crate::word::{inverse_letter, primitive_words};
+
+/// A property that every word in a sweep should have.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    /// No reduced word is elliptic.
+    NoElliptic,
+    /// No primitive word has trace in [-2, 2], the first of Bowditch's conditions.
+    Bowditch,
+    /// Every reduced word has determinant 1 up to the tolerance, relative to the
+    /// size of its entries.
+    Determinant,
+}
+
+impl Check {
+    /// The inverse of `Display`.
+    pub fn parse(name: &str) -> Option<Check> {
+        [Check::NoElliptic, Check::Bowditch, Check::Determinant].into_iter().find(|c| c.to_string() == name)
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Check::NoElliptic => write!(f, "no_elliptic"),
+            Check::Bowditch => write!(f, "bowditch"),
+            Check::Determinant => write!(f, "determinant"),
+        }
+    }
+}
+
+pub struct CheckResult {
+    pub check: Check,
+    pub checked: usize,
+    pub failures: usize,
+    pub first_failure: Option<String>,
+}
+
+impl CheckResult {
+    fn new(check: Check) -> Self {
+        CheckResult { check, checked: 0, failures: 0, first_failure: None }
+    }
+
+    fn record(&mut self, word: &str, ok: bool) {
+        self.checked += 1;
+        if !ok {
+            self.failures += 1;
+            if self.first_failure.is_none() {
+                self.first_failure = Some(word.to_string());
+            }
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.failures == 0
+    }
+}
+
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.first_failure {
+            None => write!(f, "pass ({} words)", self.checked),
+            Some(word) => write!(f, "fail ({} of {} words, first {})", self.failures, self.checked, word),
+        }
+    }
+}
+
+/// Calls `f` on every nonempty freely reduced word of length at most `max_length` in
+/// all the generators and its matrix, multiplying out each prefix only once. Each word is one node of
+/// the budget, and the enumeration stops when the budget runs out.
+pub fn for_each_reduced_word(gens: &Generators, max_length: usize, budget: &mut Budget,
+                             mut f: impl FnMut(&str, &M<C>)) {
+    for_each_reduced_word_from(gens, max_length, None, budget, |word, prefixes| {
+        f(word, prefixes.last().expect("the word is nonempty"))
+    });
+}
+
+/// Where an enumeration of reduced words stands: the last word visited and the matrices
+/// of its nonempty prefixes, the word itself last. The words still to come are the
+/// descendants of the word in the tree of words and the later siblings of it and of its
+/// prefixes, so this is all it takes to carry on.
+#[derive(Clone)]
+pub struct Frontier {
+    pub word: String,
+    pub prefixes: Vec<M<C>>,
+}
+
+impl Frontier {
+    /// The frontier of an enumeration that has visited `word` last, with the prefixes
+    /// multiplied out in the same order as by the enumeration, so that they come out the same.
+    pub fn after(gens: &Generators, word: &str) -> Self {
+        let mut prefixes: Vec<M<C>> = Vec::with_capacity(word.len());
+        for c in word.chars() {
+            let m = match prefixes.last() {
+                Some(m) => m.clone().mul(gens.letter(c).clone()),
+                None => gens.letter(c).clone(),
+            };
+            prefixes.push(m);
+        }
+        Frontier { word: word.to_string(), prefixes }
+    }
+}
+
+/// Like `for_each_reduced_word`, but carries on after `start` (or starts from the
+/// beginning), in the same order, and passes `f` the matrices of all the prefixes of the
+/// word, those of the `Frontier` it has reached.
+pub fn for_each_reduced_word_from(gens: &Generators, max_length: usize, start: Option<&Frontier>,
+                                  budget: &mut Budget, mut f: impl FnMut(&str, &[M<C>])) {
+    // `resume` is the rest of the starting word below `word`, whose subtree the
+    // enumeration is still in: its first letter was visited already, along with the
+    // letters before it
+    fn visit(gens: &Generators, word: &mut String, prefixes: &mut Vec<M<C>>, resume: Option<(&str, &[M<C>])>,
+             max_length: usize, budget: &mut Budget, f: &mut impl FnMut(&str, &[M<C>])) {
+        if word.len() == max_length {
+            return;
+        }
+        let mut resume = resume.and_then(|(rest, matrices)| Some((rest.chars().next()?, &rest[1..], matrices)));
+        for c in gens.letters() {
+            if word.chars().last().map(inverse_letter) == Some(c) {
+                continue;
+            }
+            if let Some((next, rest, matrices)) = resume {
+                if c != next {
+                    continue;
+                }
+                resume = None;
+                word.push(c);
+                prefixes.push(matrices[0].clone());
+                let below = if rest.is_empty() { None } else { Some((rest, &matrices[1..])) };
+                visit(gens, word, prefixes, below, max_length, budget, f);
+                prefixes.pop();
+                word.pop();
+                continue;
+            }
+            if !budget.spend() {
+                return;
+            }
+            let m = match prefixes.last() {
+                Some(m) => m.clone().mul(gens.letter(c).clone()),
+                None => gens.letter(c).clone(),
+            };
+            word.push(c);
+            prefixes.push(m);
+            f(word, prefixes);
+            visit(gens, word, prefixes, None, max_length, budget, f);
+            prefixes.pop();
+            word.pop();
+        }
+    }
+    let resume = start.map(|s| (s.word.as_str(), s.prefixes.as_slice()));
+    visit(gens, &mut String::new(), &mut Vec::new(), resume, max_length, budget, &mut f);
+}
+
+fn has_real_trace_in_interval(m: &M<C>, tol: &Float) -> bool {
+    let t = m.trace();
+    t.imag().clone().abs() <= *tol && t.real().clone().abs() <= Float::with_val(tol.prec(), tol + 2u32)
+}
+
+/// |det m - 1| is compared with the tolerance relative to |ad| + |bc|, since that
+/// much is lost to cancellation in computing the determinant.
+fn det_is_one(m: &M<C>, one: &C, tol: &Float) -> bool {
+    let precision = tol.prec();
+    let [a, b, c, d] = &m.0;
+    let abs = |x: &C| Float::with_val(precision, x.abs_ref());
+    let scale = Float::with_val(precision, abs(a) * abs(d)) + Float::with_val(precision, abs(b) * abs(c));
+    let scale = if scale < 1 { Float::with_val(precision, 1) } else { scale };
+    let error = m.det() - one;
+    abs(&error) <= Float::with_val(precision, tol * &scale)
+}
+
+/// Runs the given checks over all reduced (or, for `Bowditch`, primitive) words
+/// of length at most `max_length`, or until the budget runs out. Only the words in
+/// `shard` of each enumeration are checked.
+pub fn sweep(gens: &Generators, max_length: usize, checks: &[Check], shard: Shard,
+             budget: &mut Budget) -> Vec<CheckResult> {
+    let precision = gens.precision;
+    let tol = tolerance(precision);
+    let one = Complex::with_val(precision, 1);
+    let mut results: Vec<CheckResult> = checks.iter().map(|&c| CheckResult::new(c)).collect();
+
+    let reduced_checks = checks.iter().any(|&c| c != Check::Bowditch);
+    if reduced_checks {
+        let mut position = 0;
+        for_each_reduced_word(gens, max_length, budget, |word, m| {
+            position += 1;
+            if !shard.contains(position - 1) {
+                return;
+            }
+            for result in results.iter_mut() {
+                match result.check {
+                    Check::NoElliptic => {
+                        let elliptic = matches!(classify(m, precision), Classification::Elliptic { .. });
+                        result.record(word, !elliptic);
+                    }
+                    Check::Determinant => result.record(word, det_is_one(m, &one, &tol)),
+                    Check::Bowditch => {}
+                }
+            }
+        });
+    }
+    for result in results.iter_mut().filter(|r| r.check == Check::Bowditch) {
+        for (_, word) in primitive_words(max_length).into_iter().enumerate().filter(|&(k, _)| shard.contains(k)) {
+            if !budget.spend() {
+                break;
+            }
+            result.record(&word, !has_real_trace_in_interval(&gens.eval(&word), &tol));
+        }
+    }
+    results
+}
@@ -0,0 +1,90 @@
+use rug::Complex;
+
+use crate::matrix::C;
+
+/// A move of the Markov trace map on the trace coordinates (x, y, z) = (tr X, tr Y, tr XY)
+/// of a pair of generators (X, Y).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Move {
+    /// (x, y, z) -> (x, z, xz - y), for (X, Y) -> (X, XY)
+    R,
+    /// (x, y, z) -> (z, y, yz - x), for (X, Y) -> (XY, Y)
+    L,
+    /// (x, y, z) -> (x, y, xy - z), for XY -> X Y^-1, which keeps X and Y
+    F,
+}
+
+impl Move {
+    /// Parses a sequence of moves such as `RRL`.
+    pub fn parse_all(input: &str) -> Result<Vec<Move>, String> {
+        let moves: Vec<Move> = input.chars()
+            .map(|c| match c {
+                'R' => Ok(Move::R),
+                'L' => Ok(Move::L),
+                'F' => Ok(Move::F),
+                _ => Err(format!("invalid move {}; expected R, L or F", c)),
+            })
+            .collect::<Result<_, _>>()?;
+        if moves.is_empty() {
+            return Err("expected at least one move".to_string());
+        }
+        Ok(moves)
+    }
+
+    /// The trace coordinates after the move. Each costs one multiplication and one
+    /// subtraction, by tr(X XY) = tr X tr XY - tr Y and its variants.
+    pub fn apply(self, [x, y, z]: [C; 3]) -> [C; 3] {
+        match self {
+            Move::R => {
+                let next = x.clone() * &z - y;
+                [x, z, next]
+            }
+            Move::L => {
+                let next = y.clone() * &z - x;
+                [z, y, next]
+            }
+            Move::F => {
+                let next = x.clone() * &y - z;
+                [x, y, next]
+            }
+        }
+    }
+}
+
+/// x^2 + y^2 + z^2 - xyz - 2, the trace of the commutator [X, Y] by the Fricke identity.
+/// Every move keeps it, so its drift along an orbit measures the rounding error.
+pub fn commutator_trace([x, y, z]: &[C; 3]) -> C {
+    let xyz = x.clone() * y * z;
+    x.clone().square() + y.clone().square() + z.clone().square() - xyz - 2u32
+}
+
+/// tr W^n = 2 T_n(x/2) from x = tr W alone, where T_n is the Chebyshev polynomial, without
+/// forming W^n. The traces s_k = tr W^k satisfy s_2k = s_k^2 - 2 and s_2k+1 = s_k s_k+1 - x
+/// (both instances of tr(UV) = tr U tr V - tr(U V^-1)), so the pair (s_k, s_k+1) climbs the
+/// bits of n from the top in 2 log2(n) multiplications of numbers, with each step adding
+/// one rounding rather than the growing cancellation of a matrix product.
+pub fn power_trace(x: &C, n: u64) -> C {
+    let precision = x.prec().0;
+    let mut s = Complex::with_val(precision, 2);
+    let mut next = x.clone();
+    for bit in (0..u64::BITS - n.leading_zeros()).rev() {
+        let middle = s.clone() * &next - x;
+        if n >> bit & 1 == 1 {
+            (s, next) = (middle, next.square() - 2u32);
+        } else {
+            (s, next) = (s.square() - 2u32, middle);
+        }
+    }
+    s
+}
+
+/// Calls `f` with the step number and the trace coordinates at each of `steps` + 1
+/// points of the orbit of `start`, applying `moves` in order and cyclically.
+pub fn orbit(start: [C; 3], moves: &[Move], steps: usize, mut f: impl FnMut(usize, &[C; 3])) {
+    let mut t = start;
+    f(0, &t);
+    for (n, m) in (1..=steps).zip(moves.iter().cycle()) {
+        t = m.apply(t);
+        f(n, &t);
+    }
+}
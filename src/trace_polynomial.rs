@@ -0,0 +1,114 @@
+use std::fmt;
+use rug::Integer;
+
+use crate::budget::{IterationLimit, LimitReached};
+use crate::stern_brocot::{ExtendedRational, stern_brocot_trace};
+
+/// A Gaussian integer re + im i.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GaussianInteger {
+    pub re: Integer,
+    pub im: Integer,
+}
+
+impl GaussianInteger {
+    pub fn new(re: i32, im: i32) -> Self {
+        GaussianInteger { re: Integer::from(re), im: Integer::from(im) }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.re.is_zero() && self.im.is_zero()
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        GaussianInteger { re: Integer::from(&self.re + &other.re), im: Integer::from(&self.im + &other.im) }
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        GaussianInteger { re: Integer::from(&self.re - &other.re), im: Integer::from(&self.im - &other.im) }
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        let re = Integer::from(&self.re * &other.re) - Integer::from(&self.im * &other.im);
+        let im = Integer::from(&self.re * &other.im) + Integer::from(&self.im * &other.re);
+        GaussianInteger { re, im }
+    }
+}
+
+impl fmt::Display for GaussianInteger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.re.is_zero(), self.im.is_zero()) {
+            (_, true) => write!(f, "{}", self.re),
+            (true, false) => write!(f, "{}i", self.im),
+            (false, false) if self.im < 0 => write!(f, "{}{}i", self.re, self.im),
+            (false, false) => write!(f, "{}+{}i", self.re, self.im),
+        }
+    }
+}
+
+/// A polynomial with Gaussian integer coefficients, constant term first and with
+/// no trailing zero coefficients.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Polynomial(pub Vec<GaussianInteger>);
+
+impl Polynomial {
+    pub fn new(mut coefficients: Vec<GaussianInteger>) -> Self {
+        while coefficients.last().is_some_and(|c| c.is_zero()) {
+            coefficients.pop();
+        }
+        Polynomial(coefficients)
+    }
+
+    /// The degree, or None for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.0.len().checked_sub(1)
+    }
+
+    pub fn leading_coefficient(&self) -> Option<&GaussianInteger> {
+        self.0.last()
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        if self.0.is_empty() || other.0.is_empty() {
+            return Polynomial(vec![]);
+        }
+        let mut out = vec![GaussianInteger::new(0, 0); self.0.len() + other.0.len() - 1];
+        for (i, x) in self.0.iter().enumerate() {
+            for (j, y) in other.0.iter().enumerate() {
+                out[i + j] = out[i + j].add(&x.mul(y));
+            }
+        }
+        Polynomial::new(out)
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        let zero = GaussianInteger::new(0, 0);
+        let n = self.0.len().max(other.0.len());
+        Polynomial::new((0..n).map(|k| {
+            self.0.get(k).unwrap_or(&zero).sub(other.0.get(k).unwrap_or(&zero))
+        }).collect())
+    }
+}
+
+/// The one-parameter families whose traces are polynomials in the parameter.
+#[derive(Clone, Copy, Debug)]
+pub enum Family {
+    /// `rho::maskit`: tr a = -i mu, tr b = 2, tr ab = -i mu - 2i.
+    Maskit,
+    /// `rho::riley`: tr a = tr b = 2, tr ab = 2 + rho.
+    Riley,
+}
+
+/// The trace of the Stern-Brocot word of q as a polynomial in the parameter of the
+/// family, computed by the trace recursion without multiplying out any matrices.
+/// Its degree bounds the number of parameters where the word has a given trace,
+/// such as the cusps where it is parabolic. q must be positive.
+pub fn trace_polynomial(q: &ExtendedRational, family: Family, limit: IterationLimit) -> Result<Polynomial, LimitReached> {
+    let constant = |re, im| Polynomial::new(vec![GaussianInteger::new(re, im)]);
+    let linear = |c: (i32, i32), x: (i32, i32)| Polynomial::new(vec![GaussianInteger::new(c.0, c.1), GaussianInteger::new(x.0, x.1)]);
+    let (ta, tb, tab) = match family {
+        Family::Maskit => (linear((0, 0), (0, -1)), constant(2, 0), linear((0, -2), (0, -1))),
+        Family::Riley => (constant(2, 0), constant(2, 0), linear((2, 0), (1, 0))),
+    };
+    stern_brocot_trace(q, ta, tb, tab, Polynomial::mul, Polynomial::sub, limit)
+}
@@ -0,0 +1,226 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use rand::Rng;
+
+use crate::budget::{IterationLimit, LimitReached};
+
+pub const LETTERS: [char; 4] = ['a', 'b', 'A', 'B'];
+
+/// The inverse of a generator letter: 'a' <-> 'A', 'b' <-> 'B'.
+pub fn inverse_letter(c: char) -> char {
+    if c.is_ascii_lowercase() {
+        c.to_ascii_uppercase()
+    } else {
+        c.to_ascii_lowercase()
+    }
+}
+
+/// Cancel adjacent inverse pairs (aA, Aa, bB, Bb) until none remain.
+pub fn free_reduce(word: &str) -> String {
+    let mut stack: Vec<char> = Vec::with_capacity(word.len());
+    for c in word.chars() {
+        if stack.last() == Some(&inverse_letter(c)) {
+            stack.pop();
+        } else {
+            stack.push(c);
+        }
+    }
+    stack.into_iter().collect()
+}
+
+/// Freely reduce, then cancel inverse pairs between the first and last letters,
+/// i.e. replace the word by a shortest representative of its conjugacy class.
+pub fn cyclic_reduce(word: &str) -> String {
+    let reduced: Vec<char> = free_reduce(word).chars().collect();
+    let mut start = 0;
+    let mut end = reduced.len();
+    while end - start >= 2 && reduced[end - 1] == inverse_letter(reduced[start]) {
+        start += 1;
+        end -= 1;
+    }
+    reduced[start..end].iter().collect()
+}
+
+pub fn is_cyclically_reduced(word: &str) -> bool {
+    match (word.chars().next(), word.chars().last()) {
+        (Some(first), Some(last)) => word.len() == 1 || last != inverse_letter(first),
+        _ => true,
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// The Stern-Brocot word of the slope p/q, the same letters as
+/// `stern_brocot::stern_brocot_letters` but with machine integers, so that it is
+/// available without `rug`: a for 0/1 and 1/1, b for 1/0, and otherwise the word of the
+/// mediant at which the descent down the Stern-Brocot tree reaches p/q. Like it, it fails
+/// after `limit` steps.
+pub fn slope_letters(p: u64, q: u64, limit: IterationLimit) -> Result<String, LimitReached> {
+    if q == 0 {
+        return Ok("b".to_string());
+    }
+    let gcd = gcd(p, q);
+    let (p, q) = ((p / gcd) as u128, (q / gcd) as u128);
+    if p == q || p == 0 {
+        return Ok("a".to_string());
+    }
+    let (mut low, mut high) = ((0u128, 1u128), (1u128, 0u128));
+    let (mut low_word, mut high_word) = ("a".to_string(), "b".to_string());
+    // moves to the left not yet applied, which prepend the low word to the high one and
+    // are made all at once, so that a run copies the high word once
+    let mut left = 0;
+    let mut iterations = 0;
+    loop {
+        iterations += 1;
+        limit.check("the Stern-Brocot descent", iterations)?;
+        let med = (low.0 + high.0, low.1 + high.1);
+        let order = (med.0 * q).cmp(&(p * med.1));
+        if order != Ordering::Greater && left > 0 {
+            high_word = low_word.repeat(left) + &high_word;
+            left = 0;
+        }
+        match order {
+            Ordering::Less => {
+                low = med;
+                low_word.push_str(&high_word);
+            }
+            Ordering::Greater => {
+                high = med;
+                left += 1;
+            }
+            Ordering::Equal => return Ok(low_word + &high_word),
+        }
+    }
+}
+
+/// A uniform random unreduced word of length n.
+pub fn random_word<R: Rng>(rng: &mut R, n: usize) -> String {
+    (0..n).map(|_| LETTERS[rng.gen_range(0usize..4)]).collect()
+}
+
+/// A uniform random freely reduced word of length n: each letter is chosen
+/// uniformly among the three that do not cancel the previous one.
+pub fn random_reduced_word<R: Rng>(rng: &mut R, n: usize) -> String {
+    let mut word = String::with_capacity(n);
+    let mut prev: Option<char> = None;
+    for _ in 0..n {
+        let c = loop {
+            let c = LETTERS[rng.gen_range(0usize..4)];
+            if prev.map(inverse_letter) != Some(c) {
+                break c;
+            }
+        };
+        word.push(c);
+        prev = Some(c);
+    }
+    word
+}
+
+/// A uniform random cyclically reduced word of length n, by rejection sampling.
+pub fn random_cyclically_reduced_word<R: Rng>(rng: &mut R, n: usize) -> String {
+    loop {
+        let word = random_reduced_word(rng, n);
+        if is_cyclically_reduced(&word) {
+            return word;
+        }
+    }
+}
+
+/// The least cyclic rotation of a word. The rotations of a cyclically reduced word
+/// are exactly the cyclically reduced words conjugate to it, so this picks a
+/// canonical representative of its conjugacy class.
+pub fn canonical_rotation(word: &str) -> String {
+    let letters: Vec<char> = word.chars().collect();
+    (0..letters.len().max(1))
+        .map(|i| letters[i..].iter().chain(&letters[..i]).collect::<String>())
+        .min()
+        .unwrap()
+}
+
+fn extend_reduced(word: &mut Vec<char>, n: usize, out: &mut Vec<String>) {
+    if word.len() == n {
+        let w: String = word.iter().collect();
+        if is_cyclically_reduced(&w) && canonical_rotation(&w) == w {
+            out.push(w);
+        }
+        return;
+    }
+    for c in LETTERS {
+        if word.last().map(|&l| inverse_letter(l)) != Some(c) {
+            word.push(c);
+            extend_reduced(word, n, out);
+            word.pop();
+        }
+    }
+}
+
+/// One representative of each nontrivial conjugacy class whose cyclically reduced
+/// length is at most `max_length`, shortest first. Each class appears once, as
+/// its canonical rotation.
+pub fn conjugacy_classes(max_length: usize) -> Vec<String> {
+    let mut out = Vec::new();
+    for n in 1..=max_length {
+        extend_reduced(&mut Vec::with_capacity(n), n, &mut out);
+    }
+    out
+}
+
+/// Representatives of the primitive conjugacy classes, up to inversion, whose
+/// words have length at most `max_length`. These are the Stern-Brocot words of the
+/// slopes p/q, in {a,b} for positive slopes and in {a,B} for negative ones.
+pub fn primitive_words(max_length: usize) -> Vec<String> {
+    fn mediants(low: &str, high: &str, max_length: usize, out: &mut Vec<String>) {
+        let mediant = format!("{}{}", low, high);
+        if mediant.len() > max_length {
+            return;
+        }
+        mediants(low, &mediant, max_length, out);
+        out.push(mediant.clone());
+        mediants(&mediant, high, max_length, out);
+    }
+
+    let mut positive = Vec::new();
+    if max_length >= 1 {
+        positive.push("a".to_string());
+        positive.push("b".to_string());
+    }
+    mediants("a", "b", max_length, &mut positive);
+    let negative: Vec<String> = positive.iter()
+        .filter(|w| w.contains('a') && w.contains('b'))
+        .map(|w| w.replace('b', "B"))
+        .collect();
+    positive.extend(negative);
+    positive
+}
+
+/// The freely reduced words of length at most `radius`, shortest first, starting
+/// with the empty word: the vertices of the ball in the Cayley graph of F_2,
+/// which is the 4-regular tree.
+pub fn ball_words(radius: usize) -> Vec<String> {
+    let mut words = vec![String::new()];
+    let mut start = 0;
+    for _ in 0..radius {
+        let end = words.len();
+        for i in start..end {
+            for c in LETTERS {
+                if words[i].chars().last().map(inverse_letter) != Some(c) {
+                    let word = format!("{}{}", words[i], c);
+                    words.push(word);
+                }
+            }
+        }
+        start = end;
+    }
+    words
+}
+
+/// The edges of the ball returned by `ball_words`, as pairs of indices joining
+/// each nonempty word to the word without its last letter.
+pub fn ball_edges(words: &[String]) -> Vec<(usize, usize)> {
+    let index: HashMap<&str, usize> = words.iter().enumerate().map(|(i, w)| (w.as_str(), i)).collect();
+    words.iter().enumerate().skip(1)
+        .map(|(i, w)| (index[&w[..w.len() - 1]], i))
+        .collect()
+}
@@ -0,0 +1,78 @@
+//! Snapshot tests for the word evaluator in `repcalc::scalar`. The traces are pinned
+//! to 50 digits and checked at several precisions with every enabled backend, so
+//! the rug and dashu backends agree with the snapshots, and so with each other.
+
+use repcalc::scalar::{self, Scalar};
+
+/// (z, word, tr re, tr im) for the generators `rho_a(z)`, `rho_b(z)`.
+const SNAPSHOTS: [((f64, f64), &str, &str, &str); 14] = [
+    ((1.0, 2.0), "a", "1.8755711004828286197118806717722518526266848706323", "-0.13329273410943602893663603911336366515808900726115"),
+    ((1.0, 2.0), "b", "-2", "-4"),
+    ((1.0, 2.0), "ab", "-2.1421565687017006775851527499989791829428628851546", "-3.6178494668562212104871253044311400400952807340035"),
+    ((1.0, 2.0), "ababb", "85.5", "13.5"),
+    ((1.0, 2.0), "aBAb", "-2", "0"),
+    ((1.0, 2.0), "abAB", "-2", "0"),
+    ((1.0, 2.0), "aabAbbaB", "-74.5", "139.5"),
+    ((1.5, 0.25), "a", "2.5489050936167653635271531343343462839769422317247", "-0.29832261762441593518056859465338837170804922902809"),
+    ((1.5, 0.25), "b", "-3", "-0.5"),
+    ((1.5, 0.25), "ab", "-3.8979382948312520290858718501648665188924256548441", "-0.18974234696756743811093539160350401343216171438904"),
+    ((1.5, 0.25), "ababb", "-31.742079207920792079207920792079207920792079207921", "-12.195792079207920792079207920792079207920792079208"),
+    ((1.5, 0.25), "aBAb", "-2", "0"),
+    ((1.5, 0.25), "abAB", "-2", "0"),
+    ((1.5, 0.25), "aabAbbaB", "138.42128712871287128712871287128712871287128712871", "13.312871287128712871287128712871287128712871287129"),
+];
+
+const PRECISIONS: [u32; 3] = [64, 128, 256];
+
+fn check<S: Scalar>(precision: u32) {
+    // the tolerance of `classify::tolerance`, relative to the size of the trace
+    let tol = 2f64.powi(-((precision / 2) as i32));
+    for ((x, y), word, re, im) in SNAPSHOTS {
+        let z = S::from_f64(precision, x, y);
+        let (a, b) = scalar::rho(precision, &z);
+        let trace = scalar::trace(&scalar::eval_word(precision, &a, &b, word).unwrap());
+        let expected = S::parse(precision, re, im).unwrap();
+        let (dx, dy) = trace.sub(&expected).to_f64();
+        let (ex, ey) = expected.to_f64();
+        assert!(
+            dx.hypot(dy) <= tol * ex.hypot(ey).max(1.0),
+            "tr {} at z = ({} {}) and precision {}: {} differs from {}", word, x, y, precision, trace, expected,
+        );
+    }
+}
+
+#[cfg(feature = "rug")]
+#[test]
+fn rug_matches_snapshots() {
+    for precision in PRECISIONS {
+        check::<rug::Complex>(precision);
+    }
+}
+
+#[cfg(feature = "dashu")]
+#[test]
+fn dashu_matches_snapshots() {
+    for precision in PRECISIONS {
+        check::<scalar::DashuComplex>(precision);
+    }
+}
+
+/// `word::slope_letters`, which `-r` uses without rug, spells the same words as the
+/// descent in `stern_brocot`.
+#[cfg(feature = "rug")]
+#[test]
+fn slope_letters_match_stern_brocot() {
+    use repcalc::budget::IterationLimit;
+    use repcalc::stern_brocot::{stern_brocot_letters, ExtendedRational};
+    use repcalc::word::slope_letters;
+    use rug::Rational;
+
+    let limit = IterationLimit(Some(1000));
+    assert_eq!(slope_letters(1, 0, limit).unwrap(), stern_brocot_letters(ExtendedRational::Infinity, limit).unwrap());
+    for p in 0..=12u64 {
+        for q in 1..=12u64 {
+            let slope = ExtendedRational::R(Rational::from((p, q)));
+            assert_eq!(slope_letters(p, q, limit).unwrap(), stern_brocot_letters(slope, limit).unwrap(), "{}/{}", p, q);
+        }
+    }
+}
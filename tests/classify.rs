@@ -0,0 +1,92 @@
+//! The classification of --classify: fixed matrices of each type, and traces on either
+//! side of the tolerance that separates the boundary cases tr = ±2 and tr real.
+#![cfg(feature = "rug")]
+
+use std::f64::consts::PI;
+
+use rug::{Complex, Float};
+
+use repcalc::classify::{classify, tolerance, Classification};
+use repcalc::matrix::{C, M};
+
+const PRECISION: u32 = 64;
+
+fn c(re: f64, im: f64) -> C {
+    Complex::with_val(PRECISION, (re, im))
+}
+
+fn m(entries: [(f64, f64); 4]) -> M<C> {
+    M(entries.map(|(re, im)| c(re, im)))
+}
+
+/// The matrix [[t, -1], [1, 0]] of trace t and determinant 1.
+fn with_trace(re: f64, im: f64) -> M<C> {
+    m([(re, im), (-1.0, 0.0), (1.0, 0.0), (0.0, 0.0)])
+}
+
+fn name(m: &M<C>) -> String {
+    classify(m, PRECISION).to_string()
+}
+
+#[test]
+fn fixed_matrices() {
+    for (matrix, expected) in [
+        (m([(1.0, 0.0), (0.0, 0.0), (0.0, 0.0), (1.0, 0.0)]), "identity"),
+        (m([(-1.0, 0.0), (0.0, 0.0), (0.0, 0.0), (-1.0, 0.0)]), "identity"),
+        (m([(1.0, 0.0), (1.0, 0.0), (0.0, 0.0), (1.0, 0.0)]), "parabolic"),
+        (m([(-1.0, 0.0), (0.0, 0.0), (0.0, 2.0), (-1.0, 0.0)]), "parabolic"),
+        (with_trace(2.0, 0.0), "parabolic"),
+        (with_trace(-2.0, 0.0), "parabolic"),
+        (with_trace(0.0, 0.0), "elliptic"),
+        (with_trace(-1.5, 0.0), "elliptic"),
+        (with_trace(2.5, 0.0), "loxodromic"),
+        (with_trace(-3.0, 0.0), "loxodromic"),
+        (with_trace(0.0, 1.5), "loxodromic"),
+        (with_trace(2.0, 0.5), "loxodromic"),
+    ] {
+        assert_eq!(name(&matrix), expected, "{:?}", matrix.0.map(|x| x.to_string()));
+    }
+}
+
+/// tr = 2 cos(angle / 2) for elliptics, and tr = 2 cosh(length / 2) for real loxodromics.
+#[test]
+fn angles_and_lengths() {
+    for (trace, angle) in [(0.0, PI), (1.0, 2.0 * PI / 3.0), (-1.0, 4.0 * PI / 3.0)] {
+        let Classification::Elliptic { angle: a } = classify(&with_trace(trace, 0.0), PRECISION) else {
+            panic!("tr = {} is not elliptic", trace)
+        };
+        assert!((a.to_f64() - angle).abs() < 1e-12, "tr = {}: angle {}", trace, a);
+    }
+    for trace in [2.5, -2.5, 10.0] {
+        let Classification::Loxodromic { translation_length: l } = classify(&with_trace(trace, 0.0), PRECISION)
+        else {
+            panic!("tr = {} is not loxodromic", trace)
+        };
+        let length = 2.0 * (f64::abs(trace) / 2.0).acosh();
+        assert!((l.to_f64() - length).abs() < 1e-12, "tr = {}: length {}", trace, l);
+    }
+}
+
+/// Traces within the tolerance of ±2 are parabolic and those just outside it are not, and
+/// an imaginary part within the tolerance still counts as real.
+#[test]
+fn tolerance_boundary() {
+    let tol = tolerance(PRECISION).to_f64();
+    assert_eq!(Float::with_val(PRECISION, tol), tolerance(PRECISION));
+    for (re, im, expected) in [
+        (2.0 + tol, 0.0, "parabolic"),
+        (2.0 - tol, 0.0, "parabolic"),
+        (-2.0 - tol, 0.0, "parabolic"),
+        (2.0, tol, "parabolic"),
+        (2.0 + 2.0 * tol, 0.0, "loxodromic"),
+        (2.0 - 2.0 * tol, 0.0, "elliptic"),
+        (-2.0 + 2.0 * tol, 0.0, "elliptic"),
+        (-2.0 - 2.0 * tol, 0.0, "loxodromic"),
+        (2.0, 2.0 * tol, "loxodromic"),
+        (1.0, tol, "elliptic"),
+        (1.0, -tol, "elliptic"),
+        (1.0, 2.0 * tol, "loxodromic"),
+    ] {
+        assert_eq!(name(&with_trace(re, im)), expected, "tr = {} + {}i", re, im);
+    }
+}
@@ -0,0 +1,308 @@
+//! Cross-checks of the algorithms built on word evaluation, run exactly over the
+//! Gaussian rationals Q(i) at parameters where the generators have entries in Q(i), so
+//! that every identity holds with equality and nothing depends on a tolerance. The
+//! floating point backends are then compared with the exact matrices.
+#![cfg(feature = "rug")]
+
+use rug::{Complex, Float, Integer, Rational};
+
+use repcalc::budget::IterationLimit;
+use repcalc::error::Error;
+use repcalc::exact::{self, GaussianRational};
+use repcalc::matrix::{C, M};
+use repcalc::rho::Generators;
+use repcalc::scalar::{self, Scalar};
+use repcalc::stern_brocot::{for_each_farey, stern_brocot_letters, stern_brocot_trace, ExtendedRational,
+                            SternBrocotCache};
+use repcalc::word::{ball_words, inverse_letter};
+
+type Q = GaussianRational;
+
+/// A two-generator family and (re, im) of its parameter.
+#[derive(Clone, Copy, Debug)]
+enum Family {
+    Z,
+    Maskit,
+    Riley,
+}
+
+/// The parameters, chosen with small heights so that long words stay cheap. Those of
+/// `Family::Z` are (t + 1/t)/2 for t = 2, 1 + i and 2 + i, where z^2 - 1 is a square, away
+/// from the branch cuts of the square roots in `scalar::rho`, where rounding picks a root.
+const PARAMETERS: [(Family, &str, &str); 7] = [
+    (Family::Z, "5/4", "0"),
+    (Family::Z, "3/4", "1/4"),
+    (Family::Z, "6/5", "2/5"),
+    (Family::Maskit, "3/2", "2"),
+    (Family::Maskit, "0", "2"),
+    (Family::Riley, "0", "2"),
+    (Family::Riley, "-1/2", "3/4"),
+];
+
+const PRECISIONS: [u32; 3] = [64, 128, 256];
+
+fn q(re: i32, im: i32) -> Q {
+    GaussianRational::new(Rational::from(re), Rational::from(im))
+}
+
+fn parameter(re: &str, im: &str) -> Q {
+    GaussianRational::parse(re, im).unwrap()
+}
+
+fn generators<S: Scalar>(family: Family, precision: u32, z: &S) -> (M<S>, M<S>) {
+    match family {
+        Family::Z => scalar::rho(precision, z),
+        Family::Maskit => scalar::maskit(precision, z),
+        Family::Riley => scalar::riley(precision, z),
+    }
+}
+
+/// The exact generators at each of `PARAMETERS`.
+fn exact_generators() -> Vec<(Family, Q, M<Q>, M<Q>)> {
+    PARAMETERS.iter()
+        .map(|&(family, re, im)| {
+            let z = parameter(re, im);
+            let (a, b) = generators(family, 0, &z);
+            (family, z, a, b)
+        })
+        .collect()
+}
+
+fn eval(a: &M<Q>, b: &M<Q>, word: &str) -> M<Q> {
+    exact::eval_word(a, b, word).unwrap()
+}
+
+fn identity() -> M<Q> {
+    scalar::identity(0)
+}
+
+fn inverse_word(word: &str) -> String {
+    word.chars().rev().map(inverse_letter).collect()
+}
+
+/// The positive slopes p/q with p, q at most `max_height`, and 1/0.
+fn slopes(max_height: u32) -> Vec<ExtendedRational> {
+    let mut slopes = vec![ExtendedRational::Infinity];
+    for p in 1..=max_height {
+        for q in 1..=max_height {
+            let slope = Rational::from((p, q));
+            if *slope.numer() == p && *slope.denom() == q {
+                slopes.push(ExtendedRational::R(slope));
+            }
+        }
+    }
+    slopes
+}
+
+#[test]
+fn parse_and_square_roots() {
+    assert_eq!(parameter("1.25", "-0.5"), GaussianRational::new(Rational::from((5, 4)), Rational::from((-1, 2))));
+    assert_eq!(parameter("-3/6", "2"), GaussianRational::new(Rational::from((-1, 2)), Rational::from(2)));
+    assert!(GaussianRational::parse("1/2.5", "0").is_err());
+    assert!(GaussianRational::parse("0.x", "0").is_err());
+    // the principal root, with positive real part or on the positive imaginary axis
+    assert_eq!(q(-3, 4).sqrt_exact(), Some(q(1, 2)));
+    assert_eq!(q(-3, -4).sqrt_exact(), Some(q(1, -2)));
+    assert_eq!(q(-4, 0).sqrt_exact(), Some(q(0, 2)));
+    assert_eq!(parameter("9/16", "0").sqrt_exact(), Some(parameter("3/4", "0")));
+    assert_eq!(q(2, 0).sqrt_exact(), None);
+    assert_eq!(q(0, 1).sqrt_exact(), None);
+    assert_eq!(q(0, 0).recip(), None);
+    assert_eq!(q(1, 1).recip(), Some(parameter("1/2", "-1/2")));
+}
+
+#[test]
+fn rho_parameters() {
+    for (family, re, im) in PARAMETERS {
+        if let Family::Z = family {
+            assert!(exact::rho_parameter(&parameter(re, im)).is_ok(), "z = {} + {} i", re, im);
+        }
+    }
+    assert!(matches!(exact::rho_parameter(&parameter("3/2", "2")), Err(Error::Input(_))));
+    assert!(matches!(exact::rho_parameter(&q(1, 0)), Err(Error::Degenerate(_))));
+    assert!(matches!(exact::rho_parameter(&q(-1, 0)), Err(Error::Degenerate(_))));
+    let (a, b) = generators(Family::Z, 0, &parameter("5/4", "0"));
+    assert!(matches!(exact::eval_word(&a, &b, "abx"), Err(Error::Input(_))));
+}
+
+#[test]
+fn determinants_and_inverses() {
+    for (family, z, a, b) in exact_generators() {
+        for word in ball_words(3) {
+            let m = eval(&a, &b, &word);
+            let inverse = eval(&a, &b, &inverse_word(&word));
+            assert_eq!(exact::det(&m), q(1, 0), "det {} in {:?} at {}", word, family, z);
+            assert!(scalar::inv_sl2(&m) == inverse, "inverse of {} in {:?} at {}", word, family, z);
+            assert!(scalar::mul(&m, &inverse) == identity(), "{} times its inverse in {:?} at {}", word, family, z);
+        }
+    }
+}
+
+#[test]
+fn products_of_words() {
+    for (family, z, a, b) in exact_generators() {
+        let words = ball_words(2);
+        for u in &words {
+            for v in &words {
+                let uv = eval(&a, &b, &format!("{}{}", u, v));
+                let product = scalar::mul(&eval(&a, &b, u), &eval(&a, &b, v));
+                assert!(uv == product, "{} {} in {:?} at {}", u, v, family, z);
+            }
+        }
+    }
+}
+
+/// The Fricke identity tr[a, b] = x^2 + y^2 + z^2 - xyz - 2 in the traces x, y, z of a,
+/// b and ab, and tr[a, b] = -2 for the families where [a, b] is parabolic.
+#[test]
+fn fricke_identity() {
+    for (family, z, a, b) in exact_generators() {
+        let [x, y, w] = [&a, &b, &scalar::mul(&a, &b)].map(scalar::trace);
+        let fricke = x.mul(&x).add(&y.mul(&y)).add(&w.mul(&w)).sub(&x.mul(&y).mul(&w)).sub(&q(2, 0));
+        let commutator = scalar::trace(&eval(&a, &b, "abAB"));
+        assert_eq!(commutator, fricke, "{:?} at {}", family, z);
+        match family {
+            Family::Z | Family::Maskit => assert_eq!(commutator, q(-2, 0), "{:?} at {}", family, z),
+            // [[1, 1], [0, 1]] and [[1, 0], [rho, 1]] have tr[a, b] = 2 + rho^2
+            Family::Riley => assert_eq!(commutator, q(2, 0).add(&z.mul(&z)), "at {}", z),
+        }
+    }
+}
+
+/// The Stern-Brocot descent, its cache and the trace recursion along it all give the
+/// matrix of the letters of the slope.
+#[test]
+fn stern_brocot_descent() {
+    let limit = IterationLimit(None);
+    for (family, z, a, b) in exact_generators() {
+        let mut cache = SternBrocotCache::new(a.clone(), b.clone());
+        let (ta, tb, tab) = (scalar::trace(&a), scalar::trace(&b), scalar::trace(&scalar::mul(&a, &b)));
+        for slope in slopes(5) {
+            let letters = stern_brocot_letters(slope.clone(), limit).unwrap();
+            let expected = eval(&a, &b, &letters);
+            let cached = cache.word(&slope, |x, y| scalar::mul(&x, &y), limit).unwrap();
+            assert!(cached == expected, "cached word of {} in {:?} at {}", slope, family, z);
+            let trace = stern_brocot_trace(&slope, ta.clone(), tb.clone(), tab.clone(),
+                                           |x, y| x.mul(y), |x, y| x.sub(y), limit).unwrap();
+            assert_eq!(trace, scalar::trace(&expected), "trace of {} in {:?} at {}", slope, family, z);
+        }
+    }
+}
+
+/// `--exact` for `Family::Z`: the words over Q(i)[z]/(f) with s = sqrt(z^2 - 1) adjoined
+/// give the exact matrices of `scalar::rho` once z and s are replaced by their values,
+/// at rational z where z^2 - 1 > 0 so that the roots agree.
+#[test]
+fn root_field_matches_rho() {
+    for (numerator, denominator) in [(5, 4), (5, 3), (13, 12)] {
+        let field = exact::Field::new(&[Integer::from(denominator), Integer::from(-numerator)]).unwrap();
+        let root_field = exact::RootField::new(&field);
+        let (a, b) = root_field.rho().unwrap();
+        let z = GaussianRational::new(Rational::from((numerator, denominator)), Rational::new());
+        let (za, zb) = scalar::rho(0, &z);
+        let s = z.mul(&z).sub(&q(1, 0)).sqrt_exact().unwrap();
+        for word in ["a", "b", "ab", "abAB", "aabAbbaB", "abbbaBAAAb"] {
+            let m = root_field.eval_word(&a, &b, word).unwrap();
+            let substituted = M(m.0.map(|x| x.0.0[0].add(&x.1.0[0].mul(&s))));
+            assert!(substituted == eval(&za, &zb, word), "{} at z = {}", word, z);
+        }
+    }
+    let field = exact::Field::new(&[Integer::from(1), Integer::from(-1)]).unwrap();
+    assert!(matches!(exact::RootField::new(&field).rho(), Err(Error::Degenerate(_))));
+    // 1 / (z^2 - 1) in Q(i)[z]/(z^3 - 2)
+    let field = exact::Field::new(&[Integer::from(1), Integer::from(0), Integer::from(0), Integer::from(-2)]).unwrap();
+    let z = field.z();
+    let square = field.sub(&field.mul(&z, &z), &field.integer(1, 0));
+    assert_eq!(field.mul(&square, &field.inv(&square).unwrap()), field.integer(1, 0));
+    assert_eq!(field.inv(&field.integer(0, 0)), None);
+}
+
+/// The walk down the Farey tree, with matrices and with the trace recursion
+/// tr UV = tr U tr V - tr U^-1 V, gives the matrix of the letters of each slope.
+#[test]
+fn farey_recursion() {
+    let limit = IterationLimit(None);
+    for (family, z, a, b) in exact_generators() {
+        let ab = scalar::mul(&a, &b);
+        let mut matrices = Vec::new();
+        for_each_farey(5, a.clone(), b.clone(), ab.clone(), |u, v, _| scalar::mul(u, v), |slope, m| {
+            matrices.push((slope.clone(), m.clone()));
+        });
+        let mut traces = Vec::new();
+        let (ta, tb, tab) = (scalar::trace(&a), scalar::trace(&b), scalar::trace(&ab));
+        for_each_farey(5, ta, tb, tab, |u, v, w| u.mul(v).sub(w), |slope, trace| {
+            traces.push((slope.clone(), trace.clone()));
+        });
+        assert_eq!(matrices.len(), slopes(5).len() - 1, "{:?} at {}", family, z);
+        for ((slope, m), (trace_slope, trace)) in matrices.iter().zip(&traces) {
+            let expected = eval(&a, &b, &stern_brocot_letters(slope.clone(), limit).unwrap());
+            assert!(slope == trace_slope, "slope {} against {}", slope, trace_slope);
+            assert!(*m == expected, "matrix of {} in {:?} at {}", slope, family, z);
+            assert_eq!(*trace, scalar::trace(&expected), "trace of {} in {:?} at {}", slope, family, z);
+        }
+    }
+}
+
+/// The largest entry of |m - exact| relative to the largest entry of the exact matrix.
+fn deviation(m: &M<C>, exact: &M<Q>, precision: u32) -> f64 {
+    let expected = exact::to_complex(exact, precision);
+    let largest = expected.0.iter().map(|e| Float::with_val(precision, e.abs_ref()).to_f64()).fold(1.0, f64::max);
+    let error = m.0.iter().zip(&expected.0)
+        .map(|(x, e)| Float::with_val(precision, (x.clone() - e).abs_ref()).to_f64())
+        .fold(0.0, f64::max);
+    error / largest
+}
+
+/// `Generators::eval`, which multiplies out runs by squaring in a balanced tree, and the
+/// generic evaluator in rug agree with the exact matrices to the tolerance of
+/// `classify::tolerance`.
+#[test]
+fn rug_matches_exact() {
+    for precision in PRECISIONS {
+        let tol = 2f64.powi(-((precision / 2) as i32));
+        for (family, z, a, b) in exact_generators() {
+            let zc = z.to_complex(precision);
+            let gens = match family {
+                Family::Z => Generators::new(precision, zc.clone()),
+                _ => {
+                    let (a, b) = generators(family, precision, &zc);
+                    Generators::from_matrices(precision, a, b)
+                }
+            };
+            let (ga, gb) = generators::<Complex>(family, precision, &zc);
+            for word in ["a", "b", "ab", "abAB", "aabAbbaB", "abbbaBAAAb", "bbbbbbbbaaaaaaaa"] {
+                let expected = eval(&a, &b, word);
+                let evaluated = gens.eval(word);
+                let generic = scalar::eval_word(precision, &ga, &gb, word).unwrap();
+                for (name, m) in [("Generators::eval", &evaluated), ("scalar::eval_word", &generic)] {
+                    let error = deviation(m, &expected, precision);
+                    assert!(error <= tol, "{} of {} in {:?} at {} and precision {}: off by {}",
+                            name, word, family, z, precision, error);
+                }
+            }
+        }
+    }
+}
+
+/// The dashu backend agrees with the exact matrices to double precision, at a parameter
+/// rounded to it.
+#[cfg(feature = "dashu")]
+#[test]
+fn dashu_matches_exact() {
+    for precision in PRECISIONS {
+        for (family, z, a, b) in exact_generators() {
+            let (re, im) = z.to_f64();
+            let (da, db) = generators(family, precision, &scalar::DashuComplex::from_f64(precision, re, im));
+            for word in ["a", "b", "ab", "abAB", "aabAbbaB"] {
+                let expected = eval(&a, &b, word);
+                let m = scalar::eval_word(precision, &da, &db, word).unwrap();
+                let largest = expected.0.iter().map(|e| { let (x, y) = e.to_f64(); x.hypot(y) }).fold(1.0, f64::max);
+                for (x, e) in m.0.iter().zip(&expected.0) {
+                    let ((x, y), (ex, ey)) = (x.to_f64(), e.to_f64());
+                    assert!((x - ex).hypot(y - ey) <= 1e-12 * largest,
+                            "{} in {:?} at {} and precision {}: {} differs from {}", word, family, z, precision, x, e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! The results files of `--results` and `merge`: the JSON of the current schema reads
+//! back as written, and the text files of the older schemas are converted to it.
+#![cfg(feature = "rug")]
+
+use repcalc::context::RunContext;
+use repcalc::results::{self, Body, Results, SCHEMA_VERSION};
+
+const GENERATORS: &str = "1 0 1 0 0 0 1 0 1 0 2 0 0 0 1 0";
+
+/// A verify run of shard 1 of 2 in the text schema `version`.
+fn text_file(version: u32) -> String {
+    let written_by = if version >= 2 { "written_by repcalc 0.1.0\n" } else { "" };
+    format!("# repcalc verify results\nformat {}\n{}search verify\nprecision 64\ngenerators {}\nmax_length 6\n\
+             shards 2 1\nnodes 10 finished\ncheck no_elliptic 8 1 aB\ncheck determinant 8 0 -\n",
+            version, written_by, GENERATORS)
+}
+
+fn write(results: &Results) -> String {
+    let context = RunContext { family: "z".to_string(), parameter: "-".to_string(), precision: Some(64) };
+    let mut out = Vec::new();
+    results.write(&mut out, &context).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn assert_verify_run(results: &Results) {
+    assert_eq!((results.precision, results.generators.as_str(), results.max_length), (64, GENERATORS, 6));
+    assert_eq!((results.shard_count, results.shards.as_slice(), results.nodes, results.finished), (2, &[1][..], 10, true));
+    let Body::Verify(checks) = &results.body else { panic!("not a verify run") };
+    let checks: Vec<String> = checks.iter().map(|c| format!("{} {}", c.check, c)).collect();
+    assert_eq!(checks, ["no_elliptic fail (1 of 8 words, first aB)", "determinant pass (8 words)"]);
+}
+
+#[test]
+fn text_schemas_convert_to_json() {
+    for version in 1..=2 {
+        let old = results::read(&text_file(version)).unwrap();
+        assert_verify_run(&old);
+        let json = write(&old);
+        assert!(json.contains(&format!("\"schema\": {}", SCHEMA_VERSION)), "{}", json);
+        assert_verify_run(&results::read(&json).unwrap());
+    }
+}
+
+#[test]
+fn newer_schemas_are_rejected() {
+    let json = write(&results::read(&text_file(2)).unwrap())
+        .replace(&format!("\"schema\": {}", SCHEMA_VERSION), &format!("\"schema\": {}", SCHEMA_VERSION + 1));
+    let e = results::read(&json).err().unwrap();
+    assert!(e.contains("newer than this repcalc reads"), "{}", e);
+    let e = results::read(&text_file(3)).err().unwrap();
+    assert!(e.contains("JSON"), "{}", e);
+}
@@ -0,0 +1,97 @@
+//! The descents down the Stern-Brocot tree: the words of slopes given as p/q and as
+//! continued fractions agree, and the trace recursions agree with multiplying out the
+//! words they follow.
+#![cfg(feature = "rug")]
+
+use rug::{Complex, Float, Rational};
+
+use repcalc::budget::IterationLimit;
+use repcalc::matrix::C;
+use repcalc::rho::Generators;
+use repcalc::stern_brocot::{continued_fraction_letters, convergent, for_each_farey, stern_brocot_letters,
+                            stern_brocot_trace, ExtendedRational};
+use repcalc::word::slope_letters;
+
+fn slope(p: u64, q: u64) -> ExtendedRational {
+    if q == 0 { ExtendedRational::Infinity } else { ExtendedRational::R(Rational::from((p, q))) }
+}
+
+/// Both continued fractions of p/q: the one from Euclid's algorithm, and the one whose
+/// last term is 1, when there is one.
+fn continued_fractions(mut p: u64, mut q: u64) -> Vec<Vec<u64>> {
+    let mut terms = Vec::new();
+    while q != 0 {
+        terms.push(p / q);
+        (p, q) = (q, p % q);
+    }
+    let mut expansions = vec![terms.clone()];
+    // [..., t] = [..., t - 1, 1], as long as t - 1 is a valid term: positive, or the first
+    let n = terms.len();
+    if terms[n - 1] > 1 || (n == 1 && terms[0] == 1) {
+        terms[n - 1] -= 1;
+        terms.push(1);
+        expansions.push(terms);
+    }
+    expansions
+}
+
+#[test]
+fn continued_fractions_match_slopes() {
+    let limit = IterationLimit(Some(10_000));
+    assert_eq!(continued_fraction_letters(&[], limit).unwrap(), stern_brocot_letters(slope(1, 0), limit).unwrap());
+    for p in 0..=12u64 {
+        for q in 1..=12u64 {
+            let letters = stern_brocot_letters(slope(p, q), limit).unwrap();
+            for terms in continued_fractions(p, q) {
+                assert!(convergent(&terms) == slope(p, q), "{:?} is not {}/{}", terms, p, q);
+                assert_eq!(continued_fraction_letters(&terms, limit).unwrap(), letters, "{:?} for {}/{}", terms, p, q);
+            }
+        }
+    }
+}
+
+/// The words of slopes far down the tree, whose descents are one long run, are built in
+/// time linear in their length: copying the word at every step would take minutes.
+#[test]
+fn long_runs() {
+    let limit = IterationLimit(Some(10_000_000));
+    let n = 1_000_000;
+    for (p, q, terms) in [(1, n, vec![0, n]), (n, 1, vec![n]), (n + 1, n, vec![1, n])] {
+        let letters = stern_brocot_letters(slope(p, q), limit).unwrap();
+        assert_eq!(letters.len() as u64, p + q, "{}/{}", p, q);
+        assert!(continued_fraction_letters(&terms, limit).unwrap() == letters, "{:?}", terms);
+        assert!(slope_letters(p, q, limit).unwrap() == letters, "{}/{}", p, q);
+    }
+    let error = stern_brocot_letters(slope(1, n), IterationLimit(Some(1000))).unwrap_err();
+    assert_eq!(error.iterations, 1000);
+    assert!(continued_fraction_letters(&[0, n], IterationLimit(Some(1000))).is_err());
+}
+
+fn close(x: &C, y: &C) -> bool {
+    let scale = Float::with_val(64, x.abs_ref()).to_f64().max(1.0);
+    Float::with_val(64, (x.clone() - y).abs_ref()).to_f64() <= 1e-9 * scale
+}
+
+/// At a z where no trace is exact, the traces of both recursions agree with the trace of
+/// the matrix of the word, multiplied out, for every slope of height at most 8.
+#[test]
+fn trace_recursions_match_words() {
+    let limit = IterationLimit(Some(10_000));
+    let gens = Generators::new(64, Complex::with_val(64, (1.5, 0.3)));
+    let (ta, tb, tab) = (gens.a.trace(), gens.b.trace(), gens.eval("ab").trace());
+    let mut slopes = vec![slope(1, 0)];
+    slopes.extend((0..=8u64).flat_map(|p| (1..=8u64).map(move |q| slope(p, q))));
+    for slope in &slopes {
+        let expected = gens.eval(&stern_brocot_letters(slope.clone(), limit).unwrap()).trace();
+        let trace = stern_brocot_trace(slope, ta.clone(), tb.clone(), tab.clone(),
+                                       |x, y| C::with_val(64, x * y), |x, y| C::with_val(64, x - y), limit).unwrap();
+        assert!(close(&trace, &expected), "trace of {}: {} against {}", slope, trace, expected);
+    }
+    let mut visited = 0;
+    for_each_farey(8, ta, tb, tab, |u, v, w| C::with_val(64, u * v) - w, |slope, trace| {
+        let expected = gens.eval(&stern_brocot_letters(slope.clone(), limit).unwrap()).trace();
+        assert!(close(trace, &expected), "Farey trace of {}: {} against {}", slope, trace, expected);
+        visited += 1;
+    });
+    assert_eq!(visited, 43, "the slopes p/q with 1 <= p, q <= 8 in lowest terms");
+}
@@ -0,0 +1,160 @@
+//! The words of the free group F_2 = <a, b> in `repcalc::word`: free and cyclic
+//! reduction, which the searches and --reduce rely on, and the representatives of the
+//! conjugacy classes and primitive elements that the class enumerations print.
+
+use std::collections::HashSet;
+
+use repcalc::word::{canonical_rotation, conjugacy_classes, cyclic_reduce, free_reduce, inverse_letter,
+                    is_cyclically_reduced, primitive_words};
+
+/// Free reduction cancels adjacent inverse pairs until none remain, and cyclic reduction
+/// then cancels the first letter against the last.
+#[test]
+fn reductions() {
+    for (word, free, cyclic) in [
+        ("", "", ""),
+        ("a", "a", "a"),
+        ("aA", "", ""),
+        ("aAbB", "", ""),
+        ("abBA", "", ""),
+        ("abA", "abA", "b"),
+        ("BabAb", "BabAb", "b"),
+        ("aBAb", "aBAb", "aBAb"),
+        ("abAB", "abAB", "abAB"),
+        ("aabBA", "a", "a"),
+        ("bAaaBB", "baBB", "aB"),
+    ] {
+        assert_eq!(free_reduce(word), free, "free reduction of {}", word);
+        assert_eq!(cyclic_reduce(word), cyclic, "cyclic reduction of {}", word);
+        assert!(is_cyclically_reduced(&cyclic_reduce(word)), "{}", word);
+    }
+}
+
+/// A word is cyclically reduced unless its first letter is the inverse of its last, and a
+/// single letter is.
+#[test]
+fn cyclically_reduced() {
+    for word in ["", "a", "B", "ab", "aB", "aBAb", "aab"] {
+        assert!(is_cyclically_reduced(word), "{}", word);
+    }
+    for word in ["aA", "abA", "Bab", "Baab"] {
+        assert!(!is_cyclically_reduced(word), "{}", word);
+    }
+}
+
+#[test]
+fn canonical_rotations() {
+    for (word, rotation) in [("", ""), ("a", "a"), ("ba", "ab"), ("Bab", "Bab"), ("bAA", "AAb"), ("abab", "abab")] {
+        assert_eq!(canonical_rotation(word), rotation, "{}", word);
+    }
+}
+
+fn gcd(mut x: u64, mut y: u64) -> u64 {
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    x
+}
+
+/// The number of conjugacy classes of cyclically reduced length n in F_2, by Burnside's
+/// lemma over the rotations, with 3^d + 2 + (-1)^d cyclically reduced words of length d.
+fn class_count(n: u64) -> u64 {
+    let cyclically_reduced = |d: u64| 3u64.pow(d as u32) + if d % 2 == 0 { 3 } else { 1 };
+    (0..n).map(|k| cyclically_reduced(gcd(k, n))).sum::<u64>() / n
+}
+
+/// Each class appears once, as the canonical rotation of a cyclically reduced word, and
+/// the classes of each length are all there.
+#[test]
+fn conjugacy_class_counts() {
+    let expected = [4, 8, 12, 26, 52, 132];
+    let classes = conjugacy_classes(expected.len());
+    for (n, &count) in (1..).zip(&expected) {
+        assert_eq!(class_count(n), count as u64, "Burnside count for length {}", n);
+        let of_length = classes.iter().filter(|w| w.len() as u64 == n).count();
+        assert_eq!(of_length, count, "classes of length {}", n);
+    }
+    assert_eq!(classes.len(), expected.iter().sum::<usize>());
+    assert!(classes.windows(2).all(|w| w[0].len() <= w[1].len()), "not shortest first");
+    let mut seen = HashSet::new();
+    for word in &classes {
+        assert!(is_cyclically_reduced(word) && canonical_rotation(word) == *word, "{}", word);
+        assert!(seen.insert(word), "{} twice", word);
+    }
+    assert!(conjugacy_classes(0).is_empty());
+}
+
+/// The exponent sums of a and b in a word.
+fn exponent_sums(word: &str) -> (i64, i64) {
+    word.chars().fold((0, 0), |(a, b), c| match c {
+        'a' => (a + 1, b),
+        'A' => (a - 1, b),
+        'b' => (a, b + 1),
+        _ => (a, b - 1),
+    })
+}
+
+/// The canonical rotation of a word or of its inverse, whichever is less: the same for
+/// two words exactly when they are conjugate up to inversion.
+fn class_up_to_inversion(word: &str) -> String {
+    let inverse: String = word.chars().rev().map(inverse_letter).collect();
+    canonical_rotation(word).min(canonical_rotation(&inverse))
+}
+
+/// None of the primitive words is a proper power, as the exponent sums of a primitive
+/// element are coprime, and no class comes twice, even up to inversion.
+#[test]
+fn primitive_words_are_distinct() {
+    let words = primitive_words(12);
+    let mut seen = HashSet::new();
+    for word in &words {
+        let (a, b) = exponent_sums(word);
+        assert_eq!(gcd(a.unsigned_abs(), b.unsigned_abs()), 1, "{} has exponent sums ({}, {})", word, a, b);
+        assert!(seen.insert(class_up_to_inversion(word)), "the class of {} twice", word);
+    }
+    assert!(words.iter().all(|w| w.len() <= 12));
+    assert!(primitive_words(0).is_empty());
+}
+
+/// The positive primitive words are the Stern-Brocot words of the slopes p/q with
+/// p + q at most the bound, except that ab stands for 1/1, whose word is a; the negative
+/// ones are the same words with b inverted.
+#[cfg(feature = "rug")]
+#[test]
+fn primitive_words_match_stern_brocot() {
+    use repcalc::budget::IterationLimit;
+    use repcalc::stern_brocot::{stern_brocot_letters, ExtendedRational};
+    use rug::Rational;
+
+    let limit = IterationLimit(Some(1000));
+    for max_length in 0..=9u64 {
+        let mut positive = Vec::new();
+        if max_length >= 1 {
+            positive.push(stern_brocot_letters(ExtendedRational::R(Rational::new()), limit).unwrap());
+            positive.push(stern_brocot_letters(ExtendedRational::Infinity, limit).unwrap());
+        }
+        if max_length >= 2 {
+            positive.push("ab".to_string());
+        }
+        for p in 1..max_length {
+            for q in 1..=max_length - p {
+                if p == q || gcd(p, q) != 1 {
+                    continue;
+                }
+                let letters = stern_brocot_letters(ExtendedRational::R(Rational::from((p, q))), limit).unwrap();
+                assert_eq!(letters.len() as u64, p + q, "{}/{}", p, q);
+                positive.push(letters);
+            }
+        }
+        let mut negative: Vec<String> = positive.iter().filter(|w| w.len() > 1).map(|w| w.replace('b', "B")).collect();
+        positive.sort();
+        negative.sort();
+
+        let (mut found, mut found_negative): (Vec<String>, Vec<String>) =
+            primitive_words(max_length as usize).into_iter().partition(|w| !w.contains('B'));
+        found.sort();
+        found_negative.sort();
+        assert_eq!(found, positive, "positive words of length at most {}", max_length);
+        assert_eq!(found_negative, negative, "negative words of length at most {}", max_length);
+    }
+}